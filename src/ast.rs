@@ -1,3 +1,7 @@
+use crate::bigint::BigInt;
+use crate::error::SourceLocation;
+use crate::signature::{ParamKind, ParamSpec};
+
 // 核心的抽象语法树节点：一切皆 Expression
 #[derive(Debug, Clone)]
 pub enum Expression {
@@ -10,7 +14,9 @@ pub enum Expression {
 
     // 新增：列表和字典字面量，现在它们是顶层表达式
     ListLiteral(Vec<Expression>),              // 列表字面量 [1, 2+3]
+    TupleLiteral(Vec<Expression>),             // 元组字面量 (1, 2)、(1,) 和 ()
     MapLiteral(Vec<(Expression, Expression)>), // 字典字面量 {k: v, ...}
+    SetLiteral(Vec<Expression>),               // 集合字面量 #{1, 2}
 
     // ----------------------------------------------------
     // II. 运算表达式 (Operations)
@@ -25,6 +31,7 @@ pub enum Expression {
         left: Box<Expression>,
         op: BinaryOperator,
         right: Box<Expression>,
+        location: Option<SourceLocation>, // 运算符的位置，供 `--check` 报告错误
     },
 
     // ----------------------------------------------------
@@ -32,20 +39,62 @@ pub enum Expression {
     // ----------------------------------------------------
     // 函数定义 (FunctionDefinition)
     FunctionDef {
-        params: Vec<String>,
+        name: Option<String>, // `let name = fun...` 时记录的函数名，用于错误信息
+        params: Vec<Param>,
         body: Block,
+        is_generator: bool,                  // 函数体中包含 yield 时为 true
+        doc: Option<String>,                 // 紧邻其前的 `##` 文档注释，供 help() 显示
+        return_type: Option<TypeAnnotation>, // `-> string`，只由 `--check` 检查
     },
 
-    // 新增: Let 表达式用于变量声明
+    // Yield 表达式：挂起当前生成器，`yield` 单独使用时产出 nil
+    Yield(Option<Box<Expression>>),
+
+    // `defer expr`：只能直接出现在块中，expr 在块退出时 (正常结束或出错) 求值，后注册的先执行
+    Defer(Box<Expression>),
+
+    // 新增: Let 表达式用于变量声明；`const` 声明的变量不能再被赋值
     Let {
         identifier: String,
         value: Box<Expression>,
+        constant: bool,
+        doc: Option<String>,                // 紧邻 let/const 之前的 `##` 文档注释
+        annotation: Option<TypeAnnotation>, // `let x: number = ...`，只由 `--check` 检查
+    },
+
+    // 解构声明 `let (x, y) = point;`：右侧的元组或列表按位置绑定到各个变量
+    LetDestructure {
+        identifiers: Vec<String>,
+        value: Box<Expression>,
+        constant: bool,
+    },
+
+    // 枚举声明 `enum Shape { Circle(r), Rect(w, h), Empty }`：声明常量 Shape，
+    // 带字段的变体是它的构造函数，不带字段的变体就是值本身
+    EnumDef {
+        name: String,
+        variants: Vec<EnumVariantDef>,
+    },
+
+    // 模式匹配 `match value { pattern [if guard] => expr, ... }`：按顺序取第一个匹配的分支
+    Match {
+        subject: Box<Expression>,
+        arms: Vec<MatchArm>,
     },
 
     // 赋值 (AssignmentExpression)
     Assignment {
         lvalue: LValue,
         value: Box<Expression>,
+        location: Option<SourceLocation>, // `=` 的位置
+    },
+
+    // 复合赋值 e.g. a += 1，目标与索引只求值一次
+    CompoundAssignment {
+        lvalue: LValue,
+        op: BinaryOperator,
+        value: Box<Expression>,
+        location: Option<SourceLocation>, // 运算符的位置
     },
 
     // If Expression
@@ -75,14 +124,17 @@ pub enum Expression {
     // ----------------------------------------------------
     // 函数调用 (FunctionCall)
     Call {
-        callee: Box<Expression>, // 被调用的函数表达式 (e.g., f, obj.method)
-        args: Vec<Expression>,   // 参数列表
+        callee: Box<Expression>,          // 被调用的函数表达式 (e.g., f, obj.method)
+        args: Vec<Argument>,              // 参数列表
+        optional: bool,                   // `f?.(x)`: callee 为 nil 时整条链短路为 nil
+        location: Option<SourceLocation>, // `(` 的位置
     },
 
     // 列表/字典/属性访问 (Accessor 规则)
     Accessor {
         target: Box<Expression>, // 目标对象
         access: AccessType,      // 访问类型 (Index or Dot)
+        optional: bool,          // `a?.b` / `a?[k]`: target 为 nil 时整条链短路为 nil
     },
 }
 
@@ -90,7 +142,10 @@ pub enum Expression {
 #[derive(Debug, Clone)]
 pub enum LiteralValue {
     Number(f64),
+    Integer(i64),
+    BigInteger(BigInt),
     String(String),
+    Bytes(Vec<u8>),
     Boolean(bool),
     Nil,
 }
@@ -115,12 +170,95 @@ pub enum LValue {
         target: Box<Expression>,
         property_name: String, // '.' 后的标识符
     },
+    SliceAccess {
+        // 列表切片赋值 e.g. arr[1:3] = [7, 8, 9]
+        target: Box<Expression>,
+        bounds: SliceBounds,
+    },
+}
+
+// 辅助结构：切片 `[start:end:step]` 的三个部分，省略的部分为 None
+#[derive(Debug, Clone)]
+pub struct SliceBounds {
+    pub start: Option<Box<Expression>>,
+    pub end: Option<Box<Expression>>,
+    pub step: Option<Box<Expression>>,
+}
+
+// 辅助结构：枚举的一个变体；fields 为 None 表示不带字段的变体 (如 `Empty`)
+#[derive(Debug, Clone)]
+pub struct EnumVariantDef {
+    pub name: String,
+    pub fields: Option<Vec<String>>,
+}
+
+// 辅助结构：match 的一个分支
+#[derive(Debug, Clone)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>, // `pattern if guard => ...`，在模式绑定的变量可见时求值
+    pub body: Expression,
+}
+
+// 辅助结构：match 分支的模式
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Wildcard,              // `_`，匹配任何值
+    Binding(String),       // `x`，匹配任何值并绑定到 x
+    Literal(LiteralValue), // `1`, `"a"`, `true`, `nil`，与值用 == 比较
+    Variant {
+        // `Shape.Circle(r)`；不带括号的 `Shape.Circle` 只检查变体，不看字段
+        enum_name: String,
+        variant: String,
+        fields: Option<Vec<Pattern>>,
+    },
+}
+
+// 辅助结构：函数参数 `x`, `y = 10` 或 `...rest`
+#[derive(Debug, Clone)]
+pub struct Param {
+    pub name: String,
+    pub default: Option<Expression>, // 默认值，在调用时于函数作用域中求值
+    pub rest: bool,                  // `...rest` 收集剩余的位置参数
+    pub annotation: Option<TypeAnnotation>, // `x: number`，只由 `--check` 检查
+}
+
+// 辅助结构：类型注解，如 `number`；解释器忽略它，`--check` 用它检查类型
+#[derive(Debug, Clone)]
+pub struct TypeAnnotation {
+    pub name: String,
+    pub location: Option<SourceLocation>, // 类型名的位置
+}
+
+impl Param {
+    pub fn spec(&self) -> ParamSpec<'_> {
+        let kind = if self.rest {
+            ParamKind::Rest
+        } else if self.default.is_some() {
+            ParamKind::Optional
+        } else {
+            ParamKind::Required
+        };
+        ParamSpec {
+            name: &self.name,
+            kind,
+        }
+    }
+}
+
+// 辅助结构：调用参数
+#[derive(Debug, Clone)]
+pub enum Argument {
+    Positional(Expression),                      // f(x)
+    Spread(Expression),                          // f(...list)
+    Keyword { name: String, value: Expression }, // f(sep: ",")
 }
 
 // 辅助结构：访问类型
 #[derive(Debug, Clone)]
 pub enum AccessType {
     Index(Box<Expression>), // 索引访问 [TermExpression]
+    Slice(SliceBounds),     // 切片访问 [start:end:step]
     Dot(String),            // 点访问 .Identifier
 }
 
@@ -138,6 +276,7 @@ pub enum BinaryOperator {
     Sub,
     Mul,
     Div,
+    FloorDiv, // 整除 //，向零取整
     Mod,
     // 位运算
     ShL,
@@ -152,6 +291,39 @@ pub enum BinaryOperator {
     Gte,
     Eq,
     Neq,
+    In, // 成员检查：元素在列表中、键在映射中、子串在字符串中、值在 range 中
+    Is, // 同一性比较：是否为同一个对象
     And,
     Or,
+    NilCoalesce, // ??
+}
+
+impl BinaryOperator {
+    /// The operator as written in source code.
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            BinaryOperator::Add => "+",
+            BinaryOperator::Sub => "-",
+            BinaryOperator::Mul => "*",
+            BinaryOperator::Div => "/",
+            BinaryOperator::FloorDiv => "//",
+            BinaryOperator::Mod => "%",
+            BinaryOperator::ShL => "<<",
+            BinaryOperator::ShR => ">>",
+            BinaryOperator::BitAnd => "&",
+            BinaryOperator::BitOr => "|",
+            BinaryOperator::BitXor => "^",
+            BinaryOperator::Lt => "<",
+            BinaryOperator::Lte => "<=",
+            BinaryOperator::Gt => ">",
+            BinaryOperator::Gte => ">=",
+            BinaryOperator::Eq => "==",
+            BinaryOperator::Neq => "!=",
+            BinaryOperator::In => "in",
+            BinaryOperator::Is => "is",
+            BinaryOperator::And => "&&",
+            BinaryOperator::Or => "||",
+            BinaryOperator::NilCoalesce => "??",
+        }
+    }
 }
@@ -0,0 +1,467 @@
+//! Arbitrary-precision integers, the payload of `Object::BigInt`.
+//!
+//! A magnitude is a little-endian vector of base-2^32 limbs with no high zero limbs, so every
+//! value has exactly one representation (zero has no limbs and is never negative). Only the
+//! operations the interpreter needs are implemented, with schoolbook algorithms: script numbers
+//! are rarely more than a few hundred digits long.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::ops::{Add, Mul, Neg, Sub};
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        BigInt {
+            negative: false,
+            limbs: Vec::new(),
+        }
+    }
+
+    fn from_parts(negative: bool, mut limbs: Vec<u32>) -> Self {
+        trim(&mut limbs);
+        BigInt {
+            negative: negative && !limbs.is_empty(),
+            limbs,
+        }
+    }
+
+    pub fn from_u64(n: u64) -> Self {
+        BigInt::from_parts(false, vec![n as u32, (n >> 32) as u32])
+    }
+
+    pub fn from_i64(n: i64) -> Self {
+        let mut big = BigInt::from_u64(n.unsigned_abs());
+        big.negative = n < 0;
+        big
+    }
+
+    /// The integer part of a finite float, exactly.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+        let (mantissa, exponent) = decompose_f64(f);
+        let magnitude = if exponent >= 0 {
+            BigInt::from_u64(mantissa).shl(exponent as u32)
+        } else if exponent > -64 {
+            BigInt::from_u64(mantissa >> -exponent)
+        } else {
+            BigInt::zero()
+        };
+        Some(if f < 0.0 { -&magnitude } else { magnitude })
+    }
+
+    /// Parses an optionally signed run of decimal digits.
+    pub fn parse(s: &str) -> Option<Self> {
+        let (negative, digits) = match s.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, s.strip_prefix('+').unwrap_or(s)),
+        };
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let mut limbs = Vec::new();
+        for chunk in digits.as_bytes().chunks(9) {
+            let value: u32 = std::str::from_utf8(chunk).ok()?.parse().ok()?;
+            mul_small_add(&mut limbs, 10u32.pow(chunk.len() as u32), value);
+        }
+        Some(BigInt::from_parts(negative, limbs))
+    }
+
+    /// Parses unsigned digits in a radix from 2 to 36.
+    pub fn parse_radix(digits: &str, radix: u32) -> Option<Self> {
+        if digits.is_empty() {
+            return None;
+        }
+        let mut limbs = Vec::new();
+        for c in digits.chars() {
+            mul_small_add(&mut limbs, radix, c.to_digit(radix)?);
+        }
+        Some(BigInt::from_parts(false, limbs))
+    }
+
+    pub fn pow10(exponent: u32) -> Self {
+        let mut limbs = vec![1];
+        for _ in 0..exponent {
+            mul_small_add(&mut limbs, 10, 0);
+        }
+        BigInt::from_parts(false, limbs)
+    }
+
+    pub fn pow5(exponent: u32) -> Self {
+        let mut limbs = vec![1];
+        for _ in 0..exponent {
+            mul_small_add(&mut limbs, 5, 0);
+        }
+        BigInt::from_parts(false, limbs)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.limbs.first().is_some_and(|limb| limb & 1 == 1)
+    }
+
+    pub fn abs(&self) -> Self {
+        BigInt::from_parts(false, self.limbs.clone())
+    }
+
+    pub fn to_i64(&self) -> Option<i64> {
+        if self.limbs.len() > 2 {
+            return None;
+        }
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0u64, |acc, &limb| (acc << 32) | limb as u64);
+        if self.negative {
+            0i64.checked_sub_unsigned(magnitude)
+        } else {
+            i64::try_from(magnitude).ok()
+        }
+    }
+
+    /// The nearest float (or an infinity); not necessarily correctly rounded.
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self
+            .limbs
+            .iter()
+            .rev()
+            .fold(0.0, |acc, &limb| acc * 4_294_967_296.0 + limb as f64);
+        if self.negative {
+            -magnitude
+        } else {
+            magnitude
+        }
+    }
+
+    /// The number of decimal digits in the magnitude (1 for zero).
+    pub fn decimal_digits(&self) -> usize {
+        self.abs().to_string().len()
+    }
+
+    /// Division truncating toward zero, with a remainder of the dividend's sign.
+    /// `None` when dividing by zero.
+    pub fn div_rem(&self, other: &BigInt) -> Option<(BigInt, BigInt)> {
+        if other.is_zero() {
+            return None;
+        }
+        let (quotient, remainder) = div_rem_magnitude(&self.limbs, &other.limbs);
+        Some((
+            BigInt::from_parts(self.negative != other.negative, quotient),
+            BigInt::from_parts(self.negative, remainder),
+        ))
+    }
+
+    /// Multiplies by `2^bits`.
+    pub fn shl(&self, bits: u32) -> BigInt {
+        let (whole, partial) = ((bits / 32) as usize, bits % 32);
+        let mut limbs = vec![0; whole];
+        let mut carry = 0u32;
+        for &limb in &self.limbs {
+            if partial == 0 {
+                limbs.push(limb);
+            } else {
+                limbs.push((limb << partial) | carry);
+                carry = limb >> (32 - partial);
+            }
+        }
+        limbs.push(carry);
+        BigInt::from_parts(self.negative, limbs)
+    }
+
+    /// Divides by `2^bits`, rounding toward negative infinity like `>>` on an `i64`.
+    pub fn shr(&self, bits: u32) -> BigInt {
+        let divisor = BigInt::from_u64(1).shl(bits);
+        let (quotient, remainder) = self.div_rem(&divisor).unwrap();
+        if self.negative && !remainder.is_zero() {
+            &quotient - &BigInt::from_u64(1)
+        } else {
+            quotient
+        }
+    }
+}
+
+fn trim(limbs: &mut Vec<u32>) {
+    while limbs.last() == Some(&0) {
+        limbs.pop();
+    }
+}
+
+/// Splits a finite float into `mantissa * 2^exponent`, ignoring the sign.
+pub(crate) fn decompose_f64(f: f64) -> (u64, i64) {
+    let bits = f.to_bits();
+    let biased = ((bits >> 52) & 0x7ff) as i64;
+    let fraction = bits & ((1 << 52) - 1);
+    if biased == 0 {
+        (fraction, -1074) // Subnormal
+    } else {
+        (fraction | (1 << 52), biased - 1075)
+    }
+}
+
+/// `limbs = limbs * factor + addend`, in place.
+fn mul_small_add(limbs: &mut Vec<u32>, factor: u32, addend: u32) {
+    let mut carry = addend as u64;
+    for limb in limbs.iter_mut() {
+        let product = *limb as u64 * factor as u64 + carry;
+        *limb = product as u32;
+        carry = product >> 32;
+    }
+    if carry > 0 {
+        limbs.push(carry as u32);
+    }
+}
+
+fn div_rem_small(limbs: &[u32], divisor: u32) -> (Vec<u32>, u32) {
+    let mut quotient = vec![0; limbs.len()];
+    let mut remainder = 0u64;
+    for (i, &limb) in limbs.iter().enumerate().rev() {
+        let current = (remainder << 32) | limb as u64;
+        quotient[i] = (current / divisor as u64) as u32;
+        remainder = current % divisor as u64;
+    }
+    trim(&mut quotient);
+    (quotient, remainder as u32)
+}
+
+fn compare_magnitude(a: &[u32], b: &[u32]) -> Ordering {
+    a.len()
+        .cmp(&b.len())
+        .then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut sum = Vec::with_capacity(long.len() + 1);
+    let mut carry = 0u64;
+    for (i, &limb) in long.iter().enumerate() {
+        let total = limb as u64 + *short.get(i).unwrap_or(&0) as u64 + carry;
+        sum.push(total as u32);
+        carry = total >> 32;
+    }
+    sum.push(carry as u32);
+    trim(&mut sum);
+    sum
+}
+
+/// `a - b`, where `a >= b`.
+fn sub_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut difference = Vec::with_capacity(a.len());
+    let mut borrow = 0i64;
+    for (i, &limb) in a.iter().enumerate() {
+        let mut current = limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = 0;
+        if current < 0 {
+            current += 1 << 32;
+            borrow = 1;
+        }
+        difference.push(current as u32);
+    }
+    trim(&mut difference);
+    difference
+}
+
+fn mul_magnitude(a: &[u32], b: &[u32]) -> Vec<u32> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![0u32; a.len() + b.len()];
+    for (i, &x) in a.iter().enumerate() {
+        let mut carry = 0u64;
+        for (j, &y) in b.iter().enumerate() {
+            let current = product[i + j] as u64 + x as u64 * y as u64 + carry;
+            product[i + j] = current as u32;
+            carry = current >> 32;
+        }
+        product[i + b.len()] = carry as u32;
+    }
+    trim(&mut product);
+    product
+}
+
+/// Binary long division of magnitudes; `b` must not be zero.
+fn div_rem_magnitude(a: &[u32], b: &[u32]) -> (Vec<u32>, Vec<u32>) {
+    if compare_magnitude(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let (quotient, remainder) = div_rem_small(a, b[0]);
+        let mut remainder = vec![remainder];
+        trim(&mut remainder);
+        return (quotient, remainder);
+    }
+    let mut quotient = vec![0u32; a.len()];
+    let mut remainder: Vec<u32> = Vec::new();
+    for bit in (0..a.len() * 32).rev() {
+        // remainder = remainder * 2 + the next bit of `a`
+        let mut carry = (a[bit / 32] >> (bit % 32)) & 1;
+        for limb in remainder.iter_mut() {
+            let next = *limb >> 31;
+            *limb = (*limb << 1) | carry;
+            carry = next;
+        }
+        if carry > 0 {
+            remainder.push(carry);
+        }
+        if compare_magnitude(&remainder, b) != Ordering::Less {
+            remainder = sub_magnitude(&remainder, b);
+            quotient[bit / 32] |= 1 << (bit % 32);
+        }
+    }
+    trim(&mut quotient);
+    (quotient, remainder)
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitude(&self.limbs, &other.limbs),
+            (true, true) => compare_magnitude(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Add for &BigInt {
+    type Output = BigInt;
+
+    fn add(self, other: &BigInt) -> BigInt {
+        if self.negative == other.negative {
+            return BigInt::from_parts(self.negative, add_magnitude(&self.limbs, &other.limbs));
+        }
+        match compare_magnitude(&self.limbs, &other.limbs) {
+            Ordering::Less => {
+                BigInt::from_parts(other.negative, sub_magnitude(&other.limbs, &self.limbs))
+            }
+            _ => BigInt::from_parts(self.negative, sub_magnitude(&self.limbs, &other.limbs)),
+        }
+    }
+}
+
+impl Sub for &BigInt {
+    type Output = BigInt;
+
+    fn sub(self, other: &BigInt) -> BigInt {
+        self + &-other
+    }
+}
+
+impl Mul for &BigInt {
+    type Output = BigInt;
+
+    fn mul(self, other: &BigInt) -> BigInt {
+        BigInt::from_parts(
+            self.negative != other.negative,
+            mul_magnitude(&self.limbs, &other.limbs),
+        )
+    }
+}
+
+impl Neg for &BigInt {
+    type Output = BigInt;
+
+    fn neg(self) -> BigInt {
+        BigInt::from_parts(!self.negative, self.limbs.clone())
+    }
+}
+
+impl fmt::Display for BigInt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        // Peel off nine decimal digits at a time, least significant first.
+        let mut chunks = Vec::new();
+        let mut limbs = self.limbs.clone();
+        while !limbs.is_empty() {
+            let (quotient, remainder) = div_rem_small(&limbs, 1_000_000_000);
+            chunks.push(remainder);
+            limbs = quotient;
+        }
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", chunks.pop().unwrap())?;
+        for chunk in chunks.iter().rev() {
+            write!(f, "{:09}", chunk)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn big(s: &str) -> BigInt {
+        BigInt::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        for s in [
+            "0",
+            "7",
+            "-42",
+            "4294967296",
+            "-123456789012345678901234567890",
+        ] {
+            assert_eq!(big(s).to_string(), s);
+        }
+        assert_eq!(big("-0"), BigInt::zero());
+        assert_eq!(BigInt::parse("12a"), None);
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321098765432109876543210");
+        assert_eq!((&a + &b).to_string(), "-864197532086419753208641975320");
+        assert_eq!((&a - &b).to_string(), "1111111110111111111011111111100");
+        assert_eq!(
+            (&a * &b).to_string(),
+            "-121932631137021795226185032733622923332237463801111263526900"
+        );
+        let (q, r) = b.div_rem(&a).unwrap();
+        assert_eq!(
+            (q.to_string(), r.to_string()),
+            ("-8".to_string(), "-9000000000900000000090".to_string())
+        );
+        assert_eq!(a.div_rem(&BigInt::zero()), None);
+    }
+
+    #[test]
+    fn test_i64_boundaries() {
+        assert_eq!(BigInt::from_i64(i64::MIN).to_i64(), Some(i64::MIN));
+        assert_eq!(
+            (&BigInt::from_i64(i64::MAX) + &BigInt::from_u64(1)).to_i64(),
+            None
+        );
+        assert_eq!(BigInt::from_i64(-5).shr(1).to_i64(), Some(-3));
+        assert_eq!(
+            BigInt::from_u64(1).shl(70).to_string(),
+            "1180591620717411303424"
+        );
+    }
+}
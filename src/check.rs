@@ -0,0 +1,743 @@
+//! The `--check` pass: static checking of the optional type annotations.
+//!
+//! Annotations (`let x: number = ...`, `fun(s: string) -> list { ... }`) are ignored when a
+//! script runs. This pass infers the types of expressions locally, from literals, annotations,
+//! operators and the results of a few builtins, and reports every place where a value of one
+//! type meets an annotation or operator that cannot take it. Whatever it cannot infer is left
+//! alone, so unannotated code passes as long as its literals fit together.
+
+use crate::ast::{
+    AccessType, Argument, BinaryOperator, Block, Expression, LValue, LiteralValue, Param, Pattern,
+    SliceBounds, TypeAnnotation, UnaryOperator,
+};
+use crate::error::{EasyScriptError, SourceLocation};
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+/// The names an annotation can use: what `type()` returns, plus `any` for "not checked".
+const TYPE_NAMES: &[&str] = &[
+    "nil", "boolean", "number", "decimal", "string", "bytes", "list", "tuple", "map", "set",
+    "enum", "function", "method", "iterator", "channel",
+];
+
+/// An inferred type: a `type()` name, or `None` when nothing is known.
+type Type = Option<&'static str>;
+
+/// The parameters and result of a function bound by `let`, for checking calls to it.
+struct FunctionType {
+    name: Option<String>,
+    params: Vec<(String, Type, bool)>, // (name, annotated type, rest)
+    return_type: Type,
+}
+
+struct Binding {
+    ty: Type,
+    declared: bool, // the type comes from an annotation, so assignments must match it
+    function: Option<Rc<FunctionType>>,
+}
+
+/// Checks a program, returning its type errors in source order.
+pub fn check(program: &Block) -> Vec<EasyScriptError> {
+    // An unannotated variable keeps the type of its initial value only if nothing assigns
+    // to it; a first pass finds the names that are assigned anywhere.
+    let mut first_pass = Checker::new(HashSet::new());
+    first_pass.block(program);
+    let mut checker = Checker::new(first_pass.assigned);
+    checker.block(program);
+    checker.errors
+}
+
+struct Checker {
+    scopes: Vec<HashMap<String, Binding>>,
+    reassigned: HashSet<String>, // names assigned somewhere in the program
+    assigned: HashSet<String>,   // names assigned so far in this pass
+    errors: Vec<EasyScriptError>,
+}
+
+impl Checker {
+    fn new(reassigned: HashSet<String>) -> Self {
+        Checker {
+            scopes: Vec::new(),
+            reassigned,
+            assigned: HashSet::new(),
+            errors: Vec::new(),
+        }
+    }
+
+    fn error(&mut self, message: String, location: &Option<SourceLocation>) {
+        self.errors.push(EasyScriptError::TypeError {
+            message,
+            location: location.clone(),
+        });
+    }
+
+    fn declare(&mut self, name: &str, binding: Binding) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), binding);
+        }
+    }
+
+    fn declare_untyped(&mut self, name: &str) {
+        let binding = Binding {
+            ty: None,
+            declared: false,
+            function: None,
+        };
+        self.declare(name, binding);
+    }
+
+    fn lookup(&self, name: &str) -> Option<&Binding> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+
+    /// The type an annotation names, reporting names that are not types.
+    fn annotated(&mut self, annotation: &Option<TypeAnnotation>) -> Type {
+        let annotation = annotation.as_ref()?;
+        if annotation.name != "any" && type_named(&annotation.name).is_none() {
+            self.error(
+                format!("Unknown type '{}'.", annotation.name),
+                &annotation.location,
+            );
+        }
+        type_named(&annotation.name)
+    }
+
+    fn block(&mut self, block: &Block) -> Type {
+        self.scopes.push(HashMap::new());
+        let mut ty = Some("nil");
+        for (expression, _) in &block.expressions {
+            ty = self.expression(expression);
+        }
+        self.scopes.pop();
+        ty
+    }
+
+    fn expression(&mut self, expression: &Expression) -> Type {
+        match expression {
+            Expression::Literal(literal) => Some(literal_type(literal)),
+            Expression::Identifier(name) => self.lookup(name).and_then(|binding| binding.ty),
+            Expression::Block(block) => self.block(block),
+            Expression::ListLiteral(elements) => self.elements(elements, "list"),
+            Expression::TupleLiteral(elements) => self.elements(elements, "tuple"),
+            Expression::SetLiteral(elements) => self.elements(elements, "set"),
+            Expression::MapLiteral(pairs) => {
+                for (key, value) in pairs {
+                    self.expression(key);
+                    self.expression(value);
+                }
+                Some("map")
+            }
+            Expression::Unary { op, expr } => {
+                let ty = self.expression(expr);
+                match op {
+                    UnaryOperator::Not => Some("boolean"),
+                    UnaryOperator::Negate => ty.filter(|ty| is_numeric(ty)),
+                }
+            }
+            Expression::Binary {
+                left,
+                op,
+                right,
+                location,
+            } => {
+                let left = self.expression(left);
+                let right = self.expression(right);
+                self.binary(*op, left, right, location)
+            }
+            Expression::FunctionDef { .. } => {
+                self.function(expression);
+                Some("function")
+            }
+            Expression::Defer(deferred) => {
+                self.expression(deferred);
+                Some("nil")
+            }
+            Expression::Yield(value) => {
+                if let Some(value) = value {
+                    self.expression(value);
+                }
+                None
+            }
+            Expression::Let {
+                identifier,
+                value,
+                constant,
+                annotation,
+                ..
+            } => self.let_declaration(identifier, value, *constant, annotation),
+            Expression::LetDestructure {
+                identifiers, value, ..
+            } => {
+                let ty = self.expression(value);
+                for identifier in identifiers {
+                    self.declare_untyped(identifier);
+                }
+                ty
+            }
+            Expression::EnumDef { name, .. } => {
+                let binding = Binding {
+                    ty: Some("map"),
+                    declared: false,
+                    function: None,
+                };
+                self.declare(name, binding);
+                Some("map")
+            }
+            Expression::Match { subject, arms } => {
+                self.expression(subject);
+                let mut types = Vec::new();
+                for arm in arms {
+                    self.scopes.push(HashMap::new());
+                    self.pattern(&arm.pattern);
+                    if let Some(guard) = &arm.guard {
+                        self.expression(guard);
+                    }
+                    types.push(self.expression(&arm.body));
+                    self.scopes.pop();
+                }
+                common_type(types)
+            }
+            Expression::Assignment {
+                lvalue,
+                value,
+                location,
+            } => {
+                let ty = self.expression(value);
+                self.assignment(lvalue, ty, location);
+                Some("nil")
+            }
+            Expression::CompoundAssignment {
+                lvalue,
+                op,
+                value,
+                location,
+            } => {
+                let current = match lvalue {
+                    LValue::Identifier(name) => self.lookup(name).and_then(|binding| binding.ty),
+                    _ => None,
+                };
+                let value = self.expression(value);
+                let ty = self.binary(*op, current, value, location);
+                self.assignment(lvalue, ty, location);
+                Some("nil")
+            }
+            Expression::If {
+                condition,
+                then_block,
+                else_branch,
+            } => {
+                self.expression(condition);
+                let then_type = self.block(then_block);
+                let else_type = match else_branch {
+                    Some(branch) => self.expression(branch),
+                    None => Some("nil"),
+                };
+                common_type(vec![then_type, else_type])
+            }
+            Expression::ForCondition { condition, body } => {
+                self.expression(condition);
+                self.block(body);
+                Some("list")
+            }
+            Expression::ForIn {
+                identifier,
+                iterable,
+                condition,
+                body,
+            } => {
+                self.expression(iterable);
+                self.scopes.push(HashMap::new());
+                self.declare_untyped(identifier);
+                if let Some(condition) = condition {
+                    self.expression(condition);
+                }
+                self.block(body);
+                self.scopes.pop();
+                Some("list")
+            }
+            Expression::Call {
+                callee,
+                args,
+                location,
+                ..
+            } => self.call(callee, args, location),
+            Expression::Accessor { target, access, .. } => {
+                self.expression(target);
+                self.access(access);
+                None
+            }
+        }
+    }
+
+    fn elements(&mut self, elements: &[Expression], ty: &'static str) -> Type {
+        for element in elements {
+            self.expression(element);
+        }
+        Some(ty)
+    }
+
+    fn access(&mut self, access: &AccessType) {
+        match access {
+            AccessType::Index(key) => {
+                self.expression(key);
+            }
+            AccessType::Slice(bounds) => self.slice(bounds),
+            AccessType::Dot(_) => {}
+        }
+    }
+
+    fn slice(&mut self, bounds: &SliceBounds) {
+        for bound in [&bounds.start, &bounds.end, &bounds.step]
+            .into_iter()
+            .flatten()
+        {
+            self.expression(bound);
+        }
+    }
+
+    fn pattern(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Binding(name) => self.declare_untyped(name),
+            Pattern::Variant {
+                fields: Some(fields),
+                ..
+            } => {
+                for field in fields {
+                    self.pattern(field);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn binary(
+        &mut self,
+        op: BinaryOperator,
+        left: Type,
+        right: Type,
+        location: &Option<SourceLocation>,
+    ) -> Type {
+        match binary_type(op, left, right) {
+            Ok(ty) => ty,
+            Err(()) => {
+                self.error(
+                    format!(
+                        "Operator '{}' cannot be applied to '{}' and '{}'.",
+                        op.symbol(),
+                        left.unwrap_or_default(),
+                        right.unwrap_or_default()
+                    ),
+                    location,
+                );
+                None
+            }
+        }
+    }
+
+    fn let_declaration(
+        &mut self,
+        identifier: &str,
+        value: &Expression,
+        constant: bool,
+        annotation: &Option<TypeAnnotation>,
+    ) -> Type {
+        let declared = self.annotated(annotation);
+        let stable = constant || !self.reassigned.contains(identifier);
+        // A function is declared before its body is checked, so it can call itself.
+        let function = match value {
+            Expression::FunctionDef { .. } if stable => Some(Rc::new(function_type(value))),
+            _ => None,
+        };
+        if function.is_some() {
+            let binding = Binding {
+                ty: declared.or(Some("function")),
+                declared: declared.is_some(),
+                function: function.clone(),
+            };
+            self.declare(identifier, binding);
+        }
+
+        let ty = self.expression(value);
+        if !compatible(declared, ty) {
+            let location = annotation.as_ref().and_then(|a| a.location.clone());
+            self.error(
+                format!(
+                    "Variable '{}' is declared as '{}', but its value has type '{}'.",
+                    identifier,
+                    declared.unwrap_or_default(),
+                    ty.unwrap_or_default()
+                ),
+                &location,
+            );
+        }
+        let binding = Binding {
+            ty: declared.or(if stable { ty } else { None }),
+            declared: declared.is_some(),
+            function,
+        };
+        self.declare(identifier, binding);
+        ty
+    }
+
+    fn assignment(&mut self, lvalue: &LValue, ty: Type, location: &Option<SourceLocation>) {
+        match lvalue {
+            LValue::Identifier(name) => {
+                self.assigned.insert(name.clone());
+                let declared = match self.lookup(name) {
+                    Some(binding) if binding.declared => binding.ty,
+                    _ => None,
+                };
+                if !compatible(declared, ty) {
+                    self.error(
+                        format!(
+                            "Cannot assign a value of type '{}' to '{}', which is declared as '{}'.",
+                            ty.unwrap_or_default(),
+                            name,
+                            declared.unwrap_or_default()
+                        ),
+                        location,
+                    );
+                }
+            }
+            LValue::IndexAccess { target, key } => {
+                self.expression(target);
+                self.expression(key);
+            }
+            LValue::DotAccess { target, .. } => {
+                self.expression(target);
+            }
+            LValue::SliceAccess { target, bounds } => {
+                self.expression(target);
+                self.slice(bounds);
+            }
+        }
+    }
+
+    /// Checks a function definition: its defaults, its body and its declared return type.
+    fn function(&mut self, definition: &Expression) {
+        let Expression::FunctionDef {
+            name,
+            params,
+            body,
+            is_generator,
+            return_type,
+            ..
+        } = definition
+        else {
+            return;
+        };
+        self.scopes.push(HashMap::new());
+        for param in params {
+            self.param(param);
+        }
+        let body_type = self.block(body);
+        self.scopes.pop();
+
+        let declared = self.annotated(return_type);
+        let actual = if *is_generator {
+            Some("iterator")
+        } else {
+            body_type
+        };
+        if !compatible(declared, actual) {
+            let location = return_type.as_ref().and_then(|a| a.location.clone());
+            self.error(
+                format!(
+                    "{} is declared to return '{}', but its body has type '{}'.",
+                    describe_function(name),
+                    declared.unwrap_or_default(),
+                    actual.unwrap_or_default()
+                ),
+                &location,
+            );
+        }
+    }
+
+    fn param(&mut self, param: &Param) {
+        let declared = self.annotated(&param.annotation);
+        if let Some(default) = &param.default {
+            let ty = self.expression(default);
+            if !compatible(declared, ty) {
+                let location = param.annotation.as_ref().and_then(|a| a.location.clone());
+                self.error(
+                    format!(
+                        "Parameter '{}' is declared as '{}', but its default has type '{}'.",
+                        param.name,
+                        declared.unwrap_or_default(),
+                        ty.unwrap_or_default()
+                    ),
+                    &location,
+                );
+            }
+        }
+        let binding = Binding {
+            ty: if param.rest { Some("list") } else { declared },
+            declared: declared.is_some(),
+            function: None,
+        };
+        self.declare(&param.name, binding);
+    }
+
+    /// Checks the arguments of a call against the callee's annotations, if it is a function
+    /// bound by `let`, and returns the type of the result.
+    fn call(
+        &mut self,
+        callee: &Expression,
+        args: &[Argument],
+        location: &Option<SourceLocation>,
+    ) -> Type {
+        let (function, result) = match callee {
+            Expression::Identifier(name) => match self.lookup(name) {
+                Some(binding) => (
+                    binding.function.clone(),
+                    binding.function.as_ref().and_then(|f| f.return_type),
+                ),
+                None => (None, builtin_result(name)),
+            },
+            _ => {
+                self.expression(callee);
+                (None, None)
+            }
+        };
+
+        // Positional arguments fill the parameters before the rest parameter, in order; after
+        // a spread, which parameter an argument lands in is only known at run time.
+        let mut position = Some(0);
+        for arg in args {
+            let (param, value) = match arg {
+                Argument::Positional(value) => {
+                    let param = position.and_then(|i| {
+                        function
+                            .as_ref()?
+                            .params
+                            .iter()
+                            .take_while(|(_, _, rest)| !rest)
+                            .nth(i)
+                    });
+                    position = position.map(|i| i + 1);
+                    (param, value)
+                }
+                Argument::Spread(value) => {
+                    position = None;
+                    (None, value)
+                }
+                Argument::Keyword { name, value } => {
+                    let param = function
+                        .as_ref()
+                        .and_then(|f| f.params.iter().find(|(param, _, _)| param == name));
+                    (param, value)
+                }
+            };
+            let ty = self.expression(value);
+            if let Some((param_name, declared, _)) = param {
+                if !compatible(*declared, ty) {
+                    let of_function = match function.as_ref().and_then(|f| f.name.as_ref()) {
+                        Some(name) => format!(" of '{}'", name),
+                        None => String::new(),
+                    };
+                    self.error(
+                        format!(
+                            "Argument '{}'{} expects '{}', but got '{}'.",
+                            param_name,
+                            of_function,
+                            declared.unwrap_or_default(),
+                            ty.unwrap_or_default()
+                        ),
+                        location,
+                    );
+                }
+            }
+        }
+        result
+    }
+}
+
+/// The type an annotation name stands for; `None` for `any` and for unknown names.
+fn type_named(name: &str) -> Type {
+    TYPE_NAMES.iter().copied().find(|&ty| ty == name)
+}
+
+fn literal_type(literal: &LiteralValue) -> &'static str {
+    match literal {
+        LiteralValue::Number(_) | LiteralValue::Integer(_) | LiteralValue::BigInteger(_) => {
+            "number"
+        }
+        LiteralValue::String(_) => "string",
+        LiteralValue::Bytes(_) => "bytes",
+        LiteralValue::Boolean(_) => "boolean",
+        LiteralValue::Nil => "nil",
+    }
+}
+
+/// What is known about a function from its definition alone.
+fn function_type(definition: &Expression) -> FunctionType {
+    let Expression::FunctionDef {
+        name,
+        params,
+        is_generator,
+        return_type,
+        ..
+    } = definition
+    else {
+        unreachable!("function_type() takes a function definition");
+    };
+    let annotated =
+        |annotation: &Option<TypeAnnotation>| annotation.as_ref().and_then(|a| type_named(&a.name));
+    FunctionType {
+        name: name.clone(),
+        params: params
+            .iter()
+            .map(|p| (p.name.clone(), annotated(&p.annotation), p.rest))
+            .collect(),
+        return_type: if *is_generator {
+            Some("iterator")
+        } else {
+            annotated(return_type)
+        },
+    }
+}
+
+fn describe_function(name: &Option<String>) -> String {
+    match name {
+        Some(name) => format!("Function '{}'", name),
+        None => "The function".to_string(),
+    }
+}
+
+/// The result types of the global natives that always return the same type.
+fn builtin_result(name: &str) -> Type {
+    match name {
+        "print" => Some("nil"),
+        "len" | "num" | "int" | "float" => Some("number"),
+        "type" | "str" | "repr" => Some("string"),
+        "bool" => Some("boolean"),
+        "decimal" => Some("decimal"),
+        "from_hex" => Some("bytes"),
+        "set" => Some("set"),
+        "make_map" => Some("map"),
+        "iter" => Some("iterator"),
+        _ => None,
+    }
+}
+
+fn is_numeric(ty: &str) -> bool {
+    ty == "number" || ty == "decimal"
+}
+
+/// Whether a value of type `actual` may go where `expected` is declared.
+fn compatible(expected: Type, actual: Type) -> bool {
+    match (expected, actual) {
+        (Some("function"), Some("method")) => true,
+        (Some(expected), Some(actual)) => expected == actual,
+        _ => true,
+    }
+}
+
+/// The type shared by all the branches, if they agree.
+fn common_type(types: Vec<Type>) -> Type {
+    let first = *types.first()?;
+    types.into_iter().all(|ty| ty == first).then_some(first)?
+}
+
+/// The type of `left op right`, or `Err` when the operator rejects those types at run time.
+fn binary_type(op: BinaryOperator, left: Type, right: Type) -> Result<Type, ()> {
+    match op {
+        BinaryOperator::Eq | BinaryOperator::Neq | BinaryOperator::Is | BinaryOperator::In => {
+            return Ok(Some("boolean"))
+        }
+        BinaryOperator::And | BinaryOperator::Or => return Ok(common_type(vec![left, right])),
+        BinaryOperator::NilCoalesce => {
+            return Ok(match left {
+                Some("nil") => right,
+                _ => left,
+            })
+        }
+        _ => {}
+    }
+    let (Some(left), Some(right)) = (left, right) else {
+        return Ok(None);
+    };
+    // Maps can define operators with metamethods such as `__add`.
+    if left == "map" || right == "map" {
+        return Ok(None);
+    }
+    let comparison = matches!(
+        op,
+        BinaryOperator::Lt | BinaryOperator::Lte | BinaryOperator::Gt | BinaryOperator::Gte
+    );
+    if is_numeric(left) && is_numeric(right) {
+        return Ok(Some(if comparison {
+            "boolean"
+        } else if left == "decimal" || right == "decimal" {
+            "decimal"
+        } else {
+            "number"
+        }));
+    }
+    if op == BinaryOperator::Add && left == right && matches!(left, "string" | "bytes" | "list") {
+        return Ok(Some(left));
+    }
+    Err(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::Lexer;
+    use crate::parser::Parser;
+
+    fn check_source(source: &str) -> Vec<String> {
+        let tokens = Lexer::new(source).scan_located_tokens().unwrap();
+        let program = Parser::with_locations(tokens).parse().unwrap();
+        check(&program).iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_annotated_let() {
+        assert!(check_source("let x: number = 1; let s: any = nil; x = 2;").is_empty());
+        assert_eq!(
+            check_source("let x = 1;\nlet s: string = x + 2;"),
+            ["[Type Error at line 2 column 8]: Variable 's' is declared as 'string', but its value has type 'number'."]
+        );
+        assert_eq!(
+            check_source("let n: number = 0;\nn = \"zero\";"),
+            ["[Type Error at line 2 column 3]: Cannot assign a value of type 'string' to 'n', which is declared as 'number'."]
+        );
+        assert_eq!(
+            check_source("let n: numbr = 0;"),
+            ["[Type Error at line 1 column 8]: Unknown type 'numbr'."]
+        );
+    }
+
+    #[test]
+    fn test_operators() {
+        assert_eq!(
+            check_source("let label = \"n = \";\nprint(label + 1);"),
+            ["[Type Error at line 2 column 13]: Operator '+' cannot be applied to 'string' and 'number'."]
+        );
+        // A reassigned variable is not assumed to keep the type of its initial value.
+        assert!(check_source("let v = 1; v = \"a\"; v + \"b\";").is_empty());
+        assert!(check_source("let m = {}; m + 1; [1] + [2]; 1 < 2.5;").is_empty());
+    }
+
+    #[test]
+    fn test_functions() {
+        let source = "let greet = fun(name: string, times: number = 1) -> string {\n    name * times\n};\ngreet(\"a\", times: \"2\");\ngreet(42);";
+        assert_eq!(
+            check_source(source),
+            [
+                "[Type Error at line 2 column 10]: Operator '*' cannot be applied to 'string' and 'number'.",
+                "[Type Error at line 4 column 6]: Argument 'times' of 'greet' expects 'number', but got 'string'.",
+                "[Type Error at line 5 column 6]: Argument 'name' of 'greet' expects 'string', but got 'number'.",
+            ]
+        );
+        assert_eq!(
+            check_source("let f = fun(x: number) -> string { x + 1 };\nlet y: string = f(1);"),
+            ["[Type Error at line 1 column 27]: Function 'f' is declared to return 'string', but its body has type 'number'."]
+        );
+        assert!(check_source(
+            "let fact = fun(n: number) -> number { if n < 2 { 1 } else { n * fact(n - 1) } };"
+        )
+        .is_empty());
+    }
+}
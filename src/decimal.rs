@@ -0,0 +1,342 @@
+//! Exact decimal numbers, the payload of `Object::Decimal`.
+//!
+//! A decimal is a `BigInt` coefficient scaled by a power of ten, so `12.30` is `1230` at
+//! scale 2. Addition, subtraction and multiplication are exact and keep trailing zeros;
+//! division is the one inexact operation and is rounded according to a `DecimalContext`.
+
+use crate::bigint::{decompose_f64, BigInt};
+use std::cmp::Ordering;
+use std::fmt;
+
+/// How a result is rounded when digits have to be dropped.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Rounding {
+    HalfEven, // To nearest, ties to the even digit (banker's rounding)
+    HalfUp,   // To nearest, ties away from zero
+    HalfDown, // To nearest, ties toward zero
+    Up,       // Away from zero
+    Down,     // Toward zero (truncation)
+    Ceiling,  // Toward positive infinity
+    Floor,    // Toward negative infinity
+}
+
+impl Rounding {
+    pub const NAMES: [&'static str; 7] = [
+        "half_even",
+        "half_up",
+        "half_down",
+        "up",
+        "down",
+        "ceiling",
+        "floor",
+    ];
+
+    pub fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "half_even" => Rounding::HalfEven,
+            "half_up" => Rounding::HalfUp,
+            "half_down" => Rounding::HalfDown,
+            "up" => Rounding::Up,
+            "down" => Rounding::Down,
+            "ceiling" => Rounding::Ceiling,
+            "floor" => Rounding::Floor,
+            _ => return None,
+        })
+    }
+}
+
+/// The precision (in significant digits) and rounding used by decimal division.
+#[derive(Debug, Clone, Copy)]
+pub struct DecimalContext {
+    pub precision: u32,
+    pub rounding: Rounding,
+}
+
+impl Default for DecimalContext {
+    fn default() -> Self {
+        DecimalContext {
+            precision: 28,
+            rounding: Rounding::HalfEven,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Decimal {
+    coefficient: BigInt,
+    scale: u32,
+}
+
+impl Decimal {
+    pub fn new(coefficient: BigInt, scale: u32) -> Self {
+        Decimal { coefficient, scale }
+    }
+
+    pub fn from_integer(integer: BigInt) -> Self {
+        Decimal::new(integer, 0)
+    }
+
+    /// Parses `[sign] digits [. digits] [e [sign] digits]`, keeping trailing zeros.
+    pub fn parse(s: &str) -> Option<Self> {
+        let s = s.trim();
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(at) => (&s[..at], s[at + 1..].parse::<i64>().ok()?),
+            None => (s, 0),
+        };
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let unsigned = whole.trim_start_matches(['+', '-']);
+        if (unsigned.is_empty() && fraction.is_empty())
+            || !fraction.bytes().all(|b| b.is_ascii_digit())
+        {
+            return None;
+        }
+        let digits = format!("{}{}", whole, fraction);
+        let digits = if unsigned.is_empty() {
+            format!("{}0{}", whole, fraction) // ".5" or "-.5"
+        } else {
+            digits
+        };
+        let coefficient = BigInt::parse(&digits)?;
+        let scale = fraction.len() as i64 - exponent;
+        Some(if scale >= 0 {
+            Decimal::new(coefficient, u32::try_from(scale).ok()?)
+        } else {
+            let factor = BigInt::pow10(u32::try_from(-scale).ok()?);
+            Decimal::new(&coefficient * &factor, 0)
+        })
+    }
+
+    /// The exact value of a finite float, which always has a finite decimal expansion.
+    pub fn from_f64(f: f64) -> Option<Self> {
+        if !f.is_finite() {
+            return None;
+        }
+        let (mantissa, exponent) = decompose_f64(f);
+        let magnitude = BigInt::from_u64(mantissa);
+        let decimal = if exponent >= 0 {
+            Decimal::new(magnitude.shl(exponent as u32), 0)
+        } else {
+            // m / 2^k == m * 5^k / 10^k
+            let k = (-exponent) as u32;
+            Decimal::new(&magnitude * &BigInt::pow5(k), k).normalized()
+        };
+        Some(if f < 0.0 { decimal.neg() } else { decimal })
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coefficient.is_zero()
+    }
+
+    /// The nearest float, or an infinity.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// The integer part, truncated toward zero.
+    pub fn trunc(&self) -> BigInt {
+        let (quotient, _) = self
+            .coefficient
+            .div_rem(&BigInt::pow10(self.scale))
+            .unwrap();
+        quotient
+    }
+
+    /// The value as an integer, if it has no fractional part.
+    pub fn to_integer(&self) -> Option<BigInt> {
+        let (quotient, remainder) = self
+            .coefficient
+            .div_rem(&BigInt::pow10(self.scale))
+            .unwrap();
+        remainder.is_zero().then_some(quotient)
+    }
+
+    /// The same value with trailing zeros removed from the fraction.
+    pub fn normalized(&self) -> Decimal {
+        let ten = BigInt::from_u64(10);
+        let mut result = self.clone();
+        while result.scale > 0 {
+            let (quotient, remainder) = result.coefficient.div_rem(&ten).unwrap();
+            if !remainder.is_zero() {
+                break;
+            }
+            result = Decimal::new(quotient, result.scale - 1);
+        }
+        result
+    }
+
+    /// The coefficient at a scale at least as large as this decimal's.
+    fn coefficient_at(&self, scale: u32) -> BigInt {
+        &self.coefficient * &BigInt::pow10(scale - self.scale)
+    }
+
+    fn aligned(&self, other: &Decimal) -> (BigInt, BigInt, u32) {
+        let scale = self.scale.max(other.scale);
+        (
+            self.coefficient_at(scale),
+            other.coefficient_at(scale),
+            scale,
+        )
+    }
+
+    pub fn neg(&self) -> Decimal {
+        Decimal::new(-&self.coefficient, self.scale)
+    }
+
+    pub fn add(&self, other: &Decimal) -> Decimal {
+        let (a, b, scale) = self.aligned(other);
+        Decimal::new(&a + &b, scale)
+    }
+
+    pub fn sub(&self, other: &Decimal) -> Decimal {
+        let (a, b, scale) = self.aligned(other);
+        Decimal::new(&a - &b, scale)
+    }
+
+    pub fn mul(&self, other: &Decimal) -> Decimal {
+        Decimal::new(
+            &self.coefficient * &other.coefficient,
+            self.scale + other.scale,
+        )
+    }
+
+    /// Divides, rounding an inexact quotient to the context's precision. An exact quotient
+    /// keeps only the trailing zeros implied by the operands' scales (`12.30 / 2` is `6.15`).
+    /// `None` when dividing by zero.
+    pub fn div(&self, other: &Decimal, context: &DecimalContext) -> Option<Decimal> {
+        if other.is_zero() {
+            return None;
+        }
+        let precision = context.precision.max(1) as i64;
+        let ideal_scale = (self.scale as i64 - other.scale as i64).max(0);
+        let negative = self.coefficient.is_negative() != other.coefficient.is_negative();
+
+        // Shift the dividend so the quotient has at least one digit beyond the precision.
+        let shift = (precision + other.coefficient.decimal_digits() as i64
+            - self.coefficient.decimal_digits() as i64
+            + 1)
+        .max(0) as u32;
+        let dividend = &self.coefficient.abs() * &BigInt::pow10(shift);
+        let (quotient, remainder) = dividend.div_rem(&other.coefficient.abs())?;
+        let mut scale = shift as i64 + self.scale as i64 - other.scale as i64;
+        let signed = if negative { -&quotient } else { quotient };
+
+        let mut result = if remainder.is_zero() {
+            let mut result = Decimal::new(signed, 0);
+            while scale > ideal_scale {
+                let (quotient, remainder) = result.coefficient.div_rem(&BigInt::from_u64(10))?;
+                if !remainder.is_zero() {
+                    break;
+                }
+                result.coefficient = quotient;
+                scale -= 1;
+            }
+            result
+        } else {
+            let extra = (signed.decimal_digits() as i64 - precision).max(1) as u32;
+            scale -= extra as i64;
+            Decimal::new(round_off(&signed, extra, true, context.rounding), 0)
+        };
+        if scale < 0 {
+            result.coefficient = &result.coefficient * &BigInt::pow10((-scale) as u32);
+            scale = 0;
+        }
+        result.scale = scale as u32;
+        Some(result)
+    }
+
+    /// The integral quotient, truncated toward zero. `None` when dividing by zero.
+    pub fn div_trunc(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, _) = self.aligned(other);
+        let (quotient, _) = a.div_rem(&b)?;
+        Some(Decimal::from_integer(quotient))
+    }
+
+    /// The remainder of `div_trunc`, with the dividend's sign. `None` when dividing by zero.
+    pub fn rem(&self, other: &Decimal) -> Option<Decimal> {
+        let (a, b, scale) = self.aligned(other);
+        let (_, remainder) = a.div_rem(&b)?;
+        Some(Decimal::new(remainder, scale))
+    }
+
+    /// Rounds (or pads with zeros) to exactly `places` digits after the decimal point.
+    pub fn round(&self, places: u32, rounding: Rounding) -> Decimal {
+        if places >= self.scale {
+            Decimal::new(self.coefficient_at(places), places)
+        } else {
+            let coefficient = round_off(&self.coefficient, self.scale - places, false, rounding);
+            Decimal::new(coefficient, places)
+        }
+    }
+}
+
+/// Drops the last `digits` decimal digits of `value`, rounding the result. `sticky` says that
+/// nonzero digits were already dropped beyond these (as in an inexact division).
+fn round_off(value: &BigInt, digits: u32, sticky: bool, rounding: Rounding) -> BigInt {
+    let divisor = BigInt::pow10(digits);
+    let (quotient, remainder) = value.div_rem(&divisor).unwrap();
+    if remainder.is_zero() && !sticky {
+        return quotient;
+    }
+    let twice = &remainder.abs() * &BigInt::from_u64(2);
+    let against_half = match twice.cmp(&divisor) {
+        Ordering::Equal if sticky => Ordering::Greater,
+        ordering => ordering,
+    };
+    let negative = value.is_negative();
+    let away_from_zero = match rounding {
+        Rounding::Down => false,
+        Rounding::Up => true,
+        Rounding::Ceiling => !negative,
+        Rounding::Floor => negative,
+        Rounding::HalfUp => against_half != Ordering::Less,
+        Rounding::HalfDown => against_half == Ordering::Greater,
+        Rounding::HalfEven => {
+            against_half == Ordering::Greater
+                || (against_half == Ordering::Equal && quotient.is_odd())
+        }
+    };
+    match (away_from_zero, negative) {
+        (false, _) => quotient,
+        (true, false) => &quotient + &BigInt::from_u64(1),
+        (true, true) => &quotient - &BigInt::from_u64(1),
+    }
+}
+
+impl PartialEq for Decimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Decimal {}
+
+impl Ord for Decimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b, _) = self.aligned(other);
+        a.cmp(&b)
+    }
+}
+
+impl PartialOrd for Decimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for Decimal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.coefficient.abs().to_string();
+        let sign = if self.coefficient.is_negative() {
+            "-"
+        } else {
+            ""
+        };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+        let padded = format!("{:0>width$}", digits, width = scale + 1);
+        let (whole, fraction) = padded.split_at(padded.len() - scale);
+        write!(f, "{}{}.{}", sign, whole, fraction)
+    }
+}
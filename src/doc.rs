@@ -0,0 +1,49 @@
+//! Rendering of `##` doc comments, shared by the `help()` native and `--doc` mode.
+//!
+//! The parser attaches a doc comment to the `let`/`const` binding (or `fun`) that directly
+//! follows it; a function bound by `let` also keeps the doc, so `help(f)` can show it.
+
+use crate::ast::{Block, Expression};
+use crate::signature::format_signature;
+
+/// A header line (usually a signature) followed by the doc text, indented.
+pub fn render_entry(header: &str, doc: Option<&str>) -> String {
+    let mut entry = header.to_string();
+    for line in doc.into_iter().flat_map(str::lines) {
+        entry.push('\n');
+        if !line.is_empty() {
+            entry += "    ";
+            entry += line;
+        }
+    }
+    entry
+}
+
+/// The documented top-level bindings of a program, separated by blank lines, in source order.
+pub fn document(program: &Block) -> String {
+    let entries: Vec<String> = program
+        .expressions
+        .iter()
+        .filter_map(|(expression, _)| match expression {
+            Expression::Let {
+                identifier,
+                value,
+                constant,
+                doc: Some(doc),
+                ..
+            } => {
+                let header = match value.as_ref() {
+                    Expression::FunctionDef { params, .. } => {
+                        let specs: Vec<_> = params.iter().map(|p| p.spec()).collect();
+                        format_signature(identifier, &specs)
+                    }
+                    _ if *constant => format!("const {}", identifier),
+                    _ => format!("let {}", identifier),
+                };
+                Some(render_entry(&header, Some(doc)))
+            }
+            _ => None,
+        })
+        .collect();
+    entries.join("\n\n")
+}
@@ -1,6 +1,6 @@
 use crate::value::Value;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::rc::Rc;
 
 // A type alias for a reference-counted, mutable environment.
@@ -13,6 +13,7 @@ pub type EnvironmentRef = Rc<RefCell<Environment>>;
 pub struct Environment {
     pub parent: Option<EnvironmentRef>,
     pub values: HashMap<String, Value>, // Made public for direct mutation after finding environment
+    constants: HashSet<String>,         // Names declared with `const` in this scope
 }
 
 impl Environment {
@@ -21,6 +22,7 @@ impl Environment {
         Rc::new(RefCell::new(Environment {
             parent: None,
             values: HashMap::new(),
+            constants: HashSet::new(),
         }))
     }
 
@@ -29,6 +31,7 @@ impl Environment {
         Rc::new(RefCell::new(Environment {
             parent: Some(Rc::clone(parent)),
             values: HashMap::new(),
+            constants: HashSet::new(),
         }))
     }
 
@@ -38,6 +41,24 @@ impl Environment {
         self.values.insert(name.to_string(), value);
     }
 
+    /// Declares a variable in the *current* scope (`let`, or `const` when `constant`),
+    /// shadowing any outer one. A constant cannot be redeclared in its own scope.
+    pub fn declare(&mut self, name: &str, value: Value, constant: bool) -> Result<(), String> {
+        if self.constants.contains(name) {
+            return Err(format!("Cannot redeclare constant '{}'.", name));
+        }
+        if constant {
+            self.constants.insert(name.to_string());
+        }
+        self.assign(name, value);
+        Ok(())
+    }
+
+    /// Whether `name` was declared with `const` in this scope.
+    pub fn is_constant(&self, name: &str) -> bool {
+        self.constants.contains(name)
+    }
+
     /// Gets a variable's value, searching recursively up through parent scopes.
     pub fn get(&self, name: &str) -> Result<Value, String> {
         // Try to get from the current scope first.
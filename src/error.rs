@@ -28,6 +28,11 @@ pub enum EasyScriptError {
         message: String,
         location: Option<SourceLocation>,
     },
+    // `--check` 静态检查发现的类型错误
+    TypeError {
+        message: String,
+        location: Option<SourceLocation>,
+    },
 }
 
 impl fmt::Display for EasyScriptError {
@@ -54,11 +59,30 @@ impl fmt::Display for EasyScriptError {
                     write!(f, "[Runtime Error]: {}", message)
                 }
             }
+            EasyScriptError::TypeError { message, location } => {
+                if let Some(loc) = location {
+                    write!(f, "[Type Error at {}]: {}", loc, message)
+                } else {
+                    write!(f, "[Type Error]: {}", message)
+                }
+            }
         }
     }
 }
 
 // 帮助将 String 转换为 RuntimeError
+impl EasyScriptError {
+    /// The error message without its kind and location.
+    pub fn message(&self) -> &str {
+        match self {
+            EasyScriptError::LexerError { message, .. }
+            | EasyScriptError::ParserError { message, .. }
+            | EasyScriptError::RuntimeError { message, .. }
+            | EasyScriptError::TypeError { message, .. } => message,
+        }
+    }
+}
+
 impl From<String> for EasyScriptError {
     fn from(message: String) -> Self {
         EasyScriptError::RuntimeError {
@@ -1,147 +1,201 @@
-use crate::ast::{Block, Expression, LiteralValue};
+use crate::ast::{
+    AccessType, Argument, BinaryOperator, Block, Expression, LValue, LiteralValue, MatchArm, Param,
+    Pattern, SliceBounds,
+};
+use crate::bigint::BigInt;
+use crate::decimal::DecimalContext;
 use crate::environment::{Environment, EnvironmentRef};
 use crate::error::EasyScriptError;
-use crate::value::{BoundMethodInner, FunctionObjectInner, Heap, NativeFunction, Object, Value};
+use crate::native::BuiltinMethods;
+use crate::ordered_map::OrderedMap;
+use crate::signature::{bind_arguments, BoundArg, CallArgs, Signature};
+use crate::slice::{resolve_index, Slice};
+use crate::value::compare_numbers;
+use crate::value::{
+    trace_environment, BoundMethodInner, EnumValue, EnumVariant, FunctionObjectInner, GcTrace,
+    Heap, IteratorInner, NativeFunction, Object, Value,
+};
+pub use context::NativeContext;
+use corosensei::stack::DefaultStack;
+use coroutine::{ActiveCoroutine, Coroutine};
+use scheduler::Scheduler;
+use std::cell::{Cell, RefCell};
+use std::cmp::Ordering;
 use std::collections::HashMap;
+use std::marker::PhantomPinned;
+use std::pin::Pin;
 use std::rc::Rc;
 
+mod context;
+pub mod coroutine;
+mod iterator;
+mod scheduler;
+
+/// The interpreter's methods all take `&self`, and its state lives in cells: suspended
+/// generators and tasks hold on to the interpreter while it goes on running other code (see
+/// `coroutine`), so no `&mut Interpreter` may be live while they run.
 pub struct Interpreter {
     pub heap: Heap,
-    environment: EnvironmentRef,
+    environment: RefCell<EnvironmentRef>,
     // Add the builtin_methods field
-    builtin_methods: HashMap<&'static str, HashMap<&'static str, NativeFunction>>,
+    builtin_methods: BuiltinMethods,
+    // Coroutines currently being resumed, innermost last.
+    coroutines: RefCell<Vec<ActiveCoroutine>>,
+    // Every coroutine that has started and not finished yet, in the order they started.
+    started_coroutines: RefCell<Vec<Rc<Coroutine>>>,
+    scheduler: RefCell<Scheduler>,
+    // Values held only by Rust code that calls back into script; see `root`.
+    temp_roots: RefCell<Vec<Value>>,
+    // Lowest address the running coroutine's stack segment reaches; zero on the main thread.
+    stack_limit: Cell<usize>,
+    // Free coroutine stack segments, kept for reuse.
+    stacks: RefCell<Vec<DefaultStack>>,
+    // Precision and rounding of decimal division, set by `set_decimal_context()`.
+    pub(crate) decimal_context: Cell<DecimalContext>,
+    // Suspended coroutines refer to the interpreter by address (see `coroutine`).
+    _pinned: PhantomPinned,
+}
+
+impl Drop for Interpreter {
+    fn drop(&mut self) {
+        // `run` leaves no coroutine suspended, but should one be, unwind it (running its
+        // `defer`s) while the interpreter is still intact: its stack refers into it.
+        self.cancel_started_coroutines();
+    }
 }
 
 impl Interpreter {
-    pub fn new() -> Self {
+    /// Creates an interpreter. It is pinned because suspended generators and tasks refer to
+    /// it by address.
+    pub fn new() -> Pin<Box<Self>> {
         let mut interpreter = Interpreter {
             heap: Heap::new(),
-            environment: Environment::new(),
+            environment: RefCell::new(Environment::new()),
             builtin_methods: HashMap::new(), // Temporarily initialize as empty
+            coroutines: RefCell::new(Vec::new()),
+            started_coroutines: RefCell::new(Vec::new()),
+            scheduler: RefCell::new(Scheduler::default()),
+            temp_roots: RefCell::new(Vec::new()),
+            stack_limit: Cell::new(0),
+            stacks: RefCell::new(Vec::new()),
+            decimal_context: Cell::new(DecimalContext::default()),
+            _pinned: PhantomPinned,
         };
 
         // Initialize builtin_methods after heap is available
-        interpreter.builtin_methods =
-            crate::native::init_builtin_methods_map(&mut interpreter.heap);
+        interpreter.builtin_methods = crate::native::init_builtin_methods_map(&interpreter.heap);
 
         // Register global native functions
-        let global_env_ref = Rc::clone(&interpreter.environment);
+        let global_env_ref = interpreter.env();
         {
             let mut global_env = global_env_ref.borrow_mut();
-            global_env.assign(
-                "print",
-                Value::function(
-                    &mut interpreter.heap,
-                    FunctionObjectInner::Native(Rc::new(crate::native::print_fn)),
-                ),
-            );
-            global_env.assign(
-                "len",
-                Value::function(
-                    &mut interpreter.heap,
-                    FunctionObjectInner::Native(Rc::new(crate::native::len_fn)),
-                ),
-            );
-            global_env.assign(
-                "type",
-                Value::function(
-                    &mut interpreter.heap,
-                    FunctionObjectInner::Native(Rc::new(crate::native::type_fn)),
-                ),
-            );
-            global_env.assign(
-                "bool",
-                Value::function(
-                    &mut interpreter.heap,
-                    FunctionObjectInner::Native(Rc::new(crate::native::bool_fn)),
-                ),
-            );
-            global_env.assign(
-                "str",
-                Value::function(
-                    &mut interpreter.heap,
-                    FunctionObjectInner::Native(Rc::new(crate::native::str_fn)),
-                ),
-            );
-            global_env.assign(
-                "num",
-                Value::function(
-                    &mut interpreter.heap,
-                    FunctionObjectInner::Native(Rc::new(crate::native::num_fn)),
-                ),
-            );
-            global_env.assign(
-                "input",
-                Value::function(
-                    &mut interpreter.heap,
-                    FunctionObjectInner::Native(Rc::new(crate::native::input_fn)),
-                ),
-            );
-            global_env.assign(
-                "repr", // Register the new repr function
-                Value::function(
-                    &mut interpreter.heap,
-                    FunctionObjectInner::Native(Rc::new(crate::native::repr_fn)),
-                ),
-            );
-            global_env.assign(
-                "gc_collect",
-                Value::function(
-                    &mut interpreter.heap,
-                    FunctionObjectInner::Native(Rc::new(crate::native::gc_collect_fn)),
-                ),
-            );
-            global_env.assign(
-                "make_map",
-                Value::function(
-                    &mut interpreter.heap,
-                    FunctionObjectInner::Native(Rc::new(crate::native::make_map_fn)),
-                ),
-            );
+            for (function, signature) in crate::native::global_natives() {
+                let name = signature.name;
+                let value = Value::function(
+                    &interpreter.heap,
+                    FunctionObjectInner::Native {
+                        function,
+                        signature: Some(Rc::new(signature)),
+                    },
+                );
+                global_env.assign(name, value);
+            }
         } // The mutable borrow of global_env is dropped here.
 
-        interpreter
+        Box::pin(interpreter)
     }
 
     /// Runs the interpreter with a given program block.
-    pub fn run(&mut self, program: &Block) -> Result<Value, EasyScriptError> {
-        // 克隆 environment，使其与 self 的可变借用不冲突
-        let current_env = Rc::clone(&self.environment);
+    pub fn run(&self, program: &Block) -> Result<Value, EasyScriptError> {
+        let current_env = self.env();
 
-        self.execute_block(program, &current_env)
+        let result = self.execute_block(program, &current_env);
+        // Spawned tasks run to completion (or until blocked forever) before the program ends.
+        let result = result.and_then(|value| self.run_scheduler(false).map(|_| value));
+        // Nothing can resume the generators and tasks still suspended now; unwind them so that
+        // their `defer`s run before the program ends.
+        self.cancel_started_coroutines();
+        self.scheduler.borrow_mut().clear();
+        result
+    }
+
+    /// The current scope.
+    fn env(&self) -> EnvironmentRef {
+        Rc::clone(&self.environment.borrow())
+    }
+
+    /// Makes `env` the current scope, returning the previous one.
+    fn replace_env(&self, env: EnvironmentRef) -> EnvironmentRef {
+        self.environment.replace(env)
+    }
+
+    /// Cancels every coroutine that has started and not finished, in the order they started.
+    fn cancel_started_coroutines(&self) {
+        // Cleanup code may start more coroutines.
+        loop {
+            let started = self.started_coroutines.take();
+            if started.is_empty() {
+                return;
+            }
+            for coroutine in started {
+                coroutine.cancel(self);
+            }
+        }
     }
 
     /// Executes a block of expressions in a given environment.
     /// For nested blocks, a new enclosed environment is created.
-    fn execute_block(
-        &mut self,
-        block: &Block,
-        env: &EnvironmentRef,
-    ) -> Result<Value, EasyScriptError> {
+    fn execute_block(&self, block: &Block, env: &EnvironmentRef) -> Result<Value, EasyScriptError> {
         // Temporarily set the interpreter's environment to the new one.
-        let previous_env = Rc::clone(&self.environment);
-        self.environment = Rc::clone(env);
+        let previous_env = self.env();
+        self.replace_env(Rc::clone(env));
 
         // Introduce a special variable in the current environment to store the result of expressions.
         // This makes the result a root for the GC.
         let result_var_name = "__res";
         env.borrow_mut()
-            .assign(result_var_name, Value::nil(&mut self.heap)); // Initialize with nil
+            .assign(result_var_name, Value::nil(&self.heap)); // Initialize with nil
 
+        let mut deferred = Vec::new();
+        let mut outcome = Ok(());
         for (index, (expr, terminated_by_semicolon)) in block.expressions.iter().enumerate() {
-            let expr_value = self.evaluate(expr)?;
+            // `defer` only registers its expression; the value of the statement is nil.
+            let expr_value = if let Expression::Defer(deferred_expr) = expr {
+                deferred.push(deferred_expr);
+                Value::nil(&self.heap)
+            } else {
+                match self.evaluate(expr) {
+                    Ok(value) => value,
+                    Err(e) => {
+                        outcome = Err(e);
+                        break;
+                    }
+                }
+            };
             // Update the __res variable in the environment.
             env.borrow_mut().assign(result_var_name, expr_value);
 
             // Only set to nil if terminated by semicolon AND it's not the last expression.
             if *terminated_by_semicolon && index < block.expressions.len() - 1 {
                 env.borrow_mut()
-                    .assign(result_var_name, Value::nil(&mut self.heap));
+                    .assign(result_var_name, Value::nil(&self.heap));
+            }
+        }
+
+        // Deferred expressions run last-registered first, in the block's scope, even when the
+        // block failed. The first error wins: a failing cleanup never hides the original one.
+        for deferred_expr in deferred.into_iter().rev() {
+            self.replace_env(Rc::clone(env));
+            if let Err(e) = self.evaluate(deferred_expr) {
+                if outcome.is_ok() {
+                    outcome = Err(e);
+                }
             }
         }
 
         // Restore the previous environment.
-        self.environment = previous_env;
+        self.replace_env(previous_env);
+        outcome?;
 
         // Return the final value stored in the __res variable.
         // It's guaranteed to exist since we initialized it.
@@ -149,7 +203,7 @@ impl Interpreter {
     }
 
     /// The core evaluation logic that dispatches based on expression type.
-    pub fn evaluate(&mut self, expression: &Expression) -> Result<Value, EasyScriptError> {
+    pub fn evaluate(&self, expression: &Expression) -> Result<Value, EasyScriptError> {
         match expression {
             Expression::Literal(val) => self.evaluate_literal(val),
 
@@ -158,43 +212,50 @@ impl Interpreter {
                 for expr in expr_list {
                     values.push(self.evaluate(expr)?);
                 }
-                Ok(Value::list(&mut self.heap, values))
+                Ok(Value::list(&self.heap, values))
+            }
+
+            Expression::TupleLiteral(expr_list) => {
+                let mut values = Vec::new();
+                for expr in expr_list {
+                    values.push(self.evaluate(expr)?);
+                }
+                Ok(Value::tuple(&self.heap, values))
             }
 
             Expression::MapLiteral(expr_pairs) => {
-                let mut map = std::collections::HashMap::<Value, Value>::new();
+                let mut map = OrderedMap::new();
                 for (key_expr, value_expr) in expr_pairs {
                     let key = self.evaluate(key_expr)?;
                     let value = self.evaluate(value_expr)?;
 
-                    // Map keys must be primitive types (String, Number, Boolean)
-                    match key.type_of() { // Check the type_of the value
-                        "string" | "number" | "boolean" => {
-                            // These types are hashable and allowed as keys
-                            map.insert(key, value);
-                        },
-                        _ => {
-                            return Err(EasyScriptError::RuntimeError {
-                                message: format!(
-                                    "Map keys must be primitive types (String, Number, Boolean), but got '{}'.",
-                                    key.type_of()
-                                ),
-                                location: None,
-                            })
-                        }
-                    };
+                    key.expect_key()
+                        .map_err(|message| runtime_error(&message))?;
+                    map.insert(key, value);
+                }
+                Ok(Value::map(&self.heap, map))
+            }
+
+            Expression::SetLiteral(element_exprs) => {
+                let mut set = OrderedMap::new();
+                for element_expr in element_exprs {
+                    let element = self.evaluate(element_expr)?;
+                    element
+                        .expect_element()
+                        .map_err(|message| runtime_error(&message))?;
+                    set.insert(element, ());
                 }
-                Ok(Value::map(&mut self.heap, map))
+                Ok(Value::set(&self.heap, set))
             }
 
             Expression::Block(block) => {
                 // Create a new scope for the block and execute it.
-                let new_env = Environment::new_enclosed(&self.environment);
+                let new_env = Environment::new_enclosed(&self.env());
                 self.execute_block(block, &new_env)
             }
 
             Expression::Identifier(name) => {
-                self.environment
+                self.env()
                     .borrow()
                     .get(name)
                     .map_err(|e| EasyScriptError::RuntimeError {
@@ -203,296 +264,161 @@ impl Interpreter {
                     })
             }
 
-            Expression::FunctionDef { params, body } => {
-                Ok(Value::function(
-                    &mut self.heap,
-                    crate::value::FunctionObjectInner::User {
-                        params: params.clone(),
-                        body: std::rc::Rc::new(body.clone()),
-                        defined_env: Rc::clone(&self.environment), // 捕获当前环境
-                    },
-                ))
+            Expression::FunctionDef {
+                name,
+                params,
+                body,
+                is_generator,
+                doc,
+                ..
+            } => Ok(Value::function(
+                &self.heap,
+                crate::value::FunctionObjectInner::User {
+                    name: name.clone(),
+                    params: params.clone(),
+                    body: std::rc::Rc::new(body.clone()),
+                    defined_env: self.env(), // 捕获当前环境
+                    is_generator: *is_generator,
+                    doc: doc.clone(),
+                },
+            )),
+
+            Expression::Yield(value) => {
+                let yielded = match value {
+                    Some(expr) => self.evaluate(expr)?,
+                    None => Value::nil(&self.heap),
+                };
+                // The innermost running coroutine is the generator whose body contains this `yield`.
+                let coroutine = self
+                    .coroutines
+                    .borrow()
+                    .last()
+                    .map(|entry| Rc::clone(&entry.coroutine));
+                let coroutine = coroutine.ok_or_else(|| EasyScriptError::RuntimeError {
+                    message: "'yield' used outside of a running generator.".to_string(),
+                    location: None,
+                })?;
+                coroutine.yield_value(self, yielded)
             }
 
             // 新增: Let 表达式的处理
-            Expression::Let { identifier, value } => {
+            Expression::Let {
+                identifier,
+                value,
+                constant,
+                ..
+            } => {
                 let assigned_value = self.evaluate(value)?;
-                self.environment // Assigns in the current environment, allowing shadowing
+                // Assigns in the current environment, allowing shadowing
+                self.env()
                     .borrow_mut()
-                    .assign(identifier, assigned_value.clone());
+                    .declare(identifier, assigned_value.clone(), *constant)
+                    .map_err(|e| EasyScriptError::RuntimeError {
+                        message: e,
+                        location: None,
+                    })?;
                 Ok(assigned_value) // let 表达式返回被赋的值
             }
 
-            Expression::Assignment { lvalue, value } => {
-                let value_to_assign = self.evaluate(value)?;
-
-                match lvalue {
-                    crate::ast::LValue::Identifier(name) => {
-                        // Find the environment where the variable is defined.
-                        // If not found, define it in the current environment.
-                        if let Some(target_env_ref) =
-                            Environment::find_environment(&self.environment, name)
-                        {
-                            target_env_ref
-                                .borrow_mut()
-                                .assign(name, value_to_assign.clone());
-                        } else {
-                            // If not found, it's an error: variables must be declared with 'let' first.
-                            return Err(EasyScriptError::RuntimeError {
-                                message: format!("Cannot assign to undeclared variable '{}'. Use 'let' to declare it.", name),
-                                location: None,
-                            });
-                        }
-
-                        Ok(Value::nil(&mut self.heap)) // 赋值表达式现在返回 nil
-                    }
-
-                    crate::ast::LValue::IndexAccess { target, key } => {
-                        let key_val = self.evaluate(key)?;
-
-                        match &**target {
-                            Expression::Identifier(ref target_name) => {
-                                if let Some(target_env_ref) =
-                                    Environment::find_environment(&self.environment, target_name)
-                                {
-                                    let mut target_env = target_env_ref.borrow_mut();
-
-                                    if let Some(mut existing_val) =
-                                        target_env.values.remove(target_name.as_str())
-                                    {
-                                        let mut modification_err = None;
-                                        match existing_val.0.deref_mut() { // Access the inner Object mutably
-                                            Object::List(list) => {
-                                                if let Some(idx_float) = key_val.0.deref().as_number() {
-                                                    let index = *idx_float as usize;
-                                                    if index < list.len() {
-                                                        list[index] = value_to_assign.clone();
-                                                    } else {
-                                                        modification_err = Some(EasyScriptError::RuntimeError {
-                                                            message: format!("List index out of bounds for assignment: {}", idx_float),
-                                                            location: None,
-                                                        });
-                                                    }
-                                                } else {
-                                                    modification_err = Some(EasyScriptError::RuntimeError {
-                                                        message: format!("List index must be a number for assignment. Got: {}", key_val.type_of()),
-                                                            location: None,
-                                                        });
-                                                    }
-                                                }
-                                            Object::Map(map) => {
-                                                // 检查 key_val 是否是允许的键类型
-                                                match key_val.type_of() {
-                                                    "string" | "number" | "boolean" => {
-                                                        map.insert(key_val, value_to_assign.clone()); // 直接使用 key_val
-                                                    },
-                                                    _ => {
-                                                        modification_err = Some(EasyScriptError::RuntimeError {
-                                                            message: format!("Map keys must be primitive types (String, Number, Boolean) for assignment. Got: {}", key_val.type_of()),
-                                                            location: None,
-                                                        });
-                                                    }
-                                                }
-                                            }
-                                            _ => {
-                                                modification_err = Some(EasyScriptError::RuntimeError {
-                                                    message: format!("Cannot index non-list/map variable '{}'", target_name),
-                                                    location: None,
-                                                });
-                                            }
-                                        }
-
-                                        target_env
-                                            .values
-                                            .insert(target_name.clone(), existing_val);
-
-                                        if let Some(err) = modification_err {
-                                            return Err(err);
-                                        }
-                                        Ok(Value::nil(&mut self.heap))
-                                    } else {
-                                        Err(EasyScriptError::RuntimeError {
-                                        message: format!("Internal error: Variable '{}' found but could not be removed for mutation.", target_name),
-                                        location: None,
-                                    })
-                                    }
-                                } else {
-                                    Err(EasyScriptError::RuntimeError {
-                                        message: format!("Undefined variable '{}' in index assignment.", target_name),
-                                        location: None,
-                                    })
-                                }
-                            }
-                            _ => Err(EasyScriptError::RuntimeError {
-                                message: "Nested accessor assignment (e.g., obj.prop[idx]) not yet supported.".to_string(),
-                                location: None,
-                            }),
-                        }
-                    }
-                    crate::ast::LValue::DotAccess {
-                        target,
-                        property_name,
-                    } => {
-                        match &**target {
-                            Expression::Identifier(ref target_name) => {
-                                if let Some(target_env_ref) =
-                                    Environment::find_environment(&self.environment, target_name)
-                                {
-                                    let mut target_env = target_env_ref.borrow_mut();
-
-                                    if let Some(mut existing_val) =
-                                        target_env.values.remove(target_name.as_str())
-                                    {
-                                        let mut modification_err = None;
-                                        match existing_val.0.deref_mut() { // Access the inner Object mutably
-                                            Object::Map(map) => {
-                                                map.insert(
-                                                    Value::string(&mut self.heap, property_name.clone()),
-                                                    value_to_assign.clone(),
-                                                );
-                                            }
-                                            _ => {
-                                                modification_err = Some(EasyScriptError::RuntimeError {
-                                                    message: format!("Cannot use dot access on non-map variable '{}'", target_name),
-                                                    location: None,
-                                                });
-                                            }
-                                        }
-
-                                        target_env
-                                            .values
-                                            .insert(target_name.clone(), existing_val);
-
-                                        if let Some(err) = modification_err {
-                                            return Err(err);
-                                        }
-                                        Ok(value_to_assign)
-                                    } else {
-                                        Err(EasyScriptError::RuntimeError {
-                                        message: format!("Internal error: Variable '{}' found but could not be removed for mutation.", target_name),
-                                        location: None,
-                                    })
-                                    }
-                                } else {
-                                    Err(EasyScriptError::RuntimeError {
-                                        message: format!("Undefined variable '{}' in dot assignment.", target_name),
-                                        location: None,
-                                    })
-                                }
-                            }
-                            _ => Err(EasyScriptError::RuntimeError {
-                                message: "Nested accessor assignment (e.g., obj[idx].prop) not yet supported.".to_string(),
-                                location: None,
-                            }),
-                        }
+            Expression::LetDestructure {
+                identifiers,
+                value,
+                constant,
+            } => {
+                let assigned_value = self.evaluate(value)?;
+                let items = match assigned_value.0.deref() {
+                    Object::Tuple(items) | Object::List(items) => items.clone(),
+                    _ => {
+                        return Err(runtime_error(&format!(
+                            "Cannot destructure type '{}'. Expected a tuple or list.",
+                            assigned_value.type_of()
+                        )))
                     }
+                };
+                if items.len() != identifiers.len() {
+                    return Err(runtime_error(&format!(
+                        "Cannot destructure a {} of {} elements into {} names.",
+                        assigned_value.type_of(),
+                        items.len(),
+                        identifiers.len()
+                    )));
                 }
+                for (identifier, item) in identifiers.iter().zip(items) {
+                    self.env()
+                        .borrow_mut()
+                        .declare(identifier, item, *constant)
+                        .map_err(|message| runtime_error(&message))?;
+                }
+                Ok(assigned_value)
             }
 
-            Expression::Accessor { target, access } => {
-                let target_val = self.evaluate(target)?;
+            Expression::Defer(_) => Err(runtime_error(
+                "'defer' can only be used directly inside a block.",
+            )),
 
-                match access {
-                    crate::ast::AccessType::Index(key_expr) => {
-                        let key_val = self.evaluate(key_expr)?;
-
-                        match target_val.0.deref() {
-                            Object::List(list) => {
-                                if let Some(idx_float) = key_val.0.deref().as_number() {
-                                    let index = *idx_float as usize; // Cast to usize for list indexing
-
-                                    if let Some(val) = list.get(index) {
-                                        Ok(val.clone())
-                                    } else {
-                                        Err(EasyScriptError::RuntimeError {
-                                            message: format!(
-                                                "List index out of bounds: {}",
-                                                idx_float
-                                            ),
-                                            location: None,
-                                        })
-                                    }
-                                } else {
-                                    Err(EasyScriptError::RuntimeError {
-                                        message: format!(
-                                            "List index must be a number. Got: {}",
-                                            key_val.type_of()
-                                        ),
-                                        location: None,
-                                    })
-                                }
-                            }
+            Expression::EnumDef { name, variants } => {
+                let mut namespace = OrderedMap::new();
+                for variant in variants {
+                    let info = Rc::new(EnumVariant {
+                        enum_name: name.clone(),
+                        name: variant.name.clone(),
+                        fields: variant.fields.clone().unwrap_or_default(),
+                    });
+                    // A variant with fields is constructed by calling it; one without is a value.
+                    let member = match variant.fields {
+                        Some(_) => Value::function(
+                            &self.heap,
+                            FunctionObjectInner::Native {
+                                function: crate::native::enum_constructor(info),
+                                signature: None,
+                            },
+                        ),
+                        None => Value::enum_value(
+                            &self.heap,
+                            EnumValue {
+                                variant: info,
+                                fields: Vec::new(),
+                            },
+                        ),
+                    };
+                    let key = Value::string(&self.heap, variant.name.clone());
+                    namespace.insert(key, member);
+                }
+                let namespace = Value::map(&self.heap, namespace);
+                namespace.freeze();
+                self.env()
+                    .borrow_mut()
+                    .declare(name, namespace.clone(), true)
+                    .map_err(|message| runtime_error(&message))?;
+                Ok(namespace)
+            }
 
-                            Object::Map(map) => {
-                                // 检查 key_val 是否是允许的键类型
-                                match key_val.type_of() {
-                                    "string" | "number" | "boolean" => {
-                                        if let Some(val) = map.get(&key_val) { // 修改这里
-                                            Ok(val.clone())
-                                        } else {
-                                            Ok(Value::nil(&mut self.heap)) // Return nil if property not found in map
-                                        }
-                                    },
-                                    _ => {
-                                        return Err(EasyScriptError::RuntimeError {
-                                            message: format!(
-                                                "Map keys must be primitive types (String, Number, Boolean). Got: {}",
-                                                key_val.type_of()
-                                            ),
-                                            location: None,
-                                        })
-                                    }
-                                }
-                            }
+            Expression::Match { subject, arms } => {
+                let subject_val = self.evaluate(subject)?;
+                // Like `__iterator` in a for-in loop, a hidden variable keeps the subject a GC
+                // root while the arms run.
+                let match_env = Environment::new_enclosed(&self.env());
+                match_env
+                    .borrow_mut()
+                    .assign("__subject", subject_val.clone());
+                let original_env = self.replace_env(match_env);
+                let result = self.run_match(&subject_val, arms);
+                self.replace_env(original_env);
+                result
+            }
 
-                            _ => Err(EasyScriptError::RuntimeError {
-                                message: format!(
-                                    "Cannot index non-list/map type: {}",
-                                    target_val.type_of()
-                                ),
-                                location: None,
-                            }),
-                        }
-                    }
+            Expression::Assignment { lvalue, value, .. } => {
+                self.evaluate_assignment(lvalue, None, value)
+            }
 
-                    crate::ast::AccessType::Dot(property_name) => {
-                        // 1. Check for built-in methods first
-                        if let Some(methods_for_type) =
-                            self.builtin_methods.get(target_val.type_of())
-                        {
-                            if methods_for_type.contains_key(property_name.as_str()) {
-                                // Found a built-in method, return a BoundMethod
-                                return Ok(Value::bound_method(
-                                    &mut self.heap,
-                                    BoundMethodInner {
-                                        receiver: target_val.clone(),
-                                        method_name: property_name.clone(),
-                                    },
-                                ));
-                            }
-                        }
+            Expression::CompoundAssignment {
+                lvalue, op, value, ..
+            } => self.evaluate_assignment(lvalue, Some(*op), value),
 
-                        // 2. Fallback to map property lookup if not a built-in method
-                        if target_val.type_of() == "map" {
-                            let key_val = Value::string(&mut self.heap, property_name.clone());
-                            if let Some(val) = target_val.0.deref().as_map().unwrap().get(&key_val)
-                            {
-                                Ok(val.clone())
-                            } else {
-                                Ok(Value::nil(&mut self.heap)) // Return nil if property not found in map
-                            }
-                        } else {
-                            // If not a map and no built-in method found
-                            Err(EasyScriptError::RuntimeError {
-                                message: format!(
-                                    "Cannot use dot access on type '{}'. No method '{}' or map key found.",
-                                    target_val.type_of(),
-                                    property_name
-                                ),
-                                location: None,
-                            })
-                        }
-                    }
+            Expression::Accessor { .. } | Expression::Call { .. } => {
+                match self.evaluate_chain(expression)? {
+                    Some(value) => Ok(value),
+                    None => Ok(Value::nil(&self.heap)),
                 }
             }
 
@@ -505,13 +431,13 @@ impl Interpreter {
 
                 if condition_val.is_truthy() {
                     // Create a new scope for the block and execute it.
-                    let new_env = Environment::new_enclosed(&self.environment);
+                    let new_env = Environment::new_enclosed(&self.env());
                     self.execute_block(then_block, &new_env)
                 } else if let Some(else_expr) = else_branch {
                     // The else_branch can be another IfExpression or a BlockExpression
                     self.evaluate(else_expr) // Evaluate the else expression (which could be a block or another if)
                 } else {
-                    Ok(Value::nil(&mut self.heap)) // No else branch, condition false, so return nil
+                    Ok(Value::nil(&self.heap)) // No else branch, condition false, so return nil
                 }
             }
 
@@ -522,72 +448,19 @@ impl Interpreter {
                 body,
             } => {
                 let iterable_val = self.evaluate(iterable)?;
-                let mut collected_values = Vec::new(); // Collect results here
-
-                match iterable_val.0.deref() {
-                    Object::List(list) => {
-                        for element in list.iter() {
-                            let loop_env = Environment::new_enclosed(&self.environment);
-                            {
-                                let mut borrowed_env = loop_env.borrow_mut();
-                                borrowed_env.assign(identifier, element.clone());
-                            }
-
-                            // Evaluate the condition (if present) in the loop's environment
-                            let should_execute_body = if let Some(cond_expr) = &condition {
-                                // Temporarily switch interpreter's environment for condition evaluation
-                                let original_env_rc = Rc::clone(&self.environment);
-                                self.environment = Rc::clone(&loop_env);
-                                let cond_val = self.evaluate(cond_expr)?;
-                                self.environment = original_env_rc; // Restore original environment
-                                cond_val.is_truthy()
-                            } else {
-                                true // No condition, so always execute
-                            };
-
-                            if should_execute_body {
-                                let iteration_result = self.execute_block(body, &loop_env)?;
-                                collected_values.push(iteration_result);
-                            }
-                        }
-                    }
-                    Object::Map(map) => {
-                        for (key, _value) in map.iter() {
-                            // Iterate over keys for maps
-                            let loop_env = Environment::new_enclosed(&self.environment);
-                            {
-                                let mut borrowed_env = loop_env.borrow_mut();
-                                borrowed_env.assign(identifier, key.clone());
-                            }
+                let iterator = crate::native::make_iterator(&self.heap, &iterable_val)?;
 
-                            // Evaluate the condition (if present) in the loop's environment
-                            let should_execute_body = if let Some(cond_expr) = &condition {
-                                let original_env_rc = Rc::clone(&self.environment);
-                                self.environment = Rc::clone(&loop_env);
-                                let cond_val = self.evaluate(cond_expr)?;
-                                self.environment = original_env_rc;
-                                cond_val.is_truthy()
-                            } else {
-                                true // No condition, so always execute
-                            };
-
-                            if should_execute_body {
-                                let iteration_result = self.execute_block(body, &loop_env)?;
-                                collected_values.push(iteration_result);
-                            }
-                        }
-                    }
-                    _ => {
-                        return Err(EasyScriptError::RuntimeError {
-                            message: format!(
-                                "Can only iterate over lists or maps. Got: {}",
-                                iterable_val.type_of()
-                            ),
-                            location: None,
-                        })
-                    }
-                }
-                Ok(Value::list(&mut self.heap, collected_values)) // Return the collected list
+                // The iterator may only be referenced from here. Like `__res`, a hidden variable
+                // makes it a GC root for the whole loop, even while a generator is suspended
+                // inside the loop body.
+                let iteration_env = Environment::new_enclosed(&self.env());
+                iteration_env
+                    .borrow_mut()
+                    .assign("__iterator", iterator.clone());
+                let original_env = self.replace_env(iteration_env);
+                let result = self.run_for_in(identifier, &iterator, condition.as_deref(), body);
+                self.replace_env(original_env);
+                result
             }
 
             Expression::ForCondition { condition, body } => {
@@ -599,138 +472,45 @@ impl Interpreter {
 
                     if condition_val.is_truthy() {
                         // Create a new scope for the body of each iteration
-                        let loop_env = Environment::new_enclosed(&self.environment);
+                        let loop_env = Environment::new_enclosed(&self.env());
                         let iteration_result = self.execute_block(body, &loop_env)?;
                         collected_values.push(iteration_result);
                     } else {
                         break; // Condition is false, exit loop
                     }
                 }
-                Ok(Value::list(&mut self.heap, collected_values)) // Return the collected list of results
+                Ok(Value::list(&self.heap, collected_values)) // Return the collected list of results
             }
 
             Expression::Unary { op, expr } => {
                 let right_val = self.evaluate(expr)?;
                 match op {
-                    crate::ast::UnaryOperator::Negate => {
-                        if let Some(num) = right_val.0.deref().as_number() {
-                            Ok(Value::number(&mut self.heap, -num))
-                        } else {
-                            Err(EasyScriptError::RuntimeError {
-                                message: format!(
-                                    "Unary '-' operator can only be applied to numbers. Got: {}",
-                                    right_val.type_of()
-                                ),
-                                location: None,
-                            })
-                        }
-                    }
+                    crate::ast::UnaryOperator::Negate => match right_val.0.deref() {
+                        Object::Integer(i) => Ok(match i.checked_neg() {
+                            Some(negated) => Value::integer(&self.heap, negated),
+                            None => Value::big_integer(&self.heap, -&BigInt::from_i64(*i)),
+                        }),
+                        Object::BigInt(b) => Ok(Value::big_integer(&self.heap, -b)),
+                        Object::Decimal(d) => Ok(Value::decimal(&self.heap, d.neg())),
+                        Object::Number(n) => Ok(Value::number(&self.heap, -n)),
+                        _ => Err(EasyScriptError::RuntimeError {
+                            message: format!(
+                                "Unary '-' operator can only be applied to numbers. Got: {}",
+                                right_val.type_of()
+                            ),
+                            location: None,
+                        }),
+                    },
                     crate::ast::UnaryOperator::Not => {
                         let is_truthy = right_val.is_truthy();
-                        Ok(Value::boolean(&mut self.heap, !is_truthy))
-                    }
-                }
-            }
-
-            Expression::Call { callee, args } => {
-                let callee_val = self.evaluate(callee)?;
-                let mut arg_vals = Vec::new();
-                for arg_expr in args {
-                    arg_vals.push(self.evaluate(arg_expr)?);
-                }
-
-                match callee_val.0.deref() {
-                    crate::value::Object::Function(func_obj) => match func_obj {
-                        crate::value::FunctionObjectInner::User {
-                            params,
-                            body,
-                            defined_env,
-                        } => {
-                            if params.len() != arg_vals.len() {
-                                return Err(EasyScriptError::RuntimeError {
-                                    message: format!(
-                                        "Expected {} arguments but got {}.",
-                                        params.len(),
-                                        arg_vals.len()
-                                    ),
-                                    location: None,
-                                });
-                            }
-
-                            // Create a new environment for the function call,
-                            // based on the environment where the function was defined (closure)
-                            let function_env = Environment::new_enclosed(&defined_env); // 使用 defined_env
-                            {
-                                let mut borrowed_env = function_env.borrow_mut();
-                                for (param_name, arg_val) in params.iter().zip(arg_vals.into_iter())
-                                {
-                                    borrowed_env.assign(param_name, arg_val);
-                                }
-                            }
-                            // Execute the function body in the new environment
-                            self.execute_block(&body, &function_env)
-                        }
-                        crate::value::FunctionObjectInner::Native(native_fn) => {
-                            // 调用原生函数
-                            native_fn(&mut self.heap, &self.environment, arg_vals).map_err(|e| {
-                                EasyScriptError::RuntimeError {
-                                    message: e,
-                                    location: None,
-                                }
-                            })
-                        }
-                    },
-                    crate::value::Object::BoundMethod(bound_method_inner) => {
-                        let receiver = bound_method_inner.receiver.clone();
-                        let method_name = bound_method_inner.method_name.clone();
-
-                        // Look up the actual native function from the interpreter's built-in methods
-                        if let Some(methods_for_type) = self.builtin_methods.get(receiver.type_of())
-                        {
-                            if let Some(native_method_fn) =
-                                methods_for_type.get(method_name.as_str())
-                            {
-                                // Prepend the receiver to the arguments
-                                let mut full_args = vec![receiver];
-                                full_args.extend(arg_vals);
-
-                                native_method_fn(&mut self.heap, &self.environment, full_args)
-                                    .map_err(|e| EasyScriptError::RuntimeError {
-                                        message: e,
-                                        location: None,
-                                    })
-                            } else {
-                                // This should ideally not happen if Accessor correctly returns BoundMethod
-                                Err(EasyScriptError::RuntimeError {
-                                    message: format!(
-                                        "Internal error: Bound method '{}' not found for type '{}'.",
-                                        method_name,
-                                        receiver.type_of()
-                                    ),
-                                    location: None,
-                                })
-                            }
-                        } else {
-                            Err(EasyScriptError::RuntimeError {
-                                message: format!(
-                                    "Internal error: No built-in methods registered for type '{}'.",
-                                    receiver.type_of()
-                                ),
-                                location: None,
-                            })
-                        }
+                        Ok(Value::boolean(&self.heap, !is_truthy))
                     }
-                    _ => Err(EasyScriptError::RuntimeError {
-                        message: format!(
-                            "Cannot call non-function or non-method value: {}",
-                            callee_val
-                        ),
-                        location: None,
-                    }),
                 }
             }
 
-            Expression::Binary { left, op, right } => {
+            Expression::Binary {
+                left, op, right, ..
+            } => {
                 let left_val = self.evaluate(left)?;
                 // Short-circuiting for logical operators
                 match op {
@@ -748,127 +528,1436 @@ impl Interpreter {
                         let right_val = self.evaluate(right)?;
                         return Ok(right_val);
                     }
+                    BinaryOperator::NilCoalesce => {
+                        if !matches!(left_val.0.deref(), Object::Nil) {
+                            return Ok(left_val);
+                        }
+                        return self.evaluate(right);
+                    }
                     _ => {}
                 }
 
                 let right_val = self.evaluate(right)?; // Evaluate right_val only if not short-circuited
-                use crate::ast::BinaryOperator;
+                self.apply_binary_operator(*op, left_val, right_val)
+            }
+        }
+    }
 
-                match op {
-                    BinaryOperator::Eq => {
-                        return Ok(Value::boolean(&mut self.heap, left_val == right_val))
+    /// Evaluates a chain of accesses and calls. Returns `None` when an optional link
+    /// (`?.`, `?[` or `?.(`) meets `nil`, which short-circuits the rest of the chain.
+    fn evaluate_chain(&self, expression: &Expression) -> Result<Option<Value>, EasyScriptError> {
+        match expression {
+            Expression::Accessor {
+                target,
+                access,
+                optional,
+            } => {
+                let Some(target_val) = self.evaluate_chain(target)? else {
+                    return Ok(None);
+                };
+                if *optional && matches!(target_val.0.deref(), Object::Nil) {
+                    return Ok(None);
+                }
+                self.access_value(target_val, access).map(Some)
+            }
+
+            Expression::Call {
+                callee,
+                args,
+                optional,
+                ..
+            } => {
+                let Some(callee_val) = self.evaluate_chain(callee)? else {
+                    return Ok(None);
+                };
+                if *optional && matches!(callee_val.0.deref(), Object::Nil) {
+                    return Ok(None);
+                }
+                let call_args = self.evaluate_arguments(args)?;
+                self.call_value_with(&callee_val, call_args).map(Some)
+            }
+
+            _ => self.evaluate(expression).map(Some),
+        }
+    }
+
+    /// Applies an index or dot access to an evaluated target.
+    fn access_value(
+        &self,
+        target_val: Value,
+        access: &AccessType,
+    ) -> Result<Value, EasyScriptError> {
+        match access {
+            AccessType::Index(key_expr) => {
+                let key_val = self.evaluate(key_expr)?;
+                self.index_value(&target_val, &key_val)
+            }
+
+            AccessType::Slice(bounds) => {
+                let slice = self.evaluate_slice(bounds)?;
+                self.slice_value(&target_val, slice)
+            }
+
+            AccessType::Dot(property_name) => {
+                // 1. Check for built-in methods first
+                if let Some(methods_for_type) = self.builtin_methods.get(target_val.type_of()) {
+                    if methods_for_type.contains_key(property_name.as_str()) {
+                        // Found a built-in method, return a BoundMethod
+                        return Ok(Value::bound_method(
+                            &self.heap,
+                            BoundMethodInner {
+                                receiver: target_val.clone(),
+                                method_name: property_name.clone(),
+                                function: None,
+                            },
+                        ));
                     }
-                    BinaryOperator::Neq => {
-                        return Ok(Value::boolean(&mut self.heap, left_val != right_val))
+                }
+
+                // 2. The fields of an enum value are read by name
+                if let Object::Enum(value) = target_val.0.deref() {
+                    return value.field(property_name).cloned().ok_or_else(|| {
+                        runtime_error(&format!(
+                            "{} has no field '{}'.",
+                            value.variant.qualified_name(),
+                            property_name
+                        ))
+                    });
+                }
+
+                // 3. Fallback to map property lookup (own keys, then the prototype chain)
+                if target_val.type_of() == "map" {
+                    match self.lookup_method(&target_val, property_name) {
+                        Some(val) => Ok(val),
+                        None => Ok(Value::nil(&self.heap)), // Return nil if property not found in map
                     }
-                    _ => {}
+                } else {
+                    // If not a map and no built-in method found
+                    Err(EasyScriptError::RuntimeError {
+                        message: format!(
+                            "Cannot use dot access on type '{}'. No method '{}' or map key found.",
+                            target_val.type_of(),
+                            property_name
+                        ),
+                        location: None,
+                    })
                 }
+            }
+        }
+    }
 
-                let left_obj = left_val.0.deref();
-                let right_obj = right_val.0.deref();
+    /// The native and signature of builtin method `name` of values of type `type_name`.
+    pub(crate) fn builtin_method(
+        &self,
+        type_name: &str,
+        name: &str,
+    ) -> Option<&(NativeFunction, Signature)> {
+        self.builtin_methods.get(type_name)?.get(name)
+    }
 
-                match (left_obj, right_obj) {
-                    (Object::Number(l), Object::Number(r)) => match op {
-                        BinaryOperator::Add => Ok(Value::number(&mut self.heap, l + r)),
-                        BinaryOperator::Sub => Ok(Value::number(&mut self.heap, l - r)),
-                        BinaryOperator::Mul => Ok(Value::number(&mut self.heap, l * r)),
-                        BinaryOperator::Div => {
-                            if *r == 0.0 {
-                                Err(EasyScriptError::RuntimeError {
-                                    message: "Division by zero.".to_string(),
-                                    location: None,
-                                })
-                            } else {
-                                Ok(Value::number(&mut self.heap, l / r))
-                            }
+    /// Looks `name` up on a map, then its prototype chain, the way `.` does. A user function
+    /// found there is a method: it comes back bound to the map, so calling it passes the map
+    /// as `self`. Natives and already bound methods are returned as is.
+    pub(crate) fn lookup_method(&self, map: &Value, name: &str) -> Option<Value> {
+        let value = map.lookup_property_str(name)?;
+        if !matches!(
+            value.0.deref(),
+            Object::Function(FunctionObjectInner::User { .. })
+        ) {
+            return Some(value);
+        }
+        Some(Value::bound_method(
+            &self.heap,
+            BoundMethodInner {
+                receiver: map.clone(),
+                method_name: name.to_string(),
+                function: Some(value),
+            },
+        ))
+    }
+
+    /// Evaluates `lvalue = value`, or `lvalue op= value` when `op` is given. The target and
+    /// index of the lvalue are evaluated exactly once, before the right-hand side.
+    fn evaluate_assignment(
+        &self,
+        lvalue: &LValue,
+        op: Option<BinaryOperator>,
+        value: &Expression,
+    ) -> Result<Value, EasyScriptError> {
+        match lvalue {
+            LValue::Identifier(name) => {
+                // Find the environment where the variable is defined.
+                let Some(target_env_ref) = Environment::find_environment(&self.env(), name) else {
+                    // Variables must be declared with 'let' first.
+                    return Err(EasyScriptError::RuntimeError {
+                        message: format!(
+                            "Cannot assign to undeclared variable '{}'. Use 'let' to declare it.",
+                            name
+                        ),
+                        location: None,
+                    });
+                };
+                if target_env_ref.borrow().is_constant(name) {
+                    return Err(EasyScriptError::RuntimeError {
+                        message: format!("Cannot assign to constant '{}'.", name),
+                        location: None,
+                    });
+                }
+                let current = match op {
+                    Some(_) => Some(target_env_ref.borrow().get(name).map_err(|e| {
+                        EasyScriptError::RuntimeError {
+                            message: e,
+                            location: None,
                         }
-                        BinaryOperator::Mod => Ok(Value::number(&mut self.heap, l % r)),
-                        BinaryOperator::BitAnd => Ok(Value::number(
-                            &mut self.heap,
-                            (*l as i64 & *r as i64) as f64,
-                        )),
-                        BinaryOperator::BitOr => Ok(Value::number(
-                            &mut self.heap,
-                            (*l as i64 | *r as i64) as f64,
-                        )),
-                        BinaryOperator::BitXor => Ok(Value::number(
-                            &mut self.heap,
-                            (*l as i64 ^ *r as i64) as f64,
-                        )),
-                        BinaryOperator::ShL => {
-                            if *r < 0.0 {
-                                return Err(EasyScriptError::RuntimeError {
-                                    message: "Shift amount cannot be negative.".to_string(),
-                                    location: None,
-                                });
+                    })?),
+                    None => None,
+                };
+                let new_value = self.assigned_value(op, current, value)?;
+                target_env_ref.borrow_mut().assign(name, new_value);
+
+                Ok(Value::nil(&self.heap)) // 赋值表达式现在返回 nil
+            }
+
+            LValue::IndexAccess { target, key } => {
+                let target_val = self.evaluate(target)?;
+                let key_val = self.evaluate(key)?;
+                let current = match op {
+                    Some(_) => Some(self.index_value(&target_val, &key_val)?),
+                    None => None,
+                };
+                let new_value = self.assigned_value(op, current, value)?;
+                ensure_mutable(&target_val)?;
+                if let Some(function) = target_val.metamethod("__setindex") {
+                    self.call_value(&function, vec![target_val, key_val, new_value])?;
+                    return Ok(Value::nil(&self.heap));
+                }
+
+                let mut target_handle = target_val.0;
+                match target_handle.deref_mut() {
+                    Object::List(list) => {
+                        let index = key_val.expect_integer("List index").map_err(|message| {
+                            EasyScriptError::RuntimeError {
+                                message,
+                                location: None,
                             }
-                            Ok(Value::number(
-                                &mut self.heap,
-                                (*l as i64).wrapping_shl(*r as u32) as f64,
-                            ))
-                        }
-                        BinaryOperator::ShR => {
-                            if *r < 0.0 {
+                        })?;
+                        match resolve_index(index, list.len()) {
+                            Some(i) => list[i] = new_value,
+                            None => {
                                 return Err(EasyScriptError::RuntimeError {
-                                    message: "Shift amount cannot be negative.".to_string(),
+                                    message: format!(
+                                        "List index out of bounds for assignment: {}",
+                                        index
+                                    ),
                                     location: None,
                                 });
                             }
-                            Ok(Value::number(
-                                &mut self.heap,
-                                (*l as i64).wrapping_shr(*r as u32) as f64,
-                            ))
-                        }
-                        BinaryOperator::Lt => Ok(Value::boolean(&mut self.heap, l < r)),
-                        BinaryOperator::Lte => Ok(Value::boolean(&mut self.heap, l <= r)),
-                        BinaryOperator::Gt => Ok(Value::boolean(&mut self.heap, l > r)),
-                        BinaryOperator::Gte => Ok(Value::boolean(&mut self.heap, l >= r)),
-                        _ => Err(EasyScriptError::RuntimeError {
-                            message: format!("Unsupported operator '{:?}' for numbers.", op),
-                            location: None,
-                        }),
-                    },
-                    (Object::String(l), Object::String(r)) => match op {
-                        BinaryOperator::Add => {
-                            Ok(Value::string(&mut self.heap, format!("{}{}", l, r)))
-                        }
-                        _ => Err(EasyScriptError::RuntimeError {
-                            message: format!("Unsupported operator '{:?}' for strings.", op),
-                            location: None,
-                        }),
-                    },
-                    (Object::List(l), Object::List(r)) => match op {
-                        BinaryOperator::Add => {
-                            let mut new_list = l.to_vec();
-                            new_list.extend_from_slice(r);
-                            Ok(Value::list(&mut self.heap, new_list))
                         }
-                        _ => Err(EasyScriptError::RuntimeError {
-                            message: format!("Unsupported operator '{:?}' for lists.", op),
+                    }
+                    Object::Map(map) => {
+                        key_val
+                            .expect_key()
+                            .map_err(|message| runtime_error(&message))?;
+                        map.entries.insert(key_val, new_value);
+                    }
+                    Object::Tuple(_) => {
+                        return Err(runtime_error(
+                            "Cannot assign to an element of a tuple. Tuples are immutable.",
+                        ));
+                    }
+                    Object::Bytes(_) => {
+                        return Err(runtime_error(
+                            "Cannot assign to an element of bytes. Bytes are immutable.",
+                        ));
+                    }
+                    _ => {
+                        return Err(EasyScriptError::RuntimeError {
+                            message: format!(
+                                "Cannot index non-list/map type for assignment: {}",
+                                target_val.type_of()
+                            ),
                             location: None,
-                        }),
-                    },
-                    (_l, _r) => Err(EasyScriptError::RuntimeError {
-                        message: format!(
-                            "Cannot apply operator '{:?}' to unsupported types: {} and {}",
-                            op,
-                            left_val.type_of(),
-                            right_val.type_of()
-                        ),
+                        });
+                    }
+                }
+                Ok(Value::nil(&self.heap))
+            }
+
+            LValue::SliceAccess { target, bounds } => {
+                let target_val = self.evaluate(target)?;
+                let slice = self.evaluate_slice(bounds)?;
+                let current = match op {
+                    Some(_) => Some(self.slice_value(&target_val, slice)?),
+                    None => None,
+                };
+                let new_value = self.assigned_value(op, current, value)?;
+                self.assign_slice(&target_val, slice, &new_value)?;
+                Ok(Value::nil(&self.heap))
+            }
+
+            LValue::DotAccess {
+                target,
+                property_name,
+            } => {
+                let target_val = self.evaluate(target)?;
+                if target_val.type_of() != "map" {
+                    return Err(EasyScriptError::RuntimeError {
+                        message: format!(
+                            "Cannot use dot access on non-map type '{}' for assignment.",
+                            target_val.type_of()
+                        ),
+                        location: None,
+                    });
+                }
+                let key_val = Value::string(&self.heap, property_name.clone());
+                // Dot access bypasses `__index`/`__setindex`: `obj.x op= v` reads `x` like
+                // `obj.x` does (own key, then the prototype chain) and writes an own key.
+                let current = op.map(|_| match target_val.lookup_property(&key_val) {
+                    Some(current) => current,
+                    None => Value::nil(&self.heap),
+                });
+                let new_value = self.assigned_value(op, current, value)?;
+                ensure_mutable(&target_val)?;
+
+                let mut target_handle = target_val.0;
+                if let Object::Map(map) = target_handle.deref_mut() {
+                    map.entries.insert(key_val, new_value.clone());
+                }
+                Ok(new_value)
+            }
+        }
+    }
+
+    /// Replaces `target[start:end:step]` with the elements of `new_value`. A step-1 slice may
+    /// be replaced by any number of elements; other steps need exactly one per position.
+    fn assign_slice(
+        &self,
+        target_val: &Value,
+        slice: Slice,
+        new_value: &Value,
+    ) -> Result<(), EasyScriptError> {
+        let replacement = match new_value.0.deref() {
+            Object::List(items) => items.clone(),
+            _ => {
+                return Err(runtime_error(&format!(
+                    "Slice assignment expects a list, but got type '{}'.",
+                    new_value.type_of()
+                )))
+            }
+        };
+        ensure_mutable(target_val)?;
+        let mut target_handle = target_val.0;
+        let Object::List(list) = target_handle.deref_mut() else {
+            return Err(runtime_error(&format!(
+                "Cannot assign to a slice of type '{}'. Only lists support slice assignment.",
+                target_val.type_of()
+            )));
+        };
+        let to_error = |message: String| runtime_error(&message);
+        if let Some(range) = slice.contiguous_range(list.len()).map_err(to_error)? {
+            list.splice(range, replacement);
+            return Ok(());
+        }
+        let positions = slice.positions(list.len()).map_err(to_error)?;
+        if positions.len() != replacement.len() {
+            return Err(runtime_error(&format!(
+                "Cannot assign {} values to a slice with step {} that selects {} elements.",
+                replacement.len(),
+                slice.step().map_err(to_error)?,
+                positions.len()
+            )));
+        }
+        for (i, value) in positions.into_iter().zip(replacement) {
+            list[i] = value;
+        }
+        Ok(())
+    }
+
+    /// Evaluates the right-hand side of an assignment and, for a compound assignment,
+    /// combines it with the lvalue's `current` value.
+    fn assigned_value(
+        &self,
+        op: Option<BinaryOperator>,
+        current: Option<Value>,
+        value: &Expression,
+    ) -> Result<Value, EasyScriptError> {
+        let value = self.evaluate(value)?;
+        match (op, current) {
+            (Some(op), Some(current)) => self.apply_binary_operator(op, current, value),
+            _ => Ok(value),
+        }
+    }
+
+    /// Reads `target[key]` for a list, tuple, string, bytes or map.
+    fn index_value(&self, target_val: &Value, key_val: &Value) -> Result<Value, EasyScriptError> {
+        if let Some(function) = target_val.metamethod("__index") {
+            return self.call_value(&function, vec![target_val.clone(), key_val.clone()]);
+        }
+        match target_val.0.deref() {
+            Object::List(list) => {
+                let index = key_val.expect_integer("List index").map_err(|message| {
+                    EasyScriptError::RuntimeError {
+                        message,
+                        location: None,
+                    }
+                })?;
+                match resolve_index(index, list.len()) {
+                    Some(i) => Ok(list[i].clone()),
+                    None => Err(EasyScriptError::RuntimeError {
+                        message: format!("List index out of bounds: {}", index),
                         location: None,
                     }),
                 }
             }
+
+            Object::Tuple(items) => {
+                let index = key_val
+                    .expect_integer("Tuple index")
+                    .map_err(|message| runtime_error(&message))?;
+                match resolve_index(index, items.len()) {
+                    Some(i) => Ok(items[i].clone()),
+                    None => Err(runtime_error(&format!(
+                        "Tuple index out of bounds: {}",
+                        index
+                    ))),
+                }
+            }
+
+            Object::Bytes(bytes) => {
+                let index = key_val
+                    .expect_integer("Bytes index")
+                    .map_err(|message| runtime_error(&message))?;
+                match resolve_index(index, bytes.len()) {
+                    Some(i) => Ok(Value::integer(&self.heap, bytes[i] as i64)),
+                    None => Err(runtime_error(&format!(
+                        "Bytes index out of bounds: {}",
+                        index
+                    ))),
+                }
+            }
+
+            Object::String(s) => {
+                let index = key_val.expect_integer("String index").map_err(|message| {
+                    EasyScriptError::RuntimeError {
+                        message,
+                        location: None,
+                    }
+                })?;
+                let len = s.chars().count();
+                match resolve_index(index, len).and_then(|i| s.chars().nth(i)) {
+                    Some(c) => Ok(Value::string(&self.heap, c.to_string())),
+                    None => Err(EasyScriptError::RuntimeError {
+                        message: format!("String index out of bounds: {}", index),
+                        location: None,
+                    }),
+                }
+            }
+
+            Object::Map(map) => {
+                key_val
+                    .expect_key()
+                    .map_err(|message| runtime_error(&message))?;
+                match map.entries.get(key_val) {
+                    Some(val) => Ok(val.clone()),
+                    None => Ok(Value::nil(&self.heap)), // Return nil if property not found in map
+                }
+            }
+
+            _ => Err(EasyScriptError::RuntimeError {
+                message: format!(
+                    "Cannot index non-list/map/string type: {}",
+                    target_val.type_of()
+                ),
+                location: None,
+            }),
+        }
+    }
+
+    /// Evaluates the bounds of `[start:end:step]`, in that order. A nil bound counts as omitted.
+    fn evaluate_slice(&self, bounds: &SliceBounds) -> Result<Slice, EasyScriptError> {
+        let evaluate_bound = |bound: &Option<Box<Expression>>, what: &str| {
+            let Some(expr) = bound else {
+                return Ok(None);
+            };
+            let value = self.evaluate(expr)?;
+            if value.type_of() == "nil" {
+                return Ok(None);
+            }
+            value
+                .expect_integer(what)
+                .map(Some)
+                .map_err(|message| runtime_error(&message))
+        };
+        Ok(Slice {
+            start: evaluate_bound(&bounds.start, "Slice start")?,
+            end: evaluate_bound(&bounds.end, "Slice end")?,
+            step: evaluate_bound(&bounds.step, "Slice step")?,
+        })
+    }
+
+    /// Reads `target[start:end:step]` for a list, tuple, string or bytes, as a new value of
+    /// its type.
+    fn slice_value(&self, target_val: &Value, slice: Slice) -> Result<Value, EasyScriptError> {
+        match target_val.0.deref() {
+            Object::List(list) => {
+                let positions = slice
+                    .positions(list.len())
+                    .map_err(|message| runtime_error(&message))?;
+                let items = positions.into_iter().map(|i| list[i].clone()).collect();
+                Ok(Value::list(&self.heap, items))
+            }
+            Object::Tuple(tuple) => {
+                let positions = slice
+                    .positions(tuple.len())
+                    .map_err(|message| runtime_error(&message))?;
+                let items = positions.into_iter().map(|i| tuple[i].clone()).collect();
+                Ok(Value::tuple(&self.heap, items))
+            }
+            Object::Bytes(bytes) => {
+                let positions = slice
+                    .positions(bytes.len())
+                    .map_err(|message| runtime_error(&message))?;
+                let sliced = positions.into_iter().map(|i| bytes[i]).collect();
+                Ok(Value::bytes(&self.heap, sliced))
+            }
+            Object::String(s) => {
+                let chars: Vec<char> = s.chars().collect();
+                let positions = slice
+                    .positions(chars.len())
+                    .map_err(|message| runtime_error(&message))?;
+                let sliced = positions.into_iter().map(|i| chars[i]).collect();
+                Ok(Value::string(&self.heap, sliced))
+            }
+            _ => Err(runtime_error(&format!(
+                "Cannot slice type '{}'. Only lists, tuples, strings and bytes can be sliced.",
+                target_val.type_of()
+            ))),
         }
     }
 
+    /// Evaluates `item in container`: an element of a list or tuple (compared with `==`), an
+    /// own key of a map, an element of a set, a substring of a string, a byte or run of bytes
+    /// in bytes, or a value a `range()` would produce.
+    fn contains(&self, container: &Value, item: &Value) -> Result<bool, EasyScriptError> {
+        match container.0.deref() {
+            Object::List(list) | Object::Tuple(list) => {
+                for element in list.clone() {
+                    let equal = self.apply_binary_operator(BinaryOperator::Eq, item.clone(), element)?;
+                    if equal.is_truthy() {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Object::Map(map) => {
+                item.expect_key().map_err(|message| runtime_error(&message))?;
+                Ok(map.entries.contains_key(item))
+            }
+            Object::Set(set) => {
+                item.expect_element().map_err(|message| runtime_error(&message))?;
+                Ok(set.contains_key(item))
+            }
+            Object::Bytes(bytes) => match item.0.deref() {
+                Object::Integer(byte) => Ok(bytes.iter().any(|&b| b as i64 == *byte)),
+                Object::Bytes(needle) => Ok(needle.is_empty()
+                    || bytes.windows(needle.len()).any(|window| window == needle)),
+                _ => Err(runtime_error(&format!(
+                    "Operator 'in' can only find an integer or bytes in bytes, but got type '{}'.",
+                    item.type_of()
+                ))),
+            },
+            Object::String(s) => match item.0.deref() {
+                Object::String(needle) => Ok(s.contains(needle.as_str())),
+                _ => Err(runtime_error(&format!(
+                    "Operator 'in' can only find a string in a string, but got type '{}'.",
+                    item.type_of()
+                ))),
+            },
+            Object::Iterator(state) => state.range_contains(item.0.deref()).ok_or_else(|| {
+                runtime_error(
+                    "Operator 'in' can only search the iterator returned by range(). \
+                     Collect other iterators into a list first.",
+                )
+            }),
+            _ => Err(runtime_error(&format!(
+                "Operator 'in' expects a list, tuple, map, set, string, bytes or range on the right, but got type '{}'.",
+                container.type_of()
+            ))),
+        }
+    }
+
+    /// Applies a non-short-circuiting binary operator to two evaluated operands.
+    fn apply_binary_operator(
+        &self,
+        op: BinaryOperator,
+        left_val: Value,
+        right_val: Value,
+    ) -> Result<Value, EasyScriptError> {
+        if let Some(result) = self.apply_operator_metamethod(op, &left_val, &right_val)? {
+            return Ok(result);
+        }
+        match op {
+            BinaryOperator::Eq => return Ok(Value::boolean(&self.heap, left_val == right_val)),
+            BinaryOperator::Neq => return Ok(Value::boolean(&self.heap, left_val != right_val)),
+            BinaryOperator::Is => {
+                return Ok(Value::boolean(&self.heap, left_val.is_same(&right_val)))
+            }
+            BinaryOperator::In => {
+                let found = self.contains(&right_val, &left_val)?;
+                return Ok(Value::boolean(&self.heap, found));
+            }
+            _ => {}
+        }
+
+        let left_obj = left_val.0.deref();
+        let right_obj = right_val.0.deref();
+
+        if left_obj.is_numeric() && right_obj.is_numeric() {
+            if let Some(result) = ordering_satisfies(op, compare_numbers(left_obj, right_obj)) {
+                return Ok(Value::boolean(&self.heap, result));
+            }
+        }
+
+        match (left_obj, right_obj) {
+            (Object::Integer(l), Object::Integer(r)) => self.apply_integer_operator(op, *l, *r),
+            (Object::Integer(_) | Object::BigInt(_), Object::Integer(_) | Object::BigInt(_)) => {
+                self.apply_big_integer_operator(
+                    op,
+                    left_obj.as_big_integer().unwrap(),
+                    right_obj.as_big_integer().unwrap(),
+                )
+            }
+            (Object::Decimal(_), r) if r.is_numeric() => {
+                self.apply_decimal_operator(op, &left_val, &right_val)
+            }
+            (l, Object::Decimal(_)) if l.is_numeric() => {
+                self.apply_decimal_operator(op, &left_val, &right_val)
+            }
+            (l, r) if l.is_numeric() && r.is_numeric() => {
+                self.apply_float_operator(op, &left_val, &right_val)
+            }
+            (Object::String(l), Object::String(r)) => match op {
+                BinaryOperator::Add => Ok(Value::string(&self.heap, format!("{}{}", l, r))),
+                _ => Err(EasyScriptError::RuntimeError {
+                    message: format!("Unsupported operator '{:?}' for strings.", op),
+                    location: None,
+                }),
+            },
+            (Object::Bytes(l), Object::Bytes(r)) => match op {
+                BinaryOperator::Add => Ok(Value::bytes(&self.heap, [&l[..], r].concat())),
+                _ => Err(runtime_error(&format!(
+                    "Unsupported operator '{:?}' for bytes.",
+                    op
+                ))),
+            },
+            (Object::List(l), Object::List(r)) => match op {
+                BinaryOperator::Add => {
+                    let mut new_list = l.to_vec();
+                    new_list.extend_from_slice(r);
+                    Ok(Value::list(&self.heap, new_list))
+                }
+                _ => Err(EasyScriptError::RuntimeError {
+                    message: format!("Unsupported operator '{:?}' for lists.", op),
+                    location: None,
+                }),
+            },
+            (_l, _r) => Err(EasyScriptError::RuntimeError {
+                message: format!(
+                    "Cannot apply operator '{:?}' to unsupported types: {} and {}",
+                    op,
+                    left_val.type_of(),
+                    right_val.type_of()
+                ),
+                location: None,
+            }),
+        }
+    }
+
+    /// Applies an operator to two integers. Arithmetic is exact, and a result that does not
+    /// fit an i64 is computed again as a big integer; `/` is the one operator that produces a
+    /// float.
+    fn apply_integer_operator(
+        &self,
+        op: BinaryOperator,
+        l: i64,
+        r: i64,
+    ) -> Result<Value, EasyScriptError> {
+        if r == 0
+            && matches!(
+                op,
+                BinaryOperator::Div | BinaryOperator::FloorDiv | BinaryOperator::Mod
+            )
+        {
+            return Err(runtime_error("Division by zero."));
+        }
+        let result = match op {
+            BinaryOperator::Add => l.checked_add(r),
+            BinaryOperator::Sub => l.checked_sub(r),
+            BinaryOperator::Mul => l.checked_mul(r),
+            BinaryOperator::Div => return Ok(Value::number(&self.heap, l as f64 / r as f64)),
+            BinaryOperator::FloorDiv => l.checked_div(r),
+            BinaryOperator::Mod => l.checked_rem(r),
+            BinaryOperator::BitAnd => Some(l & r),
+            BinaryOperator::BitOr => Some(l | r),
+            BinaryOperator::BitXor => Some(l ^ r),
+            BinaryOperator::ShL | BinaryOperator::ShR => {
+                if r < 0 {
+                    return Err(runtime_error("Shift amount cannot be negative."));
+                }
+                // Amounts of 64 and up do not fit an i64 shift; the big integer path takes them.
+                let amount = u32::try_from(r).ok().filter(|&amount| amount < 64);
+                amount.and_then(|amount| {
+                    if op == BinaryOperator::ShR {
+                        Some(l >> amount)
+                    } else {
+                        // Shifting back recovers `l` unless bits were lost off the top
+                        Some(l << amount).filter(|shifted| shifted >> amount == l)
+                    }
+                })
+            }
+            _ => {
+                return Err(runtime_error(&format!(
+                    "Unsupported operator '{:?}' for numbers.",
+                    op
+                )))
+            }
+        };
+        match result {
+            Some(i) => Ok(Value::integer(&self.heap, i)),
+            None => self.apply_big_integer_operator(op, BigInt::from_i64(l), BigInt::from_i64(r)),
+        }
+    }
+
+    /// Applies an operator to two integers, at least one of them (or the result) too large for
+    /// an i64.
+    fn apply_big_integer_operator(
+        &self,
+        op: BinaryOperator,
+        l: BigInt,
+        r: BigInt,
+    ) -> Result<Value, EasyScriptError> {
+        let result = match op {
+            BinaryOperator::Add => &l + &r,
+            BinaryOperator::Sub => &l - &r,
+            BinaryOperator::Mul => &l * &r,
+            BinaryOperator::Div => {
+                if r.is_zero() {
+                    return Err(runtime_error("Division by zero."));
+                }
+                return Ok(Value::number(&self.heap, l.to_f64() / r.to_f64()));
+            }
+            BinaryOperator::FloorDiv | BinaryOperator::Mod => {
+                let (quotient, remainder) = l
+                    .div_rem(&r)
+                    .ok_or_else(|| runtime_error("Division by zero."))?;
+                if op == BinaryOperator::FloorDiv {
+                    quotient
+                } else {
+                    remainder
+                }
+            }
+            BinaryOperator::ShL => l.shl(shift_amount(&r)?),
+            BinaryOperator::ShR => l.shr(shift_amount(&r)?),
+            BinaryOperator::BitAnd | BinaryOperator::BitOr | BinaryOperator::BitXor => {
+                return Err(runtime_error(&format!(
+                    "Operator '{}' is not supported for integers beyond 64 bits.",
+                    op.symbol()
+                )))
+            }
+            _ => {
+                return Err(runtime_error(&format!(
+                    "Unsupported operator '{:?}' for numbers.",
+                    op
+                )))
+            }
+        };
+        Ok(Value::big_integer(&self.heap, result))
+    }
+
+    /// Applies an operator to two numbers, at least one of them a float: the other is
+    /// converted.
+    fn apply_float_operator(
+        &self,
+        op: BinaryOperator,
+        left_val: &Value,
+        right_val: &Value,
+    ) -> Result<Value, EasyScriptError> {
+        let l = left_val.0.deref().as_number().unwrap();
+        let r = right_val.0.deref().as_number().unwrap();
+        match op {
+            BinaryOperator::Add => Ok(Value::number(&self.heap, l + r)),
+            BinaryOperator::Sub => Ok(Value::number(&self.heap, l - r)),
+            BinaryOperator::Mul => Ok(Value::number(&self.heap, l * r)),
+            BinaryOperator::Div | BinaryOperator::FloorDiv => {
+                if r == 0.0 {
+                    Err(runtime_error("Division by zero."))
+                } else if op == BinaryOperator::FloorDiv {
+                    Ok(Value::number(&self.heap, (l / r).trunc()))
+                } else {
+                    Ok(Value::number(&self.heap, l / r))
+                }
+            }
+            BinaryOperator::Mod => Ok(Value::number(&self.heap, l % r)),
+            BinaryOperator::BitAnd
+            | BinaryOperator::BitOr
+            | BinaryOperator::BitXor
+            | BinaryOperator::ShL
+            | BinaryOperator::ShR => Err(runtime_error(&format!(
+                "Operator '{}' requires integers, but got {} and {}.",
+                op.symbol(),
+                left_val.repr_string(),
+                right_val.repr_string()
+            ))),
+            _ => Err(runtime_error(&format!(
+                "Unsupported operator '{:?}' for numbers.",
+                op
+            ))),
+        }
+    }
+
+    /// Applies an operator to a decimal and a decimal or an integer, exactly except for `/`,
+    /// which rounds as the decimal context says. Decimals do not mix with floats.
+    fn apply_decimal_operator(
+        &self,
+        op: BinaryOperator,
+        left_val: &Value,
+        right_val: &Value,
+    ) -> Result<Value, EasyScriptError> {
+        let (left_obj, right_obj) = (left_val.0.deref(), right_val.0.deref());
+        if matches!(left_obj, Object::Number(_)) || matches!(right_obj, Object::Number(_)) {
+            return Err(runtime_error(&format!(
+                "Operator '{}' cannot mix a decimal with a float, but got {} and {}. Convert one with decimal() or float().",
+                op.symbol(),
+                left_val.repr_string(),
+                right_val.repr_string()
+            )));
+        }
+        let l = left_obj.as_exact_decimal().unwrap();
+        let r = right_obj.as_exact_decimal().unwrap();
+        let result = match op {
+            BinaryOperator::Add => Some(l.add(&r)),
+            BinaryOperator::Sub => Some(l.sub(&r)),
+            BinaryOperator::Mul => Some(l.mul(&r)),
+            BinaryOperator::Div => l.div(&r, &self.decimal_context.get()),
+            BinaryOperator::FloorDiv => l.div_trunc(&r),
+            BinaryOperator::Mod => l.rem(&r),
+            _ => {
+                return Err(runtime_error(&format!(
+                    "Operator '{}' is not supported for decimals.",
+                    op.symbol()
+                )))
+            }
+        };
+        match result {
+            Some(d) => Ok(Value::decimal(&self.heap, d)),
+            None => Err(runtime_error("Division by zero.")),
+        }
+    }
+
+    /// Applies an operator overloaded by a map operand (`__add`, `__eq`, `__lt`, ...), or
+    /// returns `None` when neither operand overloads it.
+    ///
+    /// The metamethod is looked up on the left operand, then on the right, and always receives
+    /// both operands in order. `>`, `<=` and `>=` are derived from `__lt` and `!=` from `__eq`.
+    fn apply_operator_metamethod(
+        &self,
+        op: BinaryOperator,
+        left_val: &Value,
+        right_val: &Value,
+    ) -> Result<Option<Value>, EasyScriptError> {
+        // Only a map with a prototype can have metamethods.
+        let has_proto =
+            |value: &Value| matches!(value.0.deref(), Object::Map(map) if map.proto.is_some());
+        if !has_proto(left_val) && !has_proto(right_val) {
+            return Ok(None);
+        }
+        let is_map = |value: &Value| matches!(value.0.deref(), Object::Map(_));
+        // (metamethod, swap operands, negate result)
+        let (name, swap, negate) = match op {
+            BinaryOperator::Add => ("__add", false, false),
+            BinaryOperator::Sub => ("__sub", false, false),
+            BinaryOperator::Mul => ("__mul", false, false),
+            BinaryOperator::Div => ("__div", false, false),
+            BinaryOperator::Mod => ("__mod", false, false),
+            BinaryOperator::Eq => ("__eq", false, false),
+            BinaryOperator::Neq => ("__eq", false, true),
+            BinaryOperator::Lt => ("__lt", false, false),
+            BinaryOperator::Gt => ("__lt", true, false),
+            BinaryOperator::Lte => ("__lt", true, true),
+            BinaryOperator::Gte => ("__lt", false, true),
+            _ => return Ok(None),
+        };
+        // `__eq` only compares two maps, and a map always equals itself.
+        if name == "__eq" && (!is_map(left_val) || !is_map(right_val) || left_val.0 == right_val.0)
+        {
+            return Ok(None);
+        }
+
+        let (a, b) = if swap {
+            (right_val, left_val)
+        } else {
+            (left_val, right_val)
+        };
+        let Some(function) = a.metamethod(name).or_else(|| b.metamethod(name)) else {
+            return Ok(None);
+        };
+        let result = self.call_value(&function, vec![a.clone(), b.clone()])?;
+        if name == "__eq" || name == "__lt" {
+            Ok(Some(Value::boolean(
+                &self.heap,
+                result.is_truthy() != negate,
+            )))
+        } else {
+            Ok(Some(result))
+        }
+    }
+
+    /// Drives a `for-in` loop over an iterator, collecting the value of each executed body.
+    fn run_for_in(
+        &self,
+        identifier: &str,
+        iterator: &Value,
+        condition: Option<&Expression>,
+        body: &Block,
+    ) -> Result<Value, EasyScriptError> {
+        let mut collected_values = Vec::new(); // Collect results here
+
+        while let Some(element) = self.iterator_next(iterator)? {
+            let loop_env = Environment::new_enclosed(&self.env());
+            {
+                let mut borrowed_env = loop_env.borrow_mut();
+                borrowed_env.assign(identifier, element);
+            }
+
+            // Evaluate the condition (if present) in the loop's environment
+            let should_execute_body = if let Some(cond_expr) = condition {
+                // Temporarily switch interpreter's environment for condition evaluation
+                let original_env_rc = self.env();
+                self.replace_env(Rc::clone(&loop_env));
+                let cond_val = self.evaluate(cond_expr);
+                self.replace_env(original_env_rc); // Restore original environment
+                cond_val?.is_truthy()
+            } else {
+                true // No condition, so always execute
+            };
+
+            if should_execute_body {
+                let iteration_result = self.execute_block(body, &loop_env)?;
+                collected_values.push(iteration_result);
+            }
+        }
+        Ok(Value::list(&self.heap, collected_values)) // Return the collected list
+    }
+
+    /// Evaluates the body of the first arm whose pattern matches `subject` and whose guard
+    /// holds. Each arm runs in its own scope, holding the names its pattern binds.
+    fn run_match(&self, subject: &Value, arms: &[MatchArm]) -> Result<Value, EasyScriptError> {
+        for arm in arms {
+            let arm_env = Environment::new_enclosed(&self.env());
+            let outer_env = self.replace_env(arm_env);
+            let result = self.run_match_arm(subject, arm);
+            self.replace_env(outer_env);
+            if let Some(value) = result? {
+                return Ok(value);
+            }
+        }
+        Err(runtime_error(&format!(
+            "No match arm matches {}.",
+            subject.repr_string()
+        )))
+    }
+
+    /// Runs one arm in the current environment; `None` if its pattern or guard rejects `subject`.
+    fn run_match_arm(
+        &self,
+        subject: &Value,
+        arm: &MatchArm,
+    ) -> Result<Option<Value>, EasyScriptError> {
+        if !self.match_pattern(&arm.pattern, subject)? {
+            return Ok(None);
+        }
+        if let Some(guard) = &arm.guard {
+            if !self.evaluate(guard)?.is_truthy() {
+                return Ok(None);
+            }
+        }
+        self.evaluate(&arm.body).map(Some)
+    }
+
+    /// Whether `value` matches `pattern`, binding the names in the pattern in the current
+    /// environment as it goes.
+    fn match_pattern(&self, pattern: &Pattern, value: &Value) -> Result<bool, EasyScriptError> {
+        match pattern {
+            Pattern::Wildcard => Ok(true),
+            Pattern::Binding(name) => {
+                self.env().borrow_mut().assign(name, value.clone());
+                Ok(true)
+            }
+            Pattern::Literal(literal) => {
+                let literal = self.evaluate_literal(literal)?;
+                Ok(literal == *value)
+            }
+            Pattern::Variant {
+                enum_name,
+                variant,
+                fields,
+            } => {
+                let Object::Enum(enum_value) = value.0.deref() else {
+                    return Ok(false);
+                };
+                if !enum_value.is_variant(enum_name, variant) {
+                    return Ok(false);
+                }
+                let Some(patterns) = fields else {
+                    return Ok(true); // `Shape.Circle` without parentheses ignores the fields
+                };
+                if patterns.len() != enum_value.fields.len() {
+                    return Err(runtime_error(&format!(
+                        "Pattern {}.{} has {} fields, but the variant has {}.",
+                        enum_name,
+                        variant,
+                        patterns.len(),
+                        enum_value.fields.len()
+                    )));
+                }
+                for (pattern, field) in patterns.iter().zip(enum_value.fields.clone()) {
+                    if !self.match_pattern(pattern, &field)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+        }
+    }
+
+    /// Evaluates the arguments of a call, expanding `...list` spreads.
+    fn evaluate_arguments(&self, args: &[Argument]) -> Result<CallArgs, EasyScriptError> {
+        let mut call_args = CallArgs::default();
+        for arg in args {
+            match arg {
+                Argument::Positional(expr) => call_args.positional.push(self.evaluate(expr)?),
+                Argument::Spread(expr) => {
+                    let spread = self.evaluate(expr)?;
+                    match spread.0.deref() {
+                        Object::List(items) => call_args.positional.extend(items.iter().cloned()),
+                        _ => {
+                            return Err(EasyScriptError::RuntimeError {
+                                message: format!(
+                                    "Spread argument must be a list, but got {}.",
+                                    spread.type_of()
+                                ),
+                                location: None,
+                            })
+                        }
+                    }
+                }
+                Argument::Keyword { name, value } => {
+                    let value = self.evaluate(value)?;
+                    call_args.keyword.push((name.clone(), value));
+                }
+            }
+        }
+        Ok(call_args)
+    }
+
+    /// Calls any callable value (user function, native function or bound method)
+    /// with already-evaluated positional arguments.
+    pub(crate) fn call_value(
+        &self,
+        callee: &Value,
+        arg_vals: Vec<Value>,
+    ) -> Result<Value, EasyScriptError> {
+        self.call_value_with(callee, CallArgs::positional(arg_vals))
+    }
+
+    /// Calls a native function. Its arguments stay rooted while it runs, as it may call back
+    /// into script.
+    fn call_native(
+        &self,
+        function: &NativeFunction,
+        arg_vals: Vec<Value>,
+    ) -> Result<Value, EasyScriptError> {
+        self.with_temp_roots(|| {
+            for arg in &arg_vals {
+                self.root(arg.clone());
+            }
+            function(&mut NativeContext::new(self), arg_vals)
+        })
+        .map_err(|e| EasyScriptError::RuntimeError {
+            message: e,
+            location: None,
+        })
+    }
+
+    /// Calls any callable value with already-evaluated positional and keyword arguments.
+    fn call_value_with(&self, callee: &Value, args: CallArgs) -> Result<Value, EasyScriptError> {
+        match callee.0.deref() {
+            crate::value::Object::Function(func_obj) => match func_obj {
+                crate::value::FunctionObjectInner::User {
+                    name,
+                    params,
+                    body,
+                    defined_env,
+                    is_generator,
+                    ..
+                } => {
+                    let arg_vals =
+                        self.bind_user_arguments(name.as_deref(), params, defined_env, args)?;
+
+                    // Calling a generator function only captures the call; the body runs
+                    // on its own coroutine as the generator is iterated.
+                    if *is_generator {
+                        let coroutine = Coroutine::new(callee.clone(), arg_vals);
+                        return Ok(Value::iterator(
+                            &self.heap,
+                            IteratorInner::Generator(Rc::new(coroutine)),
+                        ));
+                    }
+                    self.execute_function_body(params, body, defined_env, arg_vals)
+                }
+                crate::value::FunctionObjectInner::Native {
+                    function,
+                    signature,
+                } => {
+                    let arg_vals = match signature {
+                        Some(signature) => self.bind_native_arguments(signature, args)?,
+                        None => positional_only("Native function", args)?,
+                    };
+                    // 调用原生函数
+                    self.call_native(function, arg_vals)
+                }
+            },
+            crate::value::Object::BoundMethod(bound_method_inner) => {
+                let receiver = bound_method_inner.receiver.clone();
+                let method_name = bound_method_inner.method_name.clone();
+                if let Some(function) = bound_method_inner.function.clone() {
+                    // A user method: the receiver becomes its first argument (`self`).
+                    let mut args = args;
+                    args.positional.insert(0, receiver);
+                    return self.call_value_with(&function, args);
+                }
+                // Look up the actual native function from the interpreter's built-in methods
+                let native_method = self.builtin_method(receiver.type_of(), &method_name);
+                match native_method {
+                    Some((native_method_fn, signature)) => {
+                        // The receiver is bound to `self`, ahead of the other arguments
+                        let mut args = args;
+                        args.positional.insert(0, receiver);
+                        let full_args = self.bind_native_arguments(signature, args)?;
+                        self.call_native(native_method_fn, full_args)
+                    }
+                    // This should ideally not happen if Accessor correctly returns BoundMethod
+                    None => Err(EasyScriptError::RuntimeError {
+                        message: format!(
+                            "Internal error: Bound method '{}' not found for type '{}'.",
+                            method_name,
+                            receiver.type_of()
+                        ),
+                        location: None,
+                    }),
+                }
+            }
+            Object::Map(_) if callee.metamethod("__call").is_some() => {
+                // A callable object: `__call` receives the map as `self`.
+                let function = callee.metamethod("__call").unwrap();
+                let mut args = args;
+                args.positional.insert(0, callee.clone());
+                self.call_value_with(&function, args)
+            }
+            _ => Err(EasyScriptError::RuntimeError {
+                message: format!("Cannot call non-function or non-method value: {}", callee),
+                location: None,
+            }),
+        }
+    }
+
+    /// Runs a function's body, even for a generator function. This is the entry point of a
+    /// task's coroutine.
+    pub(crate) fn call_function_body(
+        &self,
+        function: &Value,
+        arg_vals: Vec<Value>,
+    ) -> Result<Value, EasyScriptError> {
+        match function.0.deref() {
+            Object::Function(FunctionObjectInner::User {
+                name,
+                params,
+                body,
+                defined_env,
+                ..
+            }) => {
+                let arg_vals = self.bind_user_arguments(
+                    name.as_deref(),
+                    params,
+                    defined_env,
+                    CallArgs::positional(arg_vals),
+                )?;
+                self.execute_function_body(params, body, defined_env, arg_vals)
+            }
+            _ => self.call_value(function, arg_vals),
+        }
+    }
+
+    /// Runs a user function's body with arguments already bound by `bind_user_arguments`.
+    /// This is the entry point of a generator's coroutine.
+    pub(crate) fn execute_bound_function(
+        &self,
+        function: &Value,
+        arg_vals: Vec<Value>,
+    ) -> Result<Value, EasyScriptError> {
+        match function.0.deref() {
+            Object::Function(FunctionObjectInner::User {
+                params,
+                body,
+                defined_env,
+                ..
+            }) => self.execute_function_body(params, body, defined_env, arg_vals),
+            _ => self.call_value(function, arg_vals),
+        }
+    }
+
+    /// Binds call arguments to a user function's parameters, returning one value per
+    /// parameter: omitted parameters get their default and a rest parameter gets a list.
+    fn bind_user_arguments(
+        &self,
+        name: Option<&str>,
+        params: &[Param],
+        defined_env: &EnvironmentRef,
+        args: CallArgs,
+    ) -> Result<Vec<Value>, EasyScriptError> {
+        let function_name = match name {
+            Some(name) => format!("{}()", name),
+            None => "anonymous function".to_string(),
+        };
+        let specs: Vec<_> = params.iter().map(Param::spec).collect();
+        let bound = bind_arguments(&function_name, &specs, args).map_err(|e| {
+            EasyScriptError::RuntimeError {
+                message: e,
+                location: None,
+            }
+        })?;
+
+        // Defaults are evaluated in a scope holding the parameters bound so far. The given
+        // arguments are kept in `__args` so they stay rooted while defaults run.
+        let scope = Environment::new_enclosed(defined_env);
+        let given: Vec<Value> = bound
+            .iter()
+            .filter_map(|arg| match arg {
+                BoundArg::Value(value) => Some(value.clone()),
+                _ => None,
+            })
+            .collect();
+        let given = Value::list(&self.heap, given);
+        scope.borrow_mut().assign("__args", given);
+
+        let mut arg_vals = Vec::with_capacity(params.len());
+        for (param, arg) in params.iter().zip(bound) {
+            let value = match arg {
+                BoundArg::Value(value) => value,
+                BoundArg::Rest(values) => Value::list(&self.heap, values),
+                BoundArg::Missing => {
+                    let default = param
+                        .default
+                        .as_ref()
+                        .expect("only parameters with a default can be omitted");
+                    let previous_env = self.replace_env(Rc::clone(&scope));
+                    let result = self.evaluate(default);
+                    self.replace_env(previous_env);
+                    result?
+                }
+            };
+            scope.borrow_mut().assign(&param.name, value.clone());
+            arg_vals.push(value);
+        }
+        Ok(arg_vals)
+    }
+
+    /// Binds call arguments to a native function's declared signature.
+    fn bind_native_arguments(
+        &self,
+        signature: &Signature,
+        args: CallArgs,
+    ) -> Result<Vec<Value>, EasyScriptError> {
+        let function_name = format!("{}()", signature.name);
+        let bound = bind_arguments(&function_name, &signature.params, args).map_err(|e| {
+            EasyScriptError::RuntimeError {
+                message: e,
+                location: None,
+            }
+        })?;
+
+        // Trailing omitted parameters are left out, so natives can tell them apart.
+        let provided = bound
+            .iter()
+            .rposition(|arg| !matches!(arg, BoundArg::Missing))
+            .map_or(0, |index| index + 1);
+        let mut arg_vals = Vec::with_capacity(provided);
+        for arg in bound.into_iter().take(provided) {
+            arg_vals.push(match arg {
+                BoundArg::Value(value) => value,
+                BoundArg::Rest(values) => Value::list(&self.heap, values),
+                BoundArg::Missing => Value::nil(&self.heap),
+            });
+        }
+        Ok(arg_vals)
+    }
+
+    fn execute_function_body(
+        &self,
+        params: &[Param],
+        body: &Block,
+        defined_env: &EnvironmentRef,
+        arg_vals: Vec<Value>,
+    ) -> Result<Value, EasyScriptError> {
+        // Create a new environment for the function call,
+        // based on the environment where the function was defined (closure)
+        let function_env = Environment::new_enclosed(defined_env); // 使用 defined_env
+        {
+            let mut borrowed_env = function_env.borrow_mut();
+            for (param, arg_val) in params.iter().zip(arg_vals) {
+                borrowed_env.assign(&param.name, arg_val);
+            }
+        }
+        // Execute the function body in the new environment
+        self.with_stack_room(|| self.execute_block(body, &function_env))
+    }
+
+    /// Keeps `value` alive across collections until the innermost `with_temp_roots` returns,
+    /// for values Rust code holds while it calls back into script.
+    pub(crate) fn root(&self, value: Value) {
+        self.temp_roots.borrow_mut().push(value);
+    }
+
+    /// Runs `f`, then drops the temporary roots it added.
+    pub(crate) fn with_temp_roots<R>(&self, f: impl FnOnce() -> R) -> R {
+        let depth = self.temp_roots.borrow().len();
+        let result = f();
+        self.temp_roots.borrow_mut().truncate(depth);
+        result
+    }
+
+    /// Runs a garbage collection cycle, returning the number of objects freed.
+    pub(crate) fn collect_garbage(&self) -> usize {
+        // Roots: the current scope chain and temporary roots, every coroutine on the stack and
+        // its resumer's scopes and temporary roots, and whatever the scheduler holds on to
+        // (spawned tasks, channels being waited on).
+        let Interpreter {
+            heap,
+            environment,
+            coroutines,
+            started_coroutines,
+            scheduler,
+            temp_roots,
+            ..
+        } = self;
+        let started_coroutines = started_coroutines.borrow();
+        for coroutine in started_coroutines.iter() {
+            coroutine.unmark();
+        }
+        let mut abandoned = Vec::new();
+        let freed = heap.collect_with(|heap| {
+            trace_environment(&environment.borrow(), heap);
+            for value in temp_roots.borrow().iter() {
+                value.trace(heap);
+            }
+            for entry in coroutines.borrow().iter() {
+                entry.coroutine.trace(heap);
+                trace_environment(&entry.caller_env, heap);
+                for value in &entry.caller_roots {
+                    value.trace(heap);
+                }
+            }
+            scheduler.borrow().trace(heap);
+            // A suspended generator the roots do not reach can never be resumed. What its
+            // stack refers to survives this collection, for its `defer`s to run below.
+            abandoned = started_coroutines
+                .iter()
+                .filter(|coroutine| coroutine.is_abandoned_generator())
+                .cloned()
+                .collect();
+            for coroutine in &abandoned {
+                coroutine.trace(heap);
+            }
+        });
+        drop(started_coroutines);
+        for coroutine in abandoned {
+            coroutine.cancel(self);
+        }
+        freed
+    }
+
     /// Evaluates a literal value from the AST into a runtime Value.
-    fn evaluate_literal(&mut self, literal: &LiteralValue) -> Result<Value, EasyScriptError> {
+    fn evaluate_literal(&self, literal: &LiteralValue) -> Result<Value, EasyScriptError> {
         Ok(match literal {
-            LiteralValue::Number(n) => Value::number(&mut self.heap, *n),
-            LiteralValue::String(s) => Value::string(&mut self.heap, s.clone()),
-            LiteralValue::Boolean(b) => Value::boolean(&mut self.heap, *b),
-            LiteralValue::Nil => Value::nil(&mut self.heap),
+            LiteralValue::Number(n) => Value::number(&self.heap, *n),
+            LiteralValue::Integer(i) => Value::integer(&self.heap, *i),
+            LiteralValue::BigInteger(b) => Value::big_integer(&self.heap, b.clone()),
+            LiteralValue::String(s) => Value::string(&self.heap, s.clone()),
+            LiteralValue::Bytes(b) => Value::bytes(&self.heap, b.clone()),
+            LiteralValue::Boolean(b) => Value::boolean(&self.heap, *b),
+            LiteralValue::Nil => Value::nil(&self.heap),
         })
     }
 }
+
+fn runtime_error(message: &str) -> EasyScriptError {
+    EasyScriptError::RuntimeError {
+        message: message.to_string(),
+        location: None,
+    }
+}
+
+/// Whether a comparison operator holds for an ordering; `None` for other operators. An
+/// unordered pair (NaN) satisfies no comparison.
+fn ordering_satisfies(op: BinaryOperator, ordering: Option<Ordering>) -> Option<bool> {
+    let accepted: &[Ordering] = match op {
+        BinaryOperator::Lt => &[Ordering::Less],
+        BinaryOperator::Lte => &[Ordering::Less, Ordering::Equal],
+        BinaryOperator::Gt => &[Ordering::Greater],
+        BinaryOperator::Gte => &[Ordering::Greater, Ordering::Equal],
+        _ => return None,
+    };
+    Some(ordering.is_some_and(|ordering| accepted.contains(&ordering)))
+}
+
+/// The largest amount a `<<` or `>>` accepts. It bounds the size of a shifted big integer,
+/// so that a typo like `1 << 10 ** 12` fails instead of exhausting memory.
+const MAX_SHIFT_AMOUNT: u32 = 65536;
+
+/// The amount of a `<<` or `>>` on a big integer, which must be in 0..=`MAX_SHIFT_AMOUNT`.
+fn shift_amount(amount: &BigInt) -> Result<u32, EasyScriptError> {
+    if amount.is_negative() {
+        return Err(runtime_error("Shift amount cannot be negative."));
+    }
+    match amount.to_i64() {
+        Some(n) if n <= MAX_SHIFT_AMOUNT as i64 => Ok(n as u32),
+        _ => Err(runtime_error(&format!(
+            "Shift amount must be at most {}.",
+            MAX_SHIFT_AMOUNT
+        ))),
+    }
+}
+
+/// Rejects assignments into a frozen list or map.
+fn ensure_mutable(target: &Value) -> Result<(), EasyScriptError> {
+    target
+        .ensure_mutable()
+        .map_err(|message| EasyScriptError::RuntimeError {
+            message,
+            location: None,
+        })
+}
+
+/// Rejects keyword arguments for callables without a declared signature.
+fn positional_only(callee: &str, args: CallArgs) -> Result<Vec<Value>, EasyScriptError> {
+    if let Some((name, _)) = args.keyword.first() {
+        return Err(EasyScriptError::RuntimeError {
+            message: format!(
+                "{} does not accept keyword arguments, but got '{}'.",
+                callee, name
+            ),
+            location: None,
+        });
+    }
+    Ok(args.positional)
+}
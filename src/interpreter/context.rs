@@ -0,0 +1,43 @@
+//! The interpreter context handed to native functions.
+
+use super::Interpreter;
+use crate::value::{Heap, Value};
+use std::ops::Deref;
+
+/// What a native function works with: the heap (through `Deref`, so a context can be passed
+/// wherever a `&Heap` is expected) and a way to call back into any callable value.
+pub struct NativeContext<'a> {
+    interpreter: &'a Interpreter,
+}
+
+impl<'a> NativeContext<'a> {
+    pub(crate) fn new(interpreter: &'a Interpreter) -> Self {
+        NativeContext { interpreter }
+    }
+
+    /// Calls a user function, native or bound method with positional arguments.
+    pub fn call(&mut self, callee: &Value, args: Vec<Value>) -> Result<Value, String> {
+        self.interpreter
+            .call_value(callee, args)
+            .map_err(|e| e.message().to_string())
+    }
+
+    /// Keeps `value` alive until the native returns, for values it holds only in Rust while
+    /// calling back into script. The native's arguments are rooted already.
+    pub fn root(&mut self, value: Value) {
+        self.interpreter.root(value);
+    }
+
+    /// For natives that need interpreter state beyond the heap, such as the scheduler.
+    pub(crate) fn interpreter(&self) -> &'a Interpreter {
+        self.interpreter
+    }
+}
+
+impl Deref for NativeContext<'_> {
+    type Target = Heap;
+
+    fn deref(&self) -> &Heap {
+        &self.interpreter.heap
+    }
+}
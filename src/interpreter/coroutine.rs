@@ -0,0 +1,464 @@
+//! Stackful coroutines, the execution model behind generators and scheduler tasks.
+//!
+//! `Interpreter::evaluate` is a recursive tree walker, so the state of a suspended function
+//! lives on the Rust call stack. Each coroutine therefore runs its body on a stack of its own
+//! (a `corosensei` coroutine), and control switches between stacks on the interpreter's one
+//! thread: only the innermost coroutine being resumed runs at any time.
+//!
+//! A suspended body goes on using the interpreter that first resumed it, through a shared
+//! reference made from its address, which is why `Interpreter::new` returns the interpreter
+//! pinned. Every resume passes the address again, and `suspend` checks that it has not changed.
+//!
+//! A coroutine that will not be resumed again is cancelled rather than dropped: the `yield`
+//! (or blocking call) it is suspended at returns an error, so the body unwinds through
+//! ordinary error returns and its `defer`s run. The interpreter keeps every started coroutine
+//! until it finishes, and cancels a generator once a collection finds it unreachable, and
+//! whatever is still suspended when the program ends.
+
+use super::Interpreter;
+use crate::environment::EnvironmentRef;
+use crate::error::EasyScriptError;
+use crate::value::{trace_environment, GcTrace, Heap, Value};
+use corosensei::stack::{DefaultStack, Stack};
+use corosensei::{CoroutineResult, Yielder};
+use std::cell::{Cell, RefCell};
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+/// Coroutine stacks start this size, and a body that recurses deeper goes on in further
+/// segments of the same size (see `Interpreter::with_stack_room`).
+const STACK_SEGMENT_SIZE: usize = 512 * 1024;
+
+/// A script call made with less than this much of the current segment left runs on a new
+/// segment. It must cover the Rust frames of one call level, with room to spare.
+const STACK_RED_ZONE: usize = 128 * 1024;
+
+/// How many free segments the interpreter keeps for reuse.
+const STACK_POOL_SIZE: usize = 16;
+
+/// What a coroutine hands back to whoever resumed it.
+pub(crate) enum Resumed {
+    /// The body suspended itself with `yield`, or a task blocked.
+    Yielded(Value),
+    /// The body has run to completion; its return value is discarded.
+    Finished,
+}
+
+/// What a resumer passes into a coroutine.
+enum Input {
+    Resume {
+        interpreter: *const Interpreter,
+        sent: Value,
+    },
+    /// Makes the suspension point return an error, unwinding the body.
+    Cancel { interpreter: *const Interpreter },
+}
+
+/// What a suspending coroutine passes out to its resumer.
+enum Output {
+    Yielded(Value),
+    /// The innermost task is blocking: every coroutine up to it suspends along with it.
+    Park,
+}
+
+type Body = corosensei::Coroutine<Input, Output, Result<Value, EasyScriptError>, DefaultStack>;
+
+enum State {
+    /// Not resumed yet; the body has not started.
+    Created {
+        args: Vec<Value>,
+    },
+    /// Suspended in environment `env`. `nested` is the generator this coroutine was running
+    /// when a task it belongs to blocked; that generator is suspended inside it.
+    Suspended {
+        env: EnvironmentRef,
+        nested: Option<Rc<Coroutine>>,
+    },
+    Running,
+    Finished,
+}
+
+/// A function call that can suspend itself and be resumed later.
+pub struct Coroutine {
+    // Kept for the coroutine's whole life: the running body refers into it.
+    function: Value,
+    state: RefCell<State>,
+    // Set once started. Taken out while the body runs.
+    body: RefCell<Option<Body>>,
+    // Set by the body when it starts; lives on the body's own stack.
+    yielder: Cell<*const Yielder<Input, Output>>,
+    // The body's temporary roots (see `Interpreter::root`), set aside while it is suspended.
+    temp_roots: RefCell<Vec<Value>>,
+    // Lowest address of the stack segment the body was running on when it last suspended.
+    stack_limit: Cell<usize>,
+    // Set for scheduler tasks; `None` for generators.
+    task_id: Option<usize>,
+    // Set when a collection traces the coroutine; see `Interpreter::collect_garbage`.
+    reached: Cell<bool>,
+}
+
+/// An entry of the interpreter's stack of coroutines currently being resumed.
+pub(crate) struct ActiveCoroutine {
+    pub(crate) coroutine: Rc<Coroutine>,
+    /// The resumer's environment, restored when the coroutine hands control back.
+    pub(crate) caller_env: EnvironmentRef,
+    /// The resumer's temporary roots, set aside while the coroutine runs.
+    pub(crate) caller_roots: Vec<Value>,
+}
+
+impl fmt::Debug for Coroutine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match &*self.state.borrow() {
+            State::Created { .. } => "created",
+            State::Suspended { .. } => "suspended",
+            State::Running => "running",
+            State::Finished => "finished",
+        };
+        write!(f, "Coroutine {{ state: {} }}", state)
+    }
+}
+
+impl GcTrace for Coroutine {
+    fn trace(&self, heap: &Heap) {
+        self.reached.set(true);
+        // A running coroutine's values are reachable from the interpreter's environment.
+        self.function.trace(heap);
+        match &*self.state.borrow() {
+            State::Created { args } => {
+                for arg in args {
+                    arg.trace(heap);
+                }
+            }
+            State::Suspended { env, nested } => {
+                trace_environment(env, heap);
+                for value in self.temp_roots.borrow().iter() {
+                    value.trace(heap);
+                }
+                if let Some(nested) = nested {
+                    nested.trace(heap);
+                }
+            }
+            State::Running | State::Finished => {}
+        }
+    }
+}
+
+impl Coroutine {
+    /// Creates a generator coroutine that will run `function`'s body with `args` (already
+    /// bound to its parameters) when first resumed.
+    pub(crate) fn new(function: Value, args: Vec<Value>) -> Self {
+        Coroutine {
+            function,
+            state: RefCell::new(State::Created { args }),
+            body: RefCell::new(None),
+            yielder: Cell::new(std::ptr::null()),
+            temp_roots: RefCell::new(Vec::new()),
+            stack_limit: Cell::new(0),
+            task_id: None,
+            reached: Cell::new(false),
+        }
+    }
+
+    /// Creates the coroutine of scheduler task `task_id`.
+    pub(crate) fn new_task(task_id: usize, function: Value, args: Vec<Value>) -> Self {
+        let mut coroutine = Coroutine::new(function, args);
+        coroutine.task_id = Some(task_id);
+        coroutine
+    }
+
+    pub(crate) fn task_id(&self) -> Option<usize> {
+        self.task_id
+    }
+
+    /// Whether this is a generator suspended at a `yield` that the last collection did not
+    /// reach: nothing can resume it any more.
+    pub(crate) fn is_abandoned_generator(&self) -> bool {
+        self.task_id.is_none()
+            && !self.reached.get()
+            && matches!(*self.state.borrow(), State::Suspended { .. })
+    }
+
+    /// Clears the mark `trace` sets, before a collection.
+    pub(crate) fn unmark(&self) {
+        self.reached.set(false);
+    }
+
+    /// Runs the coroutine until it yields or finishes. `sent` becomes the value of the
+    /// `yield` expression it is suspended at.
+    pub(crate) fn resume(
+        self: &Rc<Self>,
+        interpreter: &Interpreter,
+        sent: Value,
+    ) -> Result<Resumed, EasyScriptError> {
+        self.switch(interpreter, Some(sent))
+    }
+
+    /// Unwinds a suspended coroutine by making its suspension point fail, running its
+    /// `defer`s. Errors raised while unwinding are discarded: nobody is left to report them to.
+    pub(crate) fn cancel(self: &Rc<Self>, interpreter: &Interpreter) {
+        // The body may suspend again while unwinding (a `yield` in a `defer`); that fails too.
+        while matches!(
+            *self.state.borrow(),
+            State::Created { .. } | State::Suspended { .. }
+        ) {
+            let _ = self.switch(interpreter, None);
+        }
+    }
+
+    /// Suspends the running coroutine at a `yield`, handing `value` to its resumer; returns
+    /// the value passed to the next `resume`, or an error if the coroutine is cancelled.
+    pub(crate) fn yield_value(
+        &self,
+        interpreter: &Interpreter,
+        value: Value,
+    ) -> Result<Value, EasyScriptError> {
+        self.suspend(interpreter, Output::Yielded(value), None)
+    }
+
+    /// Suspends the running coroutine because the innermost task is blocking; the generators
+    /// between it and that task suspend with it. Returns once the task is resumed, or with an
+    /// error if it is cancelled.
+    pub(crate) fn park(&self, interpreter: &Interpreter) -> Result<(), EasyScriptError> {
+        self.suspend(interpreter, Output::Park, None).map(|_| ())
+    }
+
+    /// Switches to the body with `sent`, or cancels it when `sent` is `None`, and handles
+    /// whatever it hands back.
+    fn switch(
+        self: &Rc<Self>,
+        interpreter: &Interpreter,
+        mut sent: Option<Value>,
+    ) -> Result<Resumed, EasyScriptError> {
+        loop {
+            let mut body = match self.state.replace(State::Running) {
+                State::Created { args } => {
+                    if sent.is_none() {
+                        self.state.replace(State::Finished);
+                        return Ok(Resumed::Finished);
+                    }
+                    match self.start(interpreter, args) {
+                        Ok(body) => {
+                            interpreter
+                                .started_coroutines
+                                .borrow_mut()
+                                .push(Rc::clone(self));
+                            body
+                        }
+                        Err(e) => {
+                            self.state.replace(State::Finished);
+                            return Err(e);
+                        }
+                    }
+                }
+                State::Suspended { .. } => {
+                    self.body.take().expect("suspended coroutine has a body")
+                }
+                State::Running => {
+                    self.state.replace(State::Running);
+                    return Err(EasyScriptError::RuntimeError {
+                        message: "Generator is already running.".to_string(),
+                        location: None,
+                    });
+                }
+                State::Finished => {
+                    self.state.replace(State::Finished);
+                    return Ok(Resumed::Finished);
+                }
+            };
+
+            let interpreter_ptr = interpreter as *const Interpreter;
+            let input = match sent.take() {
+                Some(sent) => Input::Resume {
+                    interpreter: interpreter_ptr,
+                    sent,
+                },
+                None => Input::Cancel {
+                    interpreter: interpreter_ptr,
+                },
+            };
+            interpreter.coroutines.borrow_mut().push(ActiveCoroutine {
+                coroutine: Rc::clone(self),
+                caller_env: interpreter.env(),
+                caller_roots: interpreter.temp_roots.replace(self.temp_roots.take()),
+            });
+            let caller_limit = interpreter.stack_limit.replace(self.stack_limit.get());
+            let result = body.resume(input);
+            self.stack_limit
+                .set(interpreter.stack_limit.replace(caller_limit));
+            let entry = interpreter
+                .coroutines
+                .borrow_mut()
+                .pop()
+                .expect("resumed coroutine missing from the stack");
+            interpreter.replace_env(entry.caller_env);
+            self.temp_roots
+                .replace(interpreter.temp_roots.replace(entry.caller_roots));
+
+            let output = match result {
+                CoroutineResult::Yield(output) => {
+                    // `suspend` has already recorded the suspended state.
+                    self.body.replace(Some(body));
+                    output
+                }
+                CoroutineResult::Return(result) => {
+                    self.state.replace(State::Finished);
+                    interpreter.release_stack(body.into_stack());
+                    interpreter
+                        .started_coroutines
+                        .borrow_mut()
+                        .retain(|started| !Rc::ptr_eq(started, self));
+                    return result.map(|_| Resumed::Finished);
+                }
+            };
+            match output {
+                Output::Yielded(value) => return Ok(Resumed::Yielded(value)),
+                Output::Park if self.task_id.is_some() => {
+                    return Ok(Resumed::Yielded(Value::nil(&interpreter.heap)))
+                }
+                Output::Park => {
+                    // A task this generator runs in is blocking. We are on the stack of the
+                    // coroutine that resumed the generator, so suspend that one too, and carry
+                    // on with the generator once it is resumed.
+                    let outer = interpreter
+                        .coroutines
+                        .borrow()
+                        .last()
+                        .map(|entry| Rc::clone(&entry.coroutine))
+                        .expect("a generator only parks inside a task");
+                    match outer.suspend(interpreter, Output::Park, Some(Rc::clone(self))) {
+                        Ok(value) => sent = Some(value),
+                        Err(e) => {
+                            self.cancel(interpreter);
+                            return Err(e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Hands `output` to the resumer of this coroutine, which must be the innermost one
+    /// running, and waits to be resumed.
+    fn suspend(
+        &self,
+        interpreter: &Interpreter,
+        output: Output,
+        nested: Option<Rc<Coroutine>>,
+    ) -> Result<Value, EasyScriptError> {
+        debug_assert!(interpreter
+            .coroutines
+            .borrow()
+            .last()
+            .is_some_and(|entry| std::ptr::eq(&*entry.coroutine, self)));
+        let env = interpreter.env();
+        self.state.replace(State::Suspended {
+            env: Rc::clone(&env),
+            nested,
+        });
+        // SAFETY: the innermost running coroutine's body is on the stack below us, so the
+        // yielder it stored when it started is still alive, and it is the one to suspend.
+        let yielder = unsafe { &*self.yielder.get() };
+        let input = yielder.suspend(output);
+        interpreter.replace_env(env);
+
+        let resumed_by = match input {
+            Input::Resume {
+                interpreter: resumed_by,
+                ..
+            }
+            | Input::Cancel {
+                interpreter: resumed_by,
+            } => resumed_by,
+        };
+        assert!(
+            std::ptr::eq(resumed_by, interpreter),
+            "coroutine resumed by a different interpreter"
+        );
+        match input {
+            Input::Resume { sent, .. } => Ok(sent),
+            Input::Cancel { .. } => Err(EasyScriptError::RuntimeError {
+                message: match self.task_id {
+                    Some(_) => "Task was cancelled.".to_string(),
+                    None => "Generator was cancelled.".to_string(),
+                },
+                location: None,
+            }),
+        }
+    }
+
+    /// Creates the body, which runs the function when first resumed.
+    fn start(
+        self: &Rc<Self>,
+        interpreter: &Interpreter,
+        args: Vec<Value>,
+    ) -> Result<Body, EasyScriptError> {
+        let coroutine: Weak<Coroutine> = Rc::downgrade(self);
+        let function = self.function.clone();
+        // A task gets its arguments as given; a generator's were bound when it was called.
+        let is_task = self.task_id.is_some();
+        let stack = interpreter.acquire_stack()?;
+        self.stack_limit.set(stack.limit().get());
+        Ok(Body::with_stack(stack, move |yielder, input| {
+            if let Some(coroutine) = coroutine.upgrade() {
+                coroutine.yielder.set(yielder);
+            }
+            let Input::Resume { interpreter, .. } = input else {
+                unreachable!("a coroutine that has not started is never cancelled")
+            };
+            // SAFETY: `switch` passed a pointer to the interpreter resuming us. The interpreter
+            // is pinned and cancels every started coroutine before it is dropped, so the pointer
+            // stays valid for as long as this body can run; `suspend` checks that each resume
+            // comes from the same one. Only shared references to the interpreter ever exist:
+            // its state lives in cells, so the resumer and this body can both hold one across
+            // a suspension without aliasing a `&mut`.
+            let interpreter = unsafe { &*interpreter };
+            if is_task {
+                interpreter.call_function_body(&function, args)
+            } else {
+                interpreter.execute_bound_function(&function, args)
+            }
+        }))
+    }
+}
+
+impl Interpreter {
+    /// Runs `f`, on a new stack segment when a coroutine's current one is nearly used up. The
+    /// main thread's stack is left to the operating system.
+    pub(crate) fn with_stack_room<R>(
+        &self,
+        f: impl FnOnce() -> Result<R, EasyScriptError>,
+    ) -> Result<R, EasyScriptError> {
+        let limit = self.stack_limit.get();
+        let here = &limit as *const usize as usize;
+        if limit == 0 || here - limit > STACK_RED_ZONE {
+            return f();
+        }
+        let mut stack = self.acquire_stack()?;
+        let outer_limit = self.stack_limit.replace(stack.limit().get());
+        // The body may suspend while on the segment; the segment stays borrowed until `f`
+        // returns, and `switch` keeps track of which segment each coroutine is on.
+        let result = corosensei::on_stack(&mut stack, f);
+        self.stack_limit.set(outer_limit);
+        self.release_stack(stack);
+        result
+    }
+
+    /// Takes a free stack segment, or maps a new one.
+    fn acquire_stack(&self) -> Result<DefaultStack, EasyScriptError> {
+        if let Some(stack) = self.stacks.borrow_mut().pop() {
+            return Ok(stack);
+        }
+        DefaultStack::new(STACK_SEGMENT_SIZE).map_err(|e| EasyScriptError::RuntimeError {
+            message: format!("Cannot allocate a stack for a generator or task: {}.", e),
+            location: None,
+        })
+    }
+
+    /// Hands back a stack segment that is no longer in use.
+    fn release_stack(&self, stack: DefaultStack) {
+        let mut stacks = self.stacks.borrow_mut();
+        if stacks.len() < STACK_POOL_SIZE {
+            stacks.push(stack);
+        }
+    }
+}
@@ -0,0 +1,264 @@
+//! Iteration support for the interpreter.
+//!
+//! Iterator objects only hold state (see `IteratorInner`); advancing them lives here
+//! because adaptors such as `map` and `filter` need to call back into script functions.
+
+use super::coroutine::Resumed;
+use super::Interpreter;
+use crate::error::EasyScriptError;
+use crate::value::{IteratorInner, Object, Value};
+use std::io::BufRead;
+use std::rc::Rc;
+
+impl Interpreter {
+    /// Advances an iterator, returning `None` once it is exhausted.
+    pub(crate) fn iterator_next(&self, iterator: &Value) -> Result<Option<Value>, EasyScriptError> {
+        let mut handle = iterator.0;
+        let state = match handle.deref_mut() {
+            Object::Iterator(state) => state,
+            _ => {
+                return Err(EasyScriptError::RuntimeError {
+                    message: format!("Expected an iterator, but got '{}'.", iterator.type_of()),
+                    location: None,
+                })
+            }
+        };
+
+        // Every arm clones what it needs out of `state` before calling back into the
+        // interpreter, since a callback may touch this very iterator.
+        match state {
+            IteratorInner::List { list, index } => {
+                let item = list
+                    .0
+                    .deref()
+                    .as_list()
+                    .and_then(|l| l.get(*index).cloned());
+                if item.is_some() {
+                    *index += 1;
+                }
+                Ok(item)
+            }
+            IteratorInner::Keys { keys, index } => {
+                let item = keys.get(*index).cloned();
+                if item.is_some() {
+                    *index += 1;
+                }
+                Ok(item)
+            }
+            IteratorInner::Range { next, end, step } => {
+                let current = *next;
+                if (*step > 0.0 && current < *end) || (*step < 0.0 && current > *end) {
+                    *next += *step;
+                    Ok(Some(Value::number(&self.heap, current)))
+                } else {
+                    Ok(None)
+                }
+            }
+            IteratorInner::IntegerRange { next, end, step } => {
+                let current = *next;
+                if (*step > 0 && current < *end) || (*step < 0 && current > *end) {
+                    // Stepping past i64 can only mean the range is done
+                    *next = current.checked_add(*step).unwrap_or(*end);
+                    Ok(Some(Value::integer(&self.heap, current)))
+                } else {
+                    Ok(None)
+                }
+            }
+            IteratorInner::Lines => {
+                let mut line = String::new();
+                let read = std::io::stdin()
+                    .lock()
+                    .read_line(&mut line)
+                    .map_err(|e| e.to_string())?;
+                if read == 0 {
+                    return Ok(None);
+                }
+                let line = line.trim_end_matches(&['\n', '\r'][..]).to_string();
+                Ok(Some(Value::string(&self.heap, line)))
+            }
+            IteratorInner::Iterable { object } => {
+                let object = object.clone();
+                let resolved = self.resolve_iterable(&object)?;
+                if let Object::Iterator(state) = handle.deref_mut() {
+                    *state = resolved;
+                }
+                self.iterator_next(iterator)
+            }
+            IteratorInner::Protocol { object } => {
+                let next_fn = self.lookup_method(object, "next");
+                let next_fn = match next_fn {
+                    Some(f) if f.is_callable() => f,
+                    _ => {
+                        return Err(EasyScriptError::RuntimeError {
+                            message: "Iterator object no longer has a 'next' function.".to_string(),
+                            location: None,
+                        })
+                    }
+                };
+                let item = self.call_value(&next_fn, Vec::new())?;
+                Ok(if item.type_of() == "nil" {
+                    None
+                } else {
+                    Some(item)
+                })
+            }
+            IteratorInner::Map { source, func } => {
+                let (source, func) = (source.clone(), func.clone());
+                match self.iterator_next(&source)? {
+                    Some(item) => Ok(Some(self.call_value(&func, vec![item])?)),
+                    None => Ok(None),
+                }
+            }
+            IteratorInner::Filter { source, func } => {
+                let (source, func) = (source.clone(), func.clone());
+                while let Some(item) = self.iterator_next(&source)? {
+                    if self.call_value(&func, vec![item.clone()])?.is_truthy() {
+                        return Ok(Some(item));
+                    }
+                }
+                Ok(None)
+            }
+            IteratorInner::Take { source, remaining } => {
+                if *remaining == 0 {
+                    return Ok(None);
+                }
+                *remaining -= 1;
+                let source = source.clone();
+                self.iterator_next(&source)
+            }
+            IteratorInner::Zip { left, right } => {
+                let (left, right) = (left.clone(), right.clone());
+                let Some(left_item) = self.iterator_next(&left)? else {
+                    return Ok(None);
+                };
+                let Some(right_item) = self.iterator_next(&right)? else {
+                    return Ok(None);
+                };
+                Ok(Some(Value::list(&self.heap, vec![left_item, right_item])))
+            }
+            IteratorInner::Enumerate { source, index } => {
+                let position = *index;
+                *index += 1;
+                let source = source.clone();
+                match self.iterator_next(&source)? {
+                    Some(item) => {
+                        let position = Value::integer(&self.heap, position as i64);
+                        Ok(Some(Value::list(&self.heap, vec![position, item])))
+                    }
+                    None => Ok(None),
+                }
+            }
+            IteratorInner::Generator(coroutine) => {
+                let coroutine = Rc::clone(coroutine);
+                let sent = Value::nil(&self.heap);
+                match coroutine.resume(self, sent)? {
+                    Resumed::Yielded(item) => Ok(Some(item)),
+                    Resumed::Finished => Ok(None),
+                }
+            }
+            IteratorInner::Chain {
+                first,
+                second,
+                on_second,
+            } => {
+                let (first, second, on_second) = (first.clone(), second.clone(), *on_second);
+                if !on_second {
+                    if let Some(item) = self.iterator_next(&first)? {
+                        return Ok(Some(item));
+                    }
+                    if let Object::Iterator(IteratorInner::Chain { on_second, .. }) =
+                        handle.deref_mut()
+                    {
+                        *on_second = true;
+                    }
+                }
+                self.iterator_next(&second)
+            }
+        }
+    }
+
+    /// Calls an object's `__iter` function and returns the state of the iterator it produced.
+    fn resolve_iterable(&self, object: &Value) -> Result<IteratorInner, EasyScriptError> {
+        let iter_fn = match self.lookup_method(object, "__iter") {
+            Some(f) if f.is_callable() => f,
+            _ => {
+                return Err(EasyScriptError::RuntimeError {
+                    message: "Iterable object no longer has an '__iter' function.".to_string(),
+                    location: None,
+                })
+            }
+        };
+        let produced = self.call_value(&iter_fn, Vec::new())?;
+
+        // A map returned by `__iter` is an iterator if it has `next`, even when it also
+        // has `__iter` (e.g. an object whose `__iter` returns itself).
+        if produced
+            .lookup_property_str("next")
+            .is_some_and(|f| f.is_callable())
+        {
+            return Ok(IteratorInner::Protocol { object: produced });
+        }
+        match produced.0.deref() {
+            Object::Iterator(state) => Ok(state.clone()),
+            Object::List(_) => Ok(IteratorInner::List {
+                list: produced.clone(),
+                index: 0,
+            }),
+            _ => Err(EasyScriptError::RuntimeError {
+                message: format!(
+                    "__iter() must return an iterator, a list or a map with a 'next' function, but got '{}'.",
+                    produced.type_of()
+                ),
+                location: None,
+            }),
+        }
+    }
+
+    /// Drains an iterator into a list of the remaining items.
+    pub(crate) fn iterator_collect(&self, iterator: &Value) -> Result<Value, EasyScriptError> {
+        // The items collected so far are only in `items` while the iterator runs script.
+        self.with_temp_roots(|| {
+            self.root(iterator.clone());
+            let mut items = Vec::new();
+            while let Some(item) = self.iterator_next(iterator)? {
+                self.root(item.clone());
+                items.push(item);
+            }
+            Ok(Value::list(&self.heap, items))
+        })
+    }
+}
+
+impl IteratorInner {
+    /// Whether a `range()` iterator would still produce `item`, checked without advancing it.
+    /// `None` for other iterators, which can only be searched by consuming them.
+    pub(crate) fn range_contains(&self, item: &Object) -> Option<bool> {
+        match self {
+            IteratorInner::IntegerRange { next, end, step } => {
+                // `3.0 in range(5)` holds, just as `3.0 == 3` does.
+                let exact = item.as_exact_decimal().and_then(|d| d.to_integer());
+                let Some(value) = exact.and_then(|i| i.to_i64()) else {
+                    return Some(false);
+                };
+                let in_bounds = if *step > 0 {
+                    *next <= value && value < *end
+                } else {
+                    *end < value && value <= *next
+                };
+                Some(in_bounds && (value as i128 - *next as i128) % *step as i128 == 0)
+            }
+            IteratorInner::Range { next, end, step } => {
+                let Some(value) = item.as_number() else {
+                    return Some(false);
+                };
+                let in_bounds = if *step > 0.0 {
+                    *next <= value && value < *end
+                } else {
+                    *end < value && value <= *next
+                };
+                Some(in_bounds && ((value - next) / step).fract() == 0.0)
+            }
+            _ => None,
+        }
+    }
+}
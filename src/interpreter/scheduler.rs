@@ -0,0 +1,282 @@
+//! Cooperative tasks: `spawn`, `yield_now`, `sleep` and channels.
+//!
+//! Every spawned task runs on its own coroutine (see `coroutine`), all of them on the
+//! interpreter's thread. Tasks only switch when one of them blocks, and the scheduler loop
+//! that picks the next one runs whenever the main program blocks, and once more after the
+//! main program finishes. The main program takes part in scheduling as the task `MAIN`.
+//!
+//! Scheduling is deterministic: ready tasks run in FIFO order, and `sleep` uses a virtual
+//! clock, so sleepers wake in deadline order (ties in the order they went to sleep) however
+//! long the tasks themselves take to run. When every task is asleep the thread waits for
+//! real time, until the next sleeper has slept as long as it asked to.
+
+use super::coroutine::{Coroutine, Resumed};
+use super::Interpreter;
+use crate::error::EasyScriptError;
+use crate::value::{GcTrace, Heap, Object, Value};
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+type TaskId = usize;
+
+/// The main program, which is not a coroutine but can block like any task.
+const MAIN: TaskId = 0;
+
+/// Why a task stopped running.
+enum Wait {
+    Ready,
+    Sleep(f64),
+    Receive(Value),
+}
+
+struct Sleeper {
+    deadline: f64,
+    seq: u64,
+    task: TaskId,
+    // When the sleep is over in real time; `None` if that is too far away to represent.
+    due: Option<Instant>,
+}
+
+pub(crate) struct Scheduler {
+    tasks: HashMap<TaskId, Rc<Coroutine>>,
+    next_id: TaskId,
+    ready: VecDeque<TaskId>,
+    sleeping: Vec<Sleeper>,
+    // Tasks blocked in `recv`, in the order they blocked.
+    receiving: Vec<(TaskId, Value)>,
+    // Virtual time in milliseconds; only advances when every task is asleep or blocked.
+    clock: f64,
+    sleep_seq: u64,
+    // Set when the running task parks itself; a task that `yield`s without parking is ready.
+    parked: bool,
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Scheduler {
+            tasks: HashMap::new(),
+            next_id: MAIN + 1,
+            ready: VecDeque::new(),
+            sleeping: Vec::new(),
+            receiving: Vec::new(),
+            clock: 0.0,
+            sleep_seq: 0,
+            parked: false,
+        }
+    }
+}
+
+impl GcTrace for Scheduler {
+    fn trace(&self, heap: &Heap) {
+        for task in self.tasks.values() {
+            task.trace(heap);
+        }
+        for (_, channel) in &self.receiving {
+            channel.trace(heap);
+        }
+    }
+}
+
+impl Scheduler {
+    fn park(&mut self, task: TaskId, wait: Wait) {
+        self.parked = true;
+        match wait {
+            Wait::Ready => self.ready.push_back(task),
+            Wait::Sleep(ms) => {
+                self.sleep_seq += 1;
+                let due = Duration::try_from_secs_f64(ms / 1000.0)
+                    .ok()
+                    .and_then(|duration| Instant::now().checked_add(duration));
+                self.sleeping.push(Sleeper {
+                    deadline: self.clock + ms,
+                    seq: self.sleep_seq,
+                    task,
+                    due,
+                });
+            }
+            Wait::Receive(channel) => self.receiving.push((task, channel)),
+        }
+    }
+
+    /// Forgets every task, once they have all finished or been cancelled.
+    pub(crate) fn clear(&mut self) {
+        self.tasks.clear();
+        self.ready.clear();
+        self.sleeping.clear();
+        self.receiving.clear();
+    }
+
+    /// Makes receivers ready, in blocking order, while their channels have values for them.
+    fn wake_receivers(&mut self) {
+        let mut claimed: Vec<(Value, usize)> = Vec::new();
+        let mut still_waiting = Vec::new();
+        for (task, channel) in self.receiving.drain(..) {
+            let available = match channel.0.deref() {
+                Object::Channel(queue) => queue.len(),
+                _ => 0,
+            };
+            let claims = match claimed.iter_mut().find(|(c, _)| c.0 == channel.0) {
+                Some((_, claims)) => claims,
+                None => {
+                    claimed.push((channel.clone(), 0));
+                    &mut claimed.last_mut().unwrap().1
+                }
+            };
+            if *claims < available {
+                *claims += 1;
+                self.ready.push_back(task);
+            } else {
+                still_waiting.push((task, channel));
+            }
+        }
+        self.receiving = still_waiting;
+    }
+
+    /// Advances the clock to the earliest deadline and wakes that sleeper, once it has slept
+    /// for as long as it asked to in real time.
+    fn wake_next_sleeper(&mut self) -> bool {
+        let Some(next) = self
+            .sleeping
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.deadline.total_cmp(&b.deadline).then(a.seq.cmp(&b.seq)))
+            .map(|(index, _)| index)
+        else {
+            return false;
+        };
+        let sleeper = self.sleeping.remove(next);
+        // Nothing else can run until then, so block the thread.
+        match sleeper.due {
+            Some(due) => std::thread::sleep(due.saturating_duration_since(Instant::now())),
+            None => std::thread::sleep(Duration::MAX),
+        }
+        self.clock = self.clock.max(sleeper.deadline);
+        self.ready.push_back(sleeper.task);
+        true
+    }
+}
+
+impl Interpreter {
+    /// Blocks the current task until `wait` is over. In the main program this runs the
+    /// other tasks in the meantime; inside a task it hands control back to the scheduler.
+    fn block_current_task(&self, wait: Wait) -> Result<(), EasyScriptError> {
+        let current_task = self
+            .coroutines
+            .borrow()
+            .iter()
+            .rev()
+            .find_map(|entry| entry.coroutine.task_id());
+
+        match current_task {
+            Some(task) => {
+                self.scheduler.borrow_mut().park(task, wait);
+                // The innermost coroutine may be a generator the task is running; it parks
+                // along with the task.
+                let innermost = Rc::clone(&self.coroutines.borrow().last().unwrap().coroutine);
+                innermost.park(self)
+            }
+            None => {
+                self.scheduler.borrow_mut().park(MAIN, wait);
+                self.run_scheduler(true)
+            }
+        }
+    }
+
+    /// Runs tasks until the main program is ready again or, when it is not waiting,
+    /// until no task can make progress.
+    pub(crate) fn run_scheduler(&self, main_waiting: bool) -> Result<(), EasyScriptError> {
+        loop {
+            let mut scheduler = self.scheduler.borrow_mut();
+            scheduler.wake_receivers();
+            if let Some(task) = scheduler.ready.pop_front() {
+                if task == MAIN {
+                    return Ok(());
+                }
+                drop(scheduler);
+                self.run_task(task)?;
+                continue;
+            }
+            if scheduler.wake_next_sleeper() {
+                continue;
+            }
+            if !main_waiting {
+                // Tasks still blocked in `recv` can never be woken; the program cancels them.
+                return Ok(());
+            }
+            scheduler.receiving.retain(|(task, _)| *task != MAIN);
+            return Err(EasyScriptError::RuntimeError {
+                message: "recv() would block forever: no other task can send to the channel."
+                    .to_string(),
+                location: None,
+            });
+        }
+    }
+
+    /// Resumes a task until it blocks or finishes. An error in a task stops the program.
+    fn run_task(&self, task: TaskId) -> Result<(), EasyScriptError> {
+        let coroutine = {
+            let mut scheduler = self.scheduler.borrow_mut();
+            let Some(coroutine) = scheduler.tasks.get(&task).cloned() else {
+                return Ok(());
+            };
+            scheduler.parked = false;
+            coroutine
+        };
+        let nil = Value::nil(&self.heap);
+        let result = coroutine.resume(self, nil);
+        let mut scheduler = self.scheduler.borrow_mut();
+        match result {
+            Ok(Resumed::Yielded(_)) => {
+                // A bare `yield` in a task body behaves like `yield_now()`.
+                if !scheduler.parked {
+                    scheduler.ready.push_back(task);
+                }
+                Ok(())
+            }
+            Ok(Resumed::Finished) => {
+                scheduler.tasks.remove(&task);
+                Ok(())
+            }
+            Err(e) => {
+                scheduler.tasks.remove(&task);
+                Err(e)
+            }
+        }
+    }
+
+    /// Registers a task that will call `function` with `args`; it starts the next time the
+    /// current task blocks.
+    pub(crate) fn spawn_task(&self, function: Value, args: Vec<Value>) {
+        let mut scheduler = self.scheduler.borrow_mut();
+        let task = scheduler.next_id;
+        scheduler.next_id += 1;
+        scheduler
+            .tasks
+            .insert(task, Rc::new(Coroutine::new_task(task, function, args)));
+        scheduler.ready.push_back(task);
+    }
+
+    /// Lets every other ready task run before the current one continues.
+    pub(crate) fn yield_now(&self) -> Result<(), EasyScriptError> {
+        self.block_current_task(Wait::Ready)
+    }
+
+    /// Blocks the current task for `ms` milliseconds of virtual time.
+    pub(crate) fn sleep(&self, ms: f64) -> Result<(), EasyScriptError> {
+        self.block_current_task(Wait::Sleep(ms))
+    }
+
+    /// Waits until `channel` has a value, then takes the oldest one.
+    pub(crate) fn channel_recv(&self, channel: &Value) -> Result<Value, EasyScriptError> {
+        loop {
+            let mut handle = channel.0;
+            if let Object::Channel(queue) = handle.deref_mut() {
+                if let Some(value) = queue.pop_front() {
+                    return Ok(value);
+                }
+            }
+            self.block_current_task(Wait::Receive(channel.clone()))?;
+        }
+    }
+}
@@ -1,3 +1,4 @@
+use crate::bigint::BigInt;
 use crate::error::{EasyScriptError, SourceLocation};
 use crate::token::{Literal, Token};
 use std::collections::HashMap;
@@ -11,10 +12,16 @@ lazy_static::lazy_static! {
         m.insert("for", Token::KeywordFor);
         m.insert("fun", Token::KeywordFun);
         m.insert("in", Token::KeywordIn);
+        m.insert("is", Token::KeywordIs);
         m.insert("true", Token::KeywordTrue);
         m.insert("false", Token::KeywordFalse);
         m.insert("nil", Token::KeywordNil);
         m.insert("let", Token::KeywordLet); // 添加这一行
+        m.insert("const", Token::KeywordConst);
+        m.insert("yield", Token::KeywordYield);
+        m.insert("enum", Token::KeywordEnum);
+        m.insert("match", Token::KeywordMatch);
+        m.insert("defer", Token::KeywordDefer);
         m
     };
 }
@@ -28,6 +35,7 @@ pub struct Lexer<'a> {
     line: usize,    // 当前 Token 的起始行号
     column: usize,  // 当前 Token 的起始列号 (字符索引)
     tokens: Vec<Token>,
+    locations: Vec<SourceLocation>, // 每个 Token 的起始位置，与 tokens 一一对应
 }
 
 impl<'a> Lexer<'a> {
@@ -40,23 +48,38 @@ impl<'a> Lexer<'a> {
             line: 1,   // 初始行号为 1
             column: 1, // 初始列号为 1
             tokens: Vec::new(),
+            locations: Vec::new(),
         }
     }
     // 核心方法：扫描所有 Token
-    pub fn scan_tokens(mut self) -> Result<Vec<Token>, EasyScriptError> {
-        // 返回 Result
+    pub fn scan_tokens(self) -> Result<Vec<Token>, EasyScriptError> {
+        let tokens = self.scan_located_tokens()?;
+        Ok(tokens.into_iter().map(|(token, _)| token).collect()) // 成功时返回 Token 列表
+    }
+
+    // 扫描所有 Token，并附带每个 Token 的起始位置 (供 `--check` 报告错误位置)
+    pub fn scan_located_tokens(mut self) -> Result<Vec<(Token, SourceLocation)>, EasyScriptError> {
         // 在 main.rs 中初始化 lazy_static
         let _ = &*KEYWORDS;
 
         while self.peek().is_some() {
             self.start = self.current;
+            let location = SourceLocation {
+                line: self.line,
+                column: self.column,
+            };
             self.scan_token()?; // scan_token 现在返回 Result(())
+            self.locations.resize(self.tokens.len(), location);
         }
 
         // 添加文件结束符
         self.tokens.push(Token::Eof);
+        self.locations.push(SourceLocation {
+            line: self.line,
+            column: self.column,
+        });
 
-        Ok(self.tokens) // 成功时返回 Token 列表
+        Ok(self.tokens.into_iter().zip(self.locations).collect())
     }
 
     // ---------------------- 辅助方法 ----------------------
@@ -117,12 +140,37 @@ impl<'a> Lexer<'a> {
         })
     }
 
+    // 消耗直到行尾（不含换行符）的字符，并返回它们
+    fn consume_line(&mut self) -> String {
+        let mut text = String::new();
+        while let Some(c) = self.peek().filter(|&c| c != '\n') {
+            text.push(c);
+            self.advance();
+        }
+        text
+    }
+
     // ---------------------- Token 处理器 ----------------------
 
+    // 处理块注释 `#[ ... ]#`（开头的 `#[` 已被消耗）。块注释可以嵌套，
+    // 因此注释掉一段本身含有块注释的代码也是安全的。
+    fn handle_block_comment(&mut self, line: usize, column: usize) -> Result<(), EasyScriptError> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.advance() {
+                Some('#') if self.match_char('[') => depth += 1,
+                Some(']') if self.match_char('#') => depth -= 1,
+                Some(_) => {}
+                None => return self.error("Unterminated block comment.", line, column),
+            }
+        }
+        Ok(())
+    }
+
     // 处理字符串字面量
     fn handle_string(&mut self, line: usize, column: usize) -> Result<(), EasyScriptError> {
         // 查找下一个双引号
-        while self.peek().map_or(false, |c| c != '"' && c != '\n') {
+        while self.peek().is_some_and(|c| c != '"' && c != '\n') {
             self.advance();
         }
 
@@ -140,37 +188,206 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
 
-    // 处理数字字面量 (整数和浮点数)
+    // 处理字节串字面量 `b"..."`（已消耗 `b"`）：内容必须是 ASCII，任意字节用 `\xHH` 表示，
+    // `\\` 表示反斜杠本身
+    fn handle_bytes(&mut self, line: usize, column: usize) -> Result<(), EasyScriptError> {
+        let mut bytes = Vec::new();
+        loop {
+            match self.peek() {
+                Some('"') => break,
+                None | Some('\n') => {
+                    return self.error("Unterminated bytes literal.", line, column)
+                }
+                Some('\\') => {
+                    self.advance();
+                    match self.advance() {
+                        Some('\\') => bytes.push(b'\\'),
+                        Some('x') => {
+                            let digits: String = [self.advance(), self.advance()]
+                                .into_iter()
+                                .flatten()
+                                .collect();
+                            let is_hex =
+                                digits.len() == 2 && digits.chars().all(|c| c.is_ascii_hexdigit());
+                            match u8::from_str_radix(&digits, 16) {
+                                Ok(byte) if is_hex => bytes.push(byte),
+                                _ => {
+                                    return self.error(
+                                        &format!("Invalid escape in bytes literal: \\x{}", digits),
+                                        line,
+                                        column,
+                                    )
+                                }
+                            }
+                        }
+                        other => {
+                            let escape = other.map(String::from).unwrap_or_default();
+                            return self.error(
+                                &format!("Invalid escape in bytes literal: \\{}", escape),
+                                line,
+                                column,
+                            );
+                        }
+                    }
+                }
+                Some(c) if c.is_ascii() => {
+                    self.advance();
+                    bytes.push(c as u8);
+                }
+                Some(c) => {
+                    return self.error(
+                        &format!(
+                            "Non-ASCII character '{}' in bytes literal. Use \\x escapes instead.",
+                            c
+                        ),
+                        line,
+                        column,
+                    )
+                }
+            }
+        }
+        self.advance(); // 消耗闭合的双引号 "
+        self.add_token(Token::Literal(Literal::Bytes(bytes)));
+        Ok(())
+    }
+
+    // 处理数字字面量：十进制整数和浮点数 (可带指数 `1.5e-3`)，以及 `0x` / `0b` / `0o` 前缀的整数。
+    // 数字之间可以用 `_` 分隔 (`1_000_000`)。没有小数部分和指数的十进制字面量是整数
     fn handle_number(&mut self, line: usize, column: usize) -> Result<(), EasyScriptError> {
-        // 整数部分
-        while self.peek().map_or(false, |c| c.is_ascii_digit()) {
-            self.advance();
+        if self.substring() == "0" {
+            let prefixed = match self.peek() {
+                Some('x' | 'X') => Some((16, "hexadecimal")),
+                Some('b' | 'B') => Some((2, "binary")),
+                Some('o' | 'O') => Some((8, "octal")),
+                _ => None,
+            };
+            if let Some((radix, name)) = prefixed {
+                return self.handle_prefixed_number(radix, name, line, column);
+            }
         }
 
+        // 整数部分
+        self.consume_digits();
+
         // 小数部分
-        if self.peek() == Some('.') && self.peek_next().map_or(false, |c| c.is_ascii_digit()) {
+        let mut is_float = false;
+        if self.peek() == Some('.') && self.peek_next().is_some_and(|c| c.is_ascii_digit()) {
+            is_float = true;
             self.advance(); // 消耗 '.'
-            while self.peek().map_or(false, |c| c.is_ascii_digit()) {
+            self.consume_digits();
+        }
+
+        // 指数部分
+        if matches!(self.peek(), Some('e' | 'E')) {
+            is_float = true;
+            self.advance(); // 消耗 'e'
+            if matches!(self.peek(), Some('+' | '-')) {
                 self.advance();
             }
+            if !self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                return self.error(
+                    &format!("Malformed exponent in number literal: {}", self.substring()),
+                    line,
+                    column,
+                );
+            }
+            self.consume_digits();
         }
 
         let num_str = self.substring();
-        match num_str.parse::<f64>() {
-            Ok(num) => self.add_token(Token::Literal(Literal::Number(num))),
-            Err(_) => {
-                return self.error(&format!("Invalid number format: {}", num_str), line, column)
+        if !separators_are_valid(num_str, 10) {
+            return self.error(
+                &format!("Misplaced '_' in number literal: {}", num_str),
+                line,
+                column,
+            );
+        }
+        let digits = num_str.replace('_', "");
+        if !is_float {
+            // 超出 i64 范围的整数字面量成为大整数
+            let literal = match digits.parse::<i64>() {
+                Ok(num) => Literal::Integer(num),
+                Err(_) => Literal::BigInteger(BigInt::parse(&digits).unwrap()),
+            };
+            self.add_token(Token::Literal(literal));
+            return Ok(());
+        }
+        match digits.parse::<f64>() {
+            Ok(num) if num.is_finite() => self.add_token(Token::Literal(Literal::Number(num))),
+            _ => {
+                return self.error(
+                    &format!("Number literal is out of range: {}", num_str),
+                    line,
+                    column,
+                )
             }
         }
         Ok(())
     }
 
+    // 处理 `0x` / `0b` / `0o` 前缀的整数字面量 (已消耗开头的 '0')
+    fn handle_prefixed_number(
+        &mut self,
+        radix: u32,
+        name: &str,
+        line: usize,
+        column: usize,
+    ) -> Result<(), EasyScriptError> {
+        self.advance(); // 消耗 'x' / 'b' / 'o'
+                        // 连同紧随其后的字母一起消耗，以便报告 `0xFG` 这样的非法数字
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
+        {
+            self.advance();
+        }
+
+        let text = self.substring();
+        let body = &text[2..];
+        if let Some(bad) = body.chars().find(|&c| c != '_' && !c.is_digit(radix)) {
+            return self.error(
+                &format!("Invalid digit '{}' in {} literal: {}", bad, name, text),
+                line,
+                column,
+            );
+        }
+        if !body.chars().any(|c| c.is_digit(radix)) {
+            return self.error(
+                &format!("Missing digits in {} literal: {}", name, text),
+                line,
+                column,
+            );
+        }
+        if !separators_are_valid(body, radix) {
+            return self.error(
+                &format!("Misplaced '_' in number literal: {}", text),
+                line,
+                column,
+            );
+        }
+
+        let digits = body.replace('_', "");
+        let literal = match i64::from_str_radix(&digits, radix) {
+            Ok(num) => Literal::Integer(num),
+            Err(_) => Literal::BigInteger(BigInt::parse_radix(&digits, radix).unwrap()),
+        };
+        self.add_token(Token::Literal(literal));
+        Ok(())
+    }
+
+    // 消耗一串十进制数字及其中的 `_` 分隔符
+    fn consume_digits(&mut self) {
+        while self.peek().is_some_and(|c| c.is_ascii_digit() || c == '_') {
+            self.advance();
+        }
+    }
+
     // 处理标识符和关键字
     fn handle_identifier(&mut self) -> Result<(), EasyScriptError> {
         // 返回 Result(())
         while self
             .peek()
-            .map_or(false, |c| c.is_ascii_alphanumeric() || c == '_')
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == '_')
         {
             self.advance();
         }
@@ -207,19 +424,68 @@ impl<'a> Lexer<'a> {
             '[' => self.add_token(Token::LeftBracket),
             ']' => self.add_token(Token::RightBracket),
             ',' => self.add_token(Token::Comma),
-            '.' => self.add_token(Token::Dot),
+            '.' => {
+                let token = if self.peek() == Some('.') && self.peek_next() == Some('.') {
+                    self.advance();
+                    self.advance();
+                    Token::Ellipsis
+                } else {
+                    Token::Dot
+                };
+                self.add_token(token);
+            }
             ':' => self.add_token(Token::Colon),
             ';' => self.add_token(Token::Semicolon),
-            '+' => self.add_token(Token::Plus),
-            '-' => self.add_token(Token::Minus), // 一元负号在 Parser 中处理
-            '*' => self.add_token(Token::Star),
-            '%' => self.add_token(Token::Percent),
-            '^' => self.add_token(Token::Caret),
 
-            // 可能是双字符 Token
+            // 可能是双字符 Token (运算符后紧跟 '=' 时为复合赋值)
+            '+' => {
+                let token = if self.match_char('=') {
+                    Token::PlusEqual
+                } else {
+                    Token::Plus
+                };
+                self.add_token(token);
+            }
+            '-' => {
+                // 一元负号在 Parser 中处理
+                let token = if self.match_char('=') {
+                    Token::MinusEqual
+                } else if self.match_char('>') {
+                    Token::Arrow
+                } else {
+                    Token::Minus
+                };
+                self.add_token(token);
+            }
+            '*' => {
+                let token = if self.match_char('=') {
+                    Token::StarEqual
+                } else {
+                    Token::Star
+                };
+                self.add_token(token);
+            }
+            '%' => {
+                let token = if self.match_char('=') {
+                    Token::PercentEqual
+                } else {
+                    Token::Percent
+                };
+                self.add_token(token);
+            }
+            '^' => {
+                let token = if self.match_char('=') {
+                    Token::CaretEqual
+                } else {
+                    Token::Caret
+                };
+                self.add_token(token);
+            }
             '=' => {
                 let token = if self.match_char('=') {
                     Token::EqualEqual
+                } else if self.match_char('>') {
+                    Token::FatArrow
                 } else {
                     Token::Equal
                 };
@@ -237,7 +503,11 @@ impl<'a> Lexer<'a> {
                 let token = if self.match_char('=') {
                     Token::LessEqual
                 } else if self.match_char('<') {
-                    Token::ShiftLeft
+                    if self.match_char('=') {
+                        Token::ShiftLeftEqual
+                    } else {
+                        Token::ShiftLeft
+                    }
                 } else {
                     Token::Less
                 };
@@ -247,7 +517,11 @@ impl<'a> Lexer<'a> {
                 let token = if self.match_char('=') {
                     Token::GreaterEqual
                 } else if self.match_char('>') {
-                    Token::ShiftRight
+                    if self.match_char('=') {
+                        Token::ShiftRightEqual
+                    } else {
+                        Token::ShiftRight
+                    }
                 } else {
                     Token::Greater
                 };
@@ -256,6 +530,8 @@ impl<'a> Lexer<'a> {
             '&' => {
                 let token = if self.match_char('&') {
                     Token::And
+                } else if self.match_char('=') {
+                    Token::AmpersandEqual
                 } else {
                     Token::Ampersand
                 };
@@ -264,18 +540,59 @@ impl<'a> Lexer<'a> {
             '|' => {
                 let token = if self.match_char('|') {
                     Token::Or
+                } else if self.match_char('=') {
+                    Token::PipeEqual
                 } else {
                     Token::Pipe
                 };
                 self.add_token(token);
             }
+            '?' => {
+                let token = if self.match_char('?') {
+                    Token::QuestionQuestion
+                } else if self.match_char('.') {
+                    Token::QuestionDot
+                } else if self.match_char('[') {
+                    Token::QuestionBracket
+                } else {
+                    return self.error(
+                        "Unexpected character: ? (expected '?.', '?[' or '??')",
+                        token_start_line,
+                        token_start_column,
+                    );
+                };
+                self.add_token(token);
+            }
             '#' => {
-                // 处理行注释：跳过直到行尾或文件结束
-                while self.peek().map_or(false, |c| c != '\n') {
-                    self.advance();
+                if self.match_char('[') {
+                    self.handle_block_comment(token_start_line, token_start_column)?;
+                } else if self.match_char('{') {
+                    // 集合字面量 `#{1, 2}`：`#{` 不开始注释，以 `{` 开头的注释要写成 `# {`
+                    self.add_token(Token::HashBrace);
+                } else if self.match_char('#') {
+                    // `##` 文档注释：保留本行文本，由解析器挂到其后的绑定上
+                    let text = self.consume_line();
+                    let text = text.strip_prefix(' ').unwrap_or(&text).to_string();
+                    self.add_token(Token::DocComment(text));
+                } else {
+                    // 处理行注释：跳过直到行尾或文件结束
+                    self.consume_line();
                 }
             }
-            '/' => self.add_token(Token::Slash),
+            '/' => {
+                let token = if self.match_char('/') {
+                    if self.match_char('=') {
+                        Token::SlashSlashEqual
+                    } else {
+                        Token::SlashSlash
+                    }
+                } else if self.match_char('=') {
+                    Token::SlashEqual
+                } else {
+                    Token::Slash
+                };
+                self.add_token(token);
+            }
 
             // 忽略空白字符
             ' ' | '\r' | '\t' => {}
@@ -283,6 +600,10 @@ impl<'a> Lexer<'a> {
 
             // 字符串字面量
             '"' => self.handle_string(token_start_line, token_start_column)?,
+            // 字节串字面量 b"..."
+            'b' if self.match_char('"') => {
+                self.handle_bytes(token_start_line, token_start_column)?
+            }
             // 数字字面量 (0-9 或 .)
             c if c.is_ascii_digit() => self.handle_number(token_start_line, token_start_column)?,
 
@@ -306,6 +627,17 @@ impl<'a> Lexer<'a> {
 // 由于我们不在 Coding Mode 修改 Cargo.toml，所以我们假设用户会自行添加或我们稍后补充。
 // 鉴于这是一个关键依赖，我将提醒用户，并提供 Cargo.toml 的修改。
 
+// `_` 只能出现在两个数字之间：不能在开头、结尾、紧邻小数点或指数，也不能连续出现
+fn separators_are_valid(text: &str, radix: u32) -> bool {
+    let chars: Vec<char> = text.chars().collect();
+    chars.iter().enumerate().all(|(i, &c)| {
+        c != '_'
+            || (i > 0
+                && chars[i - 1].is_digit(radix)
+                && chars.get(i + 1).is_some_and(|next| next.is_digit(radix)))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -324,7 +656,7 @@ mod tests {
 
     #[test]
     fn test_single_tokens() {
-        let tokens = setup_lexer("(){}[],.:;+-*/%^=!=<<>>&&||");
+        let tokens = setup_lexer("(){}[],.:;+-*/%^ =!=<<>>&&||");
         assert_eq!(
             tokens,
             vec![
@@ -355,9 +687,77 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compound_assignment_tokens() {
+        let tokens = setup_lexer("+= -= *= /= //= // %= <<= >>= &= |= ^= <= >=");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::PlusEqual,
+                Token::MinusEqual,
+                Token::StarEqual,
+                Token::SlashEqual,
+                Token::SlashSlashEqual,
+                Token::SlashSlash,
+                Token::PercentEqual,
+                Token::ShiftLeftEqual,
+                Token::ShiftRightEqual,
+                Token::AmpersandEqual,
+                Token::PipeEqual,
+                Token::CaretEqual,
+                Token::LessEqual,
+                Token::GreaterEqual,
+                Token::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_nil_safe_tokens() {
+        let tokens = setup_lexer("a?.b?[0]?.(1) ?? c");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("a".to_string()),
+                Token::QuestionDot,
+                Token::Identifier("b".to_string()),
+                Token::QuestionBracket,
+                Token::Literal(Literal::Integer(0)),
+                Token::RightBracket,
+                Token::QuestionDot,
+                Token::LeftParen,
+                Token::Literal(Literal::Integer(1)),
+                Token::RightParen,
+                Token::QuestionQuestion,
+                Token::Identifier("c".to_string()),
+                Token::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ellipsis() {
+        let tokens = setup_lexer("f(...xs). ..");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Identifier("f".to_string()),
+                Token::LeftParen,
+                Token::Ellipsis,
+                Token::Identifier("xs".to_string()),
+                Token::RightParen,
+                Token::Dot,
+                Token::Dot,
+                Token::Dot,
+                Token::Eof
+            ]
+        );
+    }
+
     #[test]
     fn test_keywords() {
-        let tokens = setup_lexer("if else for fun in true false nil let");
+        let tokens =
+            setup_lexer("if else for fun in is true false nil let const enum match defer => ->");
         assert_eq!(
             tokens,
             vec![
@@ -366,10 +766,17 @@ mod tests {
                 Token::KeywordFor,
                 Token::KeywordFun,
                 Token::KeywordIn,
+                Token::KeywordIs,
                 Token::KeywordTrue,
                 Token::KeywordFalse,
                 Token::KeywordNil,
                 Token::KeywordLet,
+                Token::KeywordConst,
+                Token::KeywordEnum,
+                Token::KeywordMatch,
+                Token::KeywordDefer,
+                Token::FatArrow,
+                Token::Arrow,
                 Token::Eof
             ]
         );
@@ -392,18 +799,57 @@ mod tests {
 
     #[test]
     fn test_numbers() {
-        let tokens = setup_lexer("123 45.67 0");
+        let tokens = setup_lexer("123 45.67 0 2.0");
         assert_eq!(
             tokens,
             vec![
-                Token::Literal(Literal::Number(123.0)),
+                Token::Literal(Literal::Integer(123)),
                 Token::Literal(Literal::Number(45.67)),
-                Token::Literal(Literal::Number(0.0)),
+                Token::Literal(Literal::Integer(0)),
+                Token::Literal(Literal::Number(2.0)),
+                Token::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_number_formats() {
+        let tokens = setup_lexer("0xFF 0b1010 0o17 1_000_000 1.5e-3 2E3 0xFFFF_FFFF_FFFF_FFFF");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Literal(Literal::Integer(255)),
+                Token::Literal(Literal::Integer(10)),
+                Token::Literal(Literal::Integer(15)),
+                Token::Literal(Literal::Integer(1_000_000)),
+                Token::Literal(Literal::Number(0.0015)),
+                Token::Literal(Literal::Number(2000.0)),
+                Token::Literal(Literal::BigInteger(
+                    BigInt::parse("18446744073709551615").unwrap()
+                )),
                 Token::Eof
             ]
         );
     }
 
+    #[test]
+    fn test_malformed_numbers() {
+        for (source, message) in [
+            ("0x", "Missing digits in hexadecimal literal: 0x"),
+            ("0b102", "Invalid digit '2' in binary literal: 0b102"),
+            ("1__000", "Misplaced '_' in number literal: 1__000"),
+            ("1_.5", "Misplaced '_' in number literal: 1_.5"),
+            ("1e+", "Malformed exponent in number literal: 1e+"),
+            ("1e999", "Number literal is out of range: 1e999"),
+        ] {
+            let error = Lexer::new(source).scan_tokens().unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("[Lexer Error at line 1 column 1]: {}", message)
+            );
+        }
+    }
+
     #[test]
     fn test_strings() {
         let tokens = setup_lexer("\"hello\" \"world\"");
@@ -426,18 +872,97 @@ mod tests {
                 Token::KeywordLet,
                 Token::Identifier("a".to_string()),
                 Token::Equal,
-                Token::Literal(Literal::Number(1.0)),
+                Token::Literal(Literal::Integer(1)),
                 Token::Semicolon,
                 Token::KeywordLet,
                 Token::Identifier("b".to_string()),
                 Token::Equal,
-                Token::Literal(Literal::Number(2.0)),
+                Token::Literal(Literal::Integer(2)),
                 Token::Semicolon,
                 Token::Eof
             ]
         );
     }
 
+    #[test]
+    fn test_hash_brace_is_not_a_comment() {
+        let tokens = setup_lexer("#{todo}\n# {todo}\n");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::HashBrace,
+                Token::Identifier("todo".to_string()),
+                Token::RightBrace,
+                Token::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_block_comments() {
+        let tokens = setup_lexer("1 #[ a #[ nested ]# b\n c ]# + 2");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Literal(Literal::Integer(1)),
+                Token::Plus,
+                Token::Literal(Literal::Integer(2)),
+                Token::Eof
+            ]
+        );
+
+        let error = Lexer::new("let a = 1;\n  #[ #[ ]# open")
+            .scan_tokens()
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "[Lexer Error at line 2 column 3]: Unterminated block comment."
+        );
+    }
+
+    #[test]
+    fn test_doc_comments() {
+        let tokens = setup_lexer("## Adds one.\n##\n##  Indented.\nlet f");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::DocComment("Adds one.".to_string()),
+                Token::DocComment("".to_string()),
+                Token::DocComment(" Indented.".to_string()),
+                Token::KeywordLet,
+                Token::Identifier("f".to_string()),
+                Token::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bytes_literal() {
+        let tokens = setup_lexer(r#"b"A\x00\\" b"#);
+        assert_eq!(
+            tokens,
+            vec![
+                Token::Literal(Literal::Bytes(vec![b'A', 0, b'\\'])),
+                Token::Identifier("b".to_string()),
+                Token::Eof
+            ]
+        );
+    }
+
+    #[test]
+    fn test_set_literal_brace() {
+        let tokens = setup_lexer("#{1} # {not a set}");
+        assert_eq!(
+            tokens,
+            vec![
+                Token::HashBrace,
+                Token::Literal(Literal::Integer(1)),
+                Token::RightBrace,
+                Token::Eof
+            ]
+        );
+    }
+
     #[test]
     fn test_multi_line_input() {
         let source = "let a = 10;\nlet b = 20;\na + b";
@@ -449,12 +974,12 @@ mod tests {
                 Token::KeywordLet,
                 Token::Identifier("a".to_string()),
                 Token::Equal,
-                Token::Literal(Literal::Number(10.0)),
+                Token::Literal(Literal::Integer(10)),
                 Token::Semicolon,
                 Token::KeywordLet,
                 Token::Identifier("b".to_string()),
                 Token::Equal,
-                Token::Literal(Literal::Number(20.0)),
+                Token::Literal(Literal::Integer(20)),
                 Token::Semicolon,
                 Token::Identifier("a".to_string()),
                 Token::Plus,
@@ -475,12 +1000,12 @@ mod tests {
                 Token::KeywordLet,
                 Token::Identifier("a".to_string()),
                 Token::Equal,
-                Token::Literal(Literal::Number(10.0)),
+                Token::Literal(Literal::Integer(10)),
                 Token::Semicolon,
                 Token::KeywordLet,
                 Token::Identifier("b".to_string()),
                 Token::Equal,
-                Token::Literal(Literal::Number(20.0)),
+                Token::Literal(Literal::Integer(20)),
                 Token::Semicolon,
                 Token::Eof
             ]
@@ -2,12 +2,19 @@
 
 // Declare all modules in the library.
 pub mod ast;
+pub mod bigint;
+pub mod check;
+pub mod decimal;
+pub mod doc;
 pub mod environment;
 pub mod error;
 pub mod interpreter;
 pub mod lexer;
 pub mod native;
+pub mod ordered_map;
 pub mod parser;
+pub mod signature;
+pub mod slice;
 pub mod token;
 pub mod value; // Add this line
 
@@ -1,13 +1,17 @@
 // main.rs now acts as a consumer of the `easyscript_rs` library
-use easyscript_rs::{Interpreter, Lexer, Parser};
+use easyscript_rs::{check, doc, Interpreter, Lexer, Parser};
 use std::env; // Added
 use std::fs; // Added
 
 fn main() {
     let args: Vec<String> = env::args().collect();
 
-    let source = if args.len() == 2 {
-        let file_path = &args[1];
+    // `--doc <文件路径>` 只解析脚本，打印其顶层带 `##` 文档注释的绑定
+    let doc_mode = args.len() == 3 && args[1] == "--doc";
+    // `--check <文件路径>` 只解析脚本，按类型注解做静态检查，不运行它
+    let check_mode = args.len() == 3 && args[1] == "--check";
+    let source = if args.len() == 2 || doc_mode || check_mode {
+        let file_path = &args[args.len() - 1];
         match fs::read_to_string(file_path) {
             Ok(content) => content,
             Err(e) => {
@@ -18,9 +22,43 @@ fn main() {
     } else {
         eprintln!("用法: {} <文件路径>", args[0]);
         eprintln!("  例如: {} examples/hello.es", args[0]);
+        eprintln!("  或:   {} --doc <文件路径>  (列出文档注释)", args[0]);
+        eprintln!("  或:   {} --check <文件路径>  (检查类型注解)", args[0]);
         std::process::exit(1);
     };
 
+    if check_mode {
+        let program = Lexer::new(&source)
+            .scan_located_tokens()
+            .and_then(|tokens| Parser::with_locations(tokens).parse());
+        let errors = match program {
+            Ok(program) => check::check(&program),
+            Err(e) => vec![e],
+        };
+        if errors.is_empty() {
+            println!("类型检查通过。");
+            return;
+        }
+        for e in &errors {
+            eprintln!("{}", e);
+        }
+        std::process::exit(1);
+    }
+
+    if doc_mode {
+        let program = Lexer::new(&source)
+            .scan_tokens()
+            .and_then(|tokens| Parser::new(tokens).parse());
+        match program {
+            Ok(program) => println!("{}", doc::document(&program)),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+
     println!("EasyScript 解释器启动...");
     println!("\n--- 源代码 ---\n{}", source);
 
@@ -1,5 +1,17 @@
-use crate::environment::EnvironmentRef;
-use crate::value::{Heap, NativeFunction, Object, Value};
+use crate::bigint::BigInt;
+use crate::decimal::{Decimal, DecimalContext, Rounding};
+use crate::doc::render_entry;
+use crate::interpreter::NativeContext;
+use crate::ordered_map::OrderedMap;
+use crate::signature::{
+    format_signature, ParamKind,
+    ParamKind::{Optional, Required, Rest},
+    Signature,
+};
+use crate::value::{
+    compare_numbers, EnumValue, EnumVariant, FunctionObjectInner, Heap, IteratorInner,
+    NativeFunction, Object, SetElements, Value,
+};
 use std::collections::HashMap;
 use std::io::{self, Write};
 use std::rc::Rc;
@@ -7,54 +19,444 @@ use std::rc::Rc;
 // --- BUILT-IN METHODS REGISTRY ---
 // BUILTIN_METHODS is no longer a static OnceCell, it will be initialized per Interpreter instance.
 
+/// The builtin methods of each type, by type name and method name, with their signatures.
+pub type BuiltinMethods = HashMap<&'static str, HashMap<&'static str, (NativeFunction, Signature)>>;
+
+// Registers a builtin method. Its signature lists the receiver as `self`, then `params`, so
+// the method binds keyword arguments and reports arity errors like any other native.
+fn add_method(
+    methods: &mut HashMap<&'static str, (NativeFunction, Signature)>,
+    name: &'static str,
+    function: NativeFunction,
+    params: &[(&'static str, ParamKind)],
+) {
+    let params: Vec<_> = std::iter::once(("self", Required))
+        .chain(params.iter().copied())
+        .collect();
+    methods.insert(name, (function, Signature::new(name, &params)));
+}
+
 // Helper function to initialize the map
-pub fn init_builtin_methods_map(
-    _heap: &mut Heap,
-) -> HashMap<&'static str, HashMap<&'static str, NativeFunction>> {
+pub fn init_builtin_methods_map(_heap: &Heap) -> BuiltinMethods {
     let mut methods = HashMap::new();
 
     // --- String Methods ---
     let mut string_methods = HashMap::new();
-    string_methods.insert("trim", Rc::new(str_trim_fn) as NativeFunction);
-    string_methods.insert("len", Rc::new(len_fn) as NativeFunction);
-    string_methods.insert("starts_with", Rc::new(str_starts_with_fn) as NativeFunction);
-    string_methods.insert("find", Rc::new(str_find_fn) as NativeFunction);
-    string_methods.insert("contains", Rc::new(str_contains_fn) as NativeFunction);
-    string_methods.insert("replace", Rc::new(str_replace_fn) as NativeFunction);
-    string_methods.insert("split", Rc::new(str_split_fn) as NativeFunction);
-    string_methods.insert("to_upper", Rc::new(str_to_upper_fn) as NativeFunction);
-    string_methods.insert("to_lower", Rc::new(str_to_lower_fn) as NativeFunction);
-    string_methods.insert("ends_with", Rc::new(str_ends_with_fn) as NativeFunction);
-    string_methods.insert("substring", Rc::new(str_substring_fn) as NativeFunction);
+    add_method(&mut string_methods, "trim", Rc::new(str_trim_fn), &[]);
+    add_method(&mut string_methods, "len", Rc::new(len_fn), &[]);
+    add_method(
+        &mut string_methods,
+        "starts_with",
+        Rc::new(str_starts_with_fn),
+        &[("prefix", Required)],
+    );
+    add_method(
+        &mut string_methods,
+        "find",
+        Rc::new(str_find_fn),
+        &[("substring", Required)],
+    );
+    add_method(
+        &mut string_methods,
+        "contains",
+        Rc::new(str_contains_fn),
+        &[("substring", Required)],
+    );
+    add_method(
+        &mut string_methods,
+        "replace",
+        Rc::new(str_replace_fn),
+        &[("old", Required), ("new", Required)],
+    );
+    add_method(
+        &mut string_methods,
+        "split",
+        Rc::new(str_split_fn),
+        &[("delimiter", Required)],
+    );
+    add_method(
+        &mut string_methods,
+        "to_upper",
+        Rc::new(str_to_upper_fn),
+        &[],
+    );
+    add_method(
+        &mut string_methods,
+        "to_lower",
+        Rc::new(str_to_lower_fn),
+        &[],
+    );
+    add_method(
+        &mut string_methods,
+        "ends_with",
+        Rc::new(str_ends_with_fn),
+        &[("suffix", Required)],
+    );
+    add_method(
+        &mut string_methods,
+        "substring",
+        Rc::new(str_substring_fn),
+        &[("start", Required), ("end", Optional)],
+    );
+    add_method(
+        &mut string_methods,
+        "encode",
+        Rc::new(str_encode_fn),
+        &[("encoding", Optional)],
+    );
     methods.insert("string", string_methods);
 
+    // --- Bytes Methods ---
+    let mut bytes_methods = HashMap::new();
+    add_method(&mut bytes_methods, "len", Rc::new(len_fn), &[]);
+    add_method(&mut bytes_methods, "hex", Rc::new(bytes_hex_fn), &[]);
+    add_method(
+        &mut bytes_methods,
+        "decode",
+        Rc::new(bytes_decode_fn),
+        &[("encoding", Optional)],
+    );
+    methods.insert("bytes", bytes_methods);
+
     // --- List Methods ---
     let mut list_methods = HashMap::new();
-    list_methods.insert("len", Rc::new(len_fn) as NativeFunction);
-    list_methods.insert("push", Rc::new(list_push_fn) as NativeFunction);
-    list_methods.insert("pop", Rc::new(list_pop_fn) as NativeFunction);
-    list_methods.insert("remove", Rc::new(list_remove_fn) as NativeFunction);
-    list_methods.insert("insert", Rc::new(list_insert_fn) as NativeFunction);
-    list_methods.insert("join", Rc::new(list_join_fn) as NativeFunction);
+    add_method(&mut list_methods, "len", Rc::new(len_fn), &[]);
+    add_method(
+        &mut list_methods,
+        "push",
+        Rc::new(list_push_fn),
+        &[("element", Required)],
+    );
+    add_method(&mut list_methods, "pop", Rc::new(list_pop_fn), &[]);
+    add_method(
+        &mut list_methods,
+        "remove",
+        Rc::new(list_remove_fn),
+        &[("index", Required)],
+    );
+    add_method(
+        &mut list_methods,
+        "insert",
+        Rc::new(list_insert_fn),
+        &[("index", Required), ("element", Required)],
+    );
+    add_method(
+        &mut list_methods,
+        "join",
+        Rc::new(list_join_fn),
+        &[("separator", Required)],
+    );
+    add_method(
+        &mut list_methods,
+        "map",
+        Rc::new(list_map_fn),
+        &[("function", Required)],
+    );
+    add_method(
+        &mut list_methods,
+        "filter",
+        Rc::new(list_filter_fn),
+        &[("predicate", Required)],
+    );
+    add_method(
+        &mut list_methods,
+        "reduce",
+        Rc::new(list_reduce_fn),
+        &[("function", Required), ("initial", Optional)],
+    );
+    add_method(
+        &mut list_methods,
+        "sort_by",
+        Rc::new(list_sort_by_fn),
+        &[("key", Required)],
+    );
+    add_method(
+        &mut list_methods,
+        "any",
+        Rc::new(list_any_fn),
+        &[("predicate", Required)],
+    );
+    add_method(
+        &mut list_methods,
+        "all",
+        Rc::new(list_all_fn),
+        &[("predicate", Required)],
+    );
+    add_method(
+        &mut list_methods,
+        "find",
+        Rc::new(list_find_fn),
+        &[("predicate", Required)],
+    );
     methods.insert("list", list_methods);
 
     // --- Map Methods ---
     let mut map_methods = HashMap::new();
-    map_methods.insert("keys", Rc::new(keys_fn) as NativeFunction);
-    map_methods.insert("values", Rc::new(values_fn) as NativeFunction);
-    map_methods.insert("len", Rc::new(len_fn) as NativeFunction);
-    map_methods.insert("has_key", Rc::new(map_has_key_fn) as NativeFunction);
+    add_method(&mut map_methods, "keys", Rc::new(keys_fn), &[]);
+    add_method(&mut map_methods, "values", Rc::new(values_fn), &[]);
+    add_method(&mut map_methods, "len", Rc::new(len_fn), &[]);
+    add_method(
+        &mut map_methods,
+        "has_key",
+        Rc::new(map_has_key_fn),
+        &[("key", Required)],
+    );
+    add_method(
+        &mut map_methods,
+        "map_values",
+        Rc::new(map_map_values_fn),
+        &[("function", Required)],
+    );
+    add_method(
+        &mut map_methods,
+        "filter",
+        Rc::new(map_filter_fn),
+        &[("predicate", Required)],
+    );
+    add_method(
+        &mut map_methods,
+        "sort_keys",
+        Rc::new(map_sort_keys_fn),
+        &[],
+    );
     methods.insert("map", map_methods);
 
+    // --- Set Methods ---
+    let mut set_methods = HashMap::new();
+    add_method(&mut set_methods, "len", Rc::new(len_fn), &[]);
+    add_method(
+        &mut set_methods,
+        "add",
+        Rc::new(set_add_fn),
+        &[("element", Required)],
+    );
+    add_method(
+        &mut set_methods,
+        "remove",
+        Rc::new(set_remove_fn),
+        &[("element", Required)],
+    );
+    add_method(
+        &mut set_methods,
+        "contains",
+        Rc::new(set_contains_fn),
+        &[("element", Required)],
+    );
+    add_method(
+        &mut set_methods,
+        "union",
+        Rc::new(set_union_fn),
+        &[("other", Required)],
+    );
+    add_method(
+        &mut set_methods,
+        "intersection",
+        Rc::new(set_intersection_fn),
+        &[("other", Required)],
+    );
+    add_method(
+        &mut set_methods,
+        "difference",
+        Rc::new(set_difference_fn),
+        &[("other", Required)],
+    );
+    add_method(
+        &mut set_methods,
+        "is_subset",
+        Rc::new(set_is_subset_fn),
+        &[("other", Required)],
+    );
+    methods.insert("set", set_methods);
+
+    // --- Decimal Methods ---
+    let mut decimal_methods = HashMap::new();
+    add_method(
+        &mut decimal_methods,
+        "round",
+        Rc::new(decimal_round_fn),
+        &[("places", Required), ("rounding", Optional)],
+    );
+    methods.insert("decimal", decimal_methods);
+
+    // --- Iterator Methods ---
+    let mut iterator_methods = HashMap::new();
+    add_method(&mut iterator_methods, "next", Rc::new(iter_next_fn), &[]);
+    add_method(
+        &mut iterator_methods,
+        "collect",
+        Rc::new(iter_collect_fn),
+        &[],
+    );
+    add_method(
+        &mut iterator_methods,
+        "map",
+        Rc::new(iter_map_fn),
+        &[("function", Required)],
+    );
+    add_method(
+        &mut iterator_methods,
+        "filter",
+        Rc::new(iter_filter_fn),
+        &[("predicate", Required)],
+    );
+    add_method(
+        &mut iterator_methods,
+        "take",
+        Rc::new(iter_take_fn),
+        &[("count", Required)],
+    );
+    add_method(
+        &mut iterator_methods,
+        "zip",
+        Rc::new(iter_zip_fn),
+        &[("other", Required)],
+    );
+    add_method(
+        &mut iterator_methods,
+        "enumerate",
+        Rc::new(iter_enumerate_fn),
+        &[],
+    );
+    add_method(
+        &mut iterator_methods,
+        "chain",
+        Rc::new(iter_chain_fn),
+        &[("other", Required)],
+    );
+    methods.insert("iterator", iterator_methods);
+
+    // --- Channel Methods ---
+    let mut channel_methods = HashMap::new();
+    add_method(
+        &mut channel_methods,
+        "send",
+        Rc::new(channel_send_fn),
+        &[("value", Required)],
+    );
+    add_method(&mut channel_methods, "recv", Rc::new(channel_recv_fn), &[]);
+    add_method(&mut channel_methods, "len", Rc::new(len_fn), &[]);
+    methods.insert("channel", channel_methods);
+
     methods
 }
 
+// --- GLOBAL FUNCTIONS ---
+
+/// The global native functions, each with the signature it declares. The interpreter binds
+/// arguments against these signatures, so arity errors read the same as for user functions.
+pub fn global_natives() -> Vec<(NativeFunction, Signature)> {
+    vec![
+        (
+            Rc::new(print_fn),
+            Signature::new("print", &[("values", Rest), ("sep", Optional)]),
+        ),
+        (
+            Rc::new(len_fn),
+            Signature::new("len", &[("value", Required)]),
+        ),
+        (
+            Rc::new(type_fn),
+            Signature::new("type", &[("value", Required)]),
+        ),
+        (
+            Rc::new(bool_fn),
+            Signature::new("bool", &[("value", Required)]),
+        ),
+        (
+            Rc::new(str_fn),
+            Signature::new("str", &[("value", Required)]),
+        ),
+        (
+            Rc::new(num_fn),
+            Signature::new("num", &[("value", Required)]),
+        ),
+        (
+            Rc::new(int_fn),
+            Signature::new("int", &[("value", Required)]),
+        ),
+        (
+            Rc::new(float_fn),
+            Signature::new("float", &[("value", Required)]),
+        ),
+        (
+            Rc::new(decimal_fn),
+            Signature::new("decimal", &[("value", Required)]),
+        ),
+        (
+            Rc::new(set_decimal_context_fn),
+            Signature::new(
+                "set_decimal_context",
+                &[("precision", Required), ("rounding", Optional)],
+            ),
+        ),
+        (
+            Rc::new(input_fn),
+            Signature::new("input", &[("prompt", Optional)]),
+        ),
+        (
+            Rc::new(repr_fn),
+            Signature::new("repr", &[("value", Required)]),
+        ),
+        (
+            Rc::new(help_fn),
+            Signature::new("help", &[("value", Required)]),
+        ),
+        (
+            Rc::new(make_map_fn),
+            Signature::new("make_map", &[("pairs", Required)]),
+        ),
+        (
+            Rc::new(from_hex_fn),
+            Signature::new("from_hex", &[("text", Required)]),
+        ),
+        (
+            Rc::new(set_fn),
+            Signature::new("set", &[("values", Optional)]),
+        ),
+        (
+            Rc::new(iter_fn),
+            Signature::new("iter", &[("value", Required)]),
+        ),
+        (
+            Rc::new(range_fn),
+            Signature::new(
+                "range",
+                &[("start", Required), ("end", Optional), ("step", Optional)],
+            ),
+        ),
+        (Rc::new(lines_fn), Signature::new("lines", &[])),
+        (Rc::new(channel_fn), Signature::new("channel", &[])),
+        (Rc::new(gc_collect_fn), Signature::new("gc_collect", &[])),
+        (
+            Rc::new(freeze_fn),
+            Signature::new("freeze", &[("value", Required)]),
+        ),
+        (
+            Rc::new(is_frozen_fn),
+            Signature::new("is_frozen", &[("value", Required)]),
+        ),
+        (
+            Rc::new(set_proto_fn),
+            Signature::new("set_proto", &[("object", Required), ("proto", Required)]),
+        ),
+        (
+            Rc::new(get_proto_fn),
+            Signature::new("get_proto", &[("object", Required)]),
+        ),
+        (
+            Rc::new(spawn_fn),
+            Signature::new("spawn", &[("function", Required), ("args", Rest)]),
+        ),
+        (Rc::new(yield_now_fn), Signature::new("yield_now", &[])),
+        (
+            Rc::new(sleep_fn),
+            Signature::new("sleep", &[("ms", Required)]),
+        ),
+    ]
+}
+
 // Native string starts_with method
-pub fn str_starts_with_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn str_starts_with_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 2 {
         return Err(format!(
             "starts_with() expected 2 arguments (self, prefix), but got {}",
@@ -84,15 +486,11 @@ pub fn str_starts_with_fn(
         }
     };
 
-    Ok(Value::boolean(heap, self_string.starts_with(prefix)))
+    Ok(Value::boolean(ctx, self_string.starts_with(prefix)))
 }
 
 // Native string contains method
-pub fn str_contains_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn str_contains_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 2 {
         return Err(format!(
             "contains() expected 2 arguments (self, substring), but got {}",
@@ -122,14 +520,10 @@ pub fn str_contains_fn(
         }
     };
 
-    Ok(Value::boolean(heap, self_string.contains(substring)))
+    Ok(Value::boolean(ctx, self_string.contains(substring)))
 }
 // Native string find method
-pub fn str_find_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn str_find_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 2 {
         return Err(format!(
             "find() expected 2 arguments (self, substring), but got {}",
@@ -163,18 +557,14 @@ pub fn str_find_fn(
     if let Some(byte_index) = self_string.find(substring) {
         // Convert byte index to character index
         let char_index = self_string[..byte_index].chars().count();
-        Ok(Value::number(heap, char_index as f64))
+        Ok(Value::integer(ctx, char_index as i64))
     } else {
-        Ok(Value::nil(heap))
+        Ok(Value::nil(ctx))
     }
 }
 
 // Native string replace method
-pub fn str_replace_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn str_replace_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 3 {
         return Err(format!(
             "replace() expected 3 arguments (self, old, new), but got {}",
@@ -216,17 +606,13 @@ pub fn str_replace_fn(
     };
 
     Ok(Value::string(
-        heap,
+        ctx,
         self_string.replace(old_substring, new_substring),
     ))
 }
 
 // Native string split method
-pub fn str_split_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn str_split_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 2 {
         return Err(format!(
             "split() expected 2 arguments (self, delimiter), but got {}",
@@ -260,24 +646,20 @@ pub fn str_split_fn(
     let parts: Vec<Value> = if delimiter.is_empty() {
         self_string
             .chars()
-            .map(|c| Value::string(heap, c.to_string()))
+            .map(|c| Value::string(ctx, c.to_string()))
             .collect()
     } else {
         self_string
             .split(delimiter)
-            .map(|s| Value::string(heap, s.to_string()))
+            .map(|s| Value::string(ctx, s.to_string()))
             .collect()
     };
 
-    Ok(Value::list(heap, parts))
+    Ok(Value::list(ctx, parts))
 }
 
 // Native string to_upper method
-pub fn str_to_upper_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn str_to_upper_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!(
             "to_upper() expected 1 argument (self), but got {}",
@@ -286,7 +668,7 @@ pub fn str_to_upper_fn(
     }
 
     match &args[0].0.deref() {
-        Object::String(s) => Ok(Value::string(heap, s.to_uppercase())),
+        Object::String(s) => Ok(Value::string(ctx, s.to_uppercase())),
         _other => Err(format!(
             "to_upper() method expected a string, but got type '{}'.",
             args[0].type_of()
@@ -295,11 +677,7 @@ pub fn str_to_upper_fn(
 }
 
 // Native string to_lower method
-pub fn str_to_lower_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn str_to_lower_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!(
             "to_lower() expected 1 argument (self), but got {}",
@@ -308,7 +686,7 @@ pub fn str_to_lower_fn(
     }
 
     match &args[0].0.deref() {
-        Object::String(s) => Ok(Value::string(heap, s.to_lowercase())),
+        Object::String(s) => Ok(Value::string(ctx, s.to_lowercase())),
         _other => Err(format!(
             "to_lower() method expected a string, but got type '{}'.",
             args[0].type_of()
@@ -316,6 +694,131 @@ pub fn str_to_lower_fn(
     }
 }
 
+/// The constructor of an enum variant with fields: it takes one value per field, by position.
+pub fn enum_constructor(variant: Rc<EnumVariant>) -> NativeFunction {
+    Rc::new(move |ctx: &mut NativeContext, args: Vec<Value>| {
+        if args.len() != variant.fields.len() {
+            return Err(format!(
+                "{}() expected {} arguments ({}), but got {}",
+                variant.qualified_name(),
+                variant.fields.len(),
+                variant.fields.join(", "),
+                args.len()
+            ));
+        }
+        let value = EnumValue {
+            variant: Rc::clone(&variant),
+            fields: args,
+        };
+        Ok(Value::enum_value(ctx, value))
+    })
+}
+
+/// Checks the optional encoding argument of `encode()`/`decode()`. UTF-8 is the only one.
+fn expect_utf8(method: &str, encoding: Option<&Value>) -> Result<(), String> {
+    match encoding.map(|e| e.0.deref()) {
+        None => Ok(()),
+        Some(Object::String(name)) if name.eq_ignore_ascii_case("utf-8") => Ok(()),
+        Some(Object::String(name)) => Err(format!(
+            "{}() does not support encoding '{}'. Only 'utf-8' is supported.",
+            method, name
+        )),
+        Some(_) => Err(format!(
+            "{}() expected a string as the encoding, but got type '{}'.",
+            method,
+            encoding.unwrap().type_of()
+        )),
+    }
+}
+
+// Native string encode method: the UTF-8 bytes of the string
+pub fn str_encode_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(format!(
+            "encode() expected 1 or 2 arguments (self, encoding), but got {}",
+            args.len()
+        ));
+    }
+    let Object::String(s) = args[0].0.deref() else {
+        return Err(format!(
+            "encode() method expected a string as the receiver, but got type '{}'.",
+            args[0].type_of()
+        ));
+    };
+    expect_utf8("encode", args.get(1))?;
+    let bytes = s.as_bytes().to_vec();
+    Ok(Value::bytes(ctx, bytes))
+}
+
+/// The receiver of a bytes method.
+fn expect_bytes_receiver<'a>(method: &str, receiver: &'a Value) -> Result<&'a [u8], String> {
+    match receiver.0.deref() {
+        Object::Bytes(bytes) => Ok(bytes),
+        _ => Err(format!(
+            "{}() method expected bytes as the receiver, but got type '{}'.",
+            method,
+            receiver.type_of()
+        )),
+    }
+}
+
+// Native bytes hex method: two lowercase hex digits per byte
+pub fn bytes_hex_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err(format!(
+            "hex() expected 1 argument (self), but got {}",
+            args.len()
+        ));
+    }
+    let bytes = expect_bytes_receiver("hex", &args[0])?;
+    let hex = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    Ok(Value::string(ctx, hex))
+}
+
+// Native bytes decode method: the string the bytes encode as UTF-8
+pub fn bytes_decode_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.is_empty() || args.len() > 2 {
+        return Err(format!(
+            "decode() expected 1 or 2 arguments (self, encoding), but got {}",
+            args.len()
+        ));
+    }
+    let bytes = expect_bytes_receiver("decode", &args[0])?;
+    expect_utf8("decode", args.get(1))?;
+    let text = std::str::from_utf8(bytes)
+        .map_err(|e| format!("decode() found invalid UTF-8 at byte {}.", e.valid_up_to()))?
+        .to_string();
+    Ok(Value::string(ctx, text))
+}
+
+// Native from_hex function: the bytes a string of hex digit pairs spells out
+pub fn from_hex_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let Object::String(text) = args[0].0.deref() else {
+        return Err(format!(
+            "from_hex() expected a string, but got type '{}'.",
+            args[0].type_of()
+        ));
+    };
+    let digits: Vec<char> = text.chars().collect();
+    if let Some(c) = digits.iter().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("from_hex() found a non-hex character '{}'.", c));
+    }
+    if !digits.len().is_multiple_of(2) {
+        return Err(format!(
+            "from_hex() expected an even number of hex digits, but got {}.",
+            digits.len()
+        ));
+    }
+    let bytes = digits
+        .chunks(2)
+        .map(|pair| {
+            let pair: String = pair.iter().collect();
+            u8::from_str_radix(&pair, 16).unwrap()
+        })
+        .collect();
+    Ok(Value::bytes(ctx, bytes))
+}
+
 // Helper function to find a built-in method (no longer needed here, will be in Interpreter)
 // pub fn find_builtin_method(type_name: &str, method_name: &str) -> Option<NativeFunction> {
 //     BUILTIN_METHODS
@@ -325,19 +828,40 @@ pub fn str_to_lower_fn(
 //         .cloned()
 // }
 
-// Native print function
-pub fn print_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Result<Value, String> {
-    if args.is_empty() {
-        writeln!(io::stdout()).map_err(|e| e.to_string())?;
-    } else {
-        let output: Vec<String> = args.iter().map(|arg| format!("{}", arg)).collect();
-        writeln!(io::stdout(), "{}", output.join(" ")).map_err(|e| e.to_string())?;
+// Native print function: print(...values, sep: " ")
+pub fn print_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (values, separator) = match args.as_slice() {
+        [values] => (values, " ".to_string()),
+        [values, sep] => match sep.0.deref() {
+            Object::String(s) => (values, s.clone()),
+            _ => {
+                return Err(format!(
+                    "print() expected a string for sep, but got type '{}'.",
+                    sep.type_of()
+                ))
+            }
+        },
+        _ => {
+            return Err(format!(
+                "print() expected 2 arguments (values, sep), but got {}",
+                args.len()
+            ))
+        }
+    };
+    let values = match values.0.deref() {
+        Object::List(list) => list.clone(),
+        _ => return Err("print() expected its values as a list.".to_string()),
+    };
+    let mut output = Vec::with_capacity(values.len());
+    for value in &values {
+        output.push(display_string(ctx, value)?);
     }
-    Ok(Value::nil(heap))
+    writeln!(io::stdout(), "{}", output.join(&separator)).map_err(|e| e.to_string())?;
+    Ok(Value::nil(ctx))
 }
 
 // Native len method (polymorphic, but called as a method)
-pub fn len_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Result<Value, String> {
+pub fn len_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     // Expect `self` (the string/list/map) as the first argument, and no other arguments.
     if args.len() != 1 {
         return Err(format!(
@@ -346,10 +870,24 @@ pub fn len_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Resul
         ));
     }
 
+    if let Some(function) = args[0].metamethod("__len") {
+        let len = ctx.call(&function, vec![args[0].clone()])?;
+        return match len.0.deref() {
+            Object::Number(_) | Object::Integer(_) => Ok(len),
+            _ => Err(format!(
+                "__len() must return a number, but got type '{}'.",
+                len.type_of()
+            )),
+        };
+    }
+
     let len = match args[0].0.deref() {
         Object::String(s) => s.chars().count(),
-        Object::List(l) => l.len(),
-        Object::Map(m) => m.len(),
+        Object::Bytes(b) => b.len(),
+        Object::List(l) | Object::Tuple(l) => l.len(),
+        Object::Map(m) => m.entries.len(),
+        Object::Set(s) => s.len(),
+        Object::Channel(queue) => queue.len(),
         _other => {
             return Err(format!(
                 "len() method does not support type '{}'.",
@@ -358,15 +896,11 @@ pub fn len_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Resul
         }
     };
 
-    Ok(Value::number(heap, len as f64))
+    Ok(Value::integer(ctx, len as i64))
 }
 
 // Native string trim method
-pub fn str_trim_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn str_trim_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     // Expect `self` (the string) as the first argument, and no other arguments.
     if args.len() != 1 {
         return Err(format!(
@@ -376,7 +910,7 @@ pub fn str_trim_fn(
     }
 
     match &args[0].0.deref() {
-        Object::String(s) => Ok(Value::string(heap, s.trim().to_string())),
+        Object::String(s) => Ok(Value::string(ctx, s.trim().to_string())),
         _other => Err(format!(
             "trim() method expected a string, but got type '{}'.",
             args[0].type_of()
@@ -385,7 +919,7 @@ pub fn str_trim_fn(
 }
 
 // Native type function
-pub fn type_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Result<Value, String> {
+pub fn type_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!(
             "type() expected 1 argument, but got {}",
@@ -394,82 +928,221 @@ pub fn type_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Resu
     }
 
     let type_str = args[0].type_of();
-    Ok(Value::string(heap, type_str.to_string()))
+    Ok(Value::string(ctx, type_str.to_string()))
 }
 
 // Native str conversion function
-pub fn str_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Result<Value, String> {
+pub fn str_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!("str() expected 1 argument, but got {}", args.len()));
     }
 
-    let result_string = match args[0].0.deref() {
-        Object::String(s) => s.clone(), // If already a string, just clone its content (no extra quotes)
-        _ => format!("{}", args[0]),    // For other types, use Display trait
-    };
+    let result_string = display_string(ctx, &args[0])?;
+    Ok(Value::string(ctx, result_string))
+}
 
-    Ok(Value::string(heap, result_string))
+// The text `str()` and `print()` show for a value: what a map's `__str` returns, otherwise
+// the Display form (strings without quotes).
+fn display_string(ctx: &mut NativeContext, value: &Value) -> Result<String, String> {
+    if let Some(function) = value.metamethod("__str") {
+        let text = ctx.call(&function, vec![value.clone()])?;
+        return match text.0.deref() {
+            Object::String(s) => Ok(s.clone()),
+            _ => Err(format!(
+                "__str() must return a string, but got type '{}'.",
+                text.type_of()
+            )),
+        };
+    }
+    Ok(format!("{}", value))
 }
 
 // Native num conversion function
 /// Converts a value to a number.
 ///
-/// - Number values are returned as-is.
-/// - String values are parsed to f64; if parsing fails, returns `Value::Nil`.
-/// - Boolean `true` becomes 1.0, `false` becomes 0.0.
-/// - `Nil` becomes 0.0.
+/// - Number (and decimal) values are returned as-is.
+/// - String values are parsed as an integer if they look like one, otherwise as a
+///   float; if parsing fails, returns `Value::Nil`.
+/// - Boolean `true` becomes 1, `false` becomes 0.
+/// - `Nil` becomes 0.
 /// - For any other type, returns `Value::Nil`.
-pub fn num_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Result<Value, String> {
+pub fn num_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!("num() expected 1 argument, but got {}", args.len()));
     }
 
-    match &args[0] {
-        value_val if value_val.type_of() == "number" => {
-            // Check using type_of()
-            Ok(Value::number(
-                heap,
-                *value_val.0.deref().as_number().unwrap(),
-            ))
+    match args[0].0.deref() {
+        number if number.is_numeric() => Ok(args[0].clone()),
+        Object::String(s) => {
+            let s = s.trim();
+            if let Some(integer) = parse_integer(ctx, s) {
+                Ok(integer)
+            } else {
+                match s.parse::<f64>() {
+                    Ok(n) => Ok(Value::number(ctx, n)),
+                    Err(_) => Ok(Value::nil(ctx)), // If string parsing fails, return Nil
+                }
+            }
         }
-        value_val if value_val.type_of() == "string" => {
-            // Check using type_of()
-            match value_val
-                .0
-                .deref()
-                .as_string()
-                .unwrap()
-                .trim()
-                .parse::<f64>()
-            {
-                Ok(n) => Ok(Value::number(heap, n)),
-                Err(_) => Ok(Value::nil(heap)), // If string parsing fails, return Nil
+        Object::Boolean(b) => Ok(Value::integer(ctx, *b as i64)),
+        Object::Nil => Ok(Value::integer(ctx, 0)),
+        // For other types (e.g., List, Map, Function), return Nil as they cannot be coerced to a number.
+        _ => Ok(Value::nil(ctx)),
+    }
+}
+
+/// Parses a string of digits as an integer of whatever size it needs.
+fn parse_integer(heap: &Heap, s: &str) -> Option<Value> {
+    match s.parse::<i64>() {
+        Ok(i) => Some(Value::integer(heap, i)),
+        Err(_) => BigInt::parse(s).map(|b| Value::big_integer(heap, b)),
+    }
+}
+
+// Native int conversion function
+/// Converts a number or a numeric string to an integer, truncating toward zero.
+/// Returns `Value::Nil` for a string that is not a number.
+pub fn int_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let n = match args[0].0.deref() {
+        Object::Integer(_) | Object::BigInt(_) => return Ok(args[0].clone()),
+        Object::Decimal(d) => return Ok(Value::big_integer(ctx, d.trunc())),
+        Object::Number(n) => *n,
+        Object::String(s) => {
+            let s = s.trim();
+            if let Some(integer) = parse_integer(ctx, s) {
+                return Ok(integer);
+            }
+            match s.parse::<f64>() {
+                Ok(n) => n,
+                Err(_) => return Ok(Value::nil(ctx)),
             }
         }
-        value_val if value_val.type_of() == "boolean" => {
-            // Check using type_of()
-            Ok(Value::number(
-                heap,
-                if *value_val.0.deref().as_boolean().unwrap() {
-                    1.0
-                } else {
-                    0.0
-                },
+        _ => {
+            return Err(format!(
+                "int() expected a number or a string, but got type '{}'.",
+                args[0].type_of()
             ))
         }
-        value_val if value_val.type_of() == "nil" => {
-            // Check using type_of()
-            Ok(Value::number(heap, 0.0))
-        }
-        _other => {
-            // For other types (e.g., List, Map, Function), return Nil as they cannot be coerced to a number.
-            Ok(Value::nil(heap))
-        }
+    };
+    match BigInt::from_f64(n) {
+        Some(b) => Ok(Value::big_integer(ctx, b)),
+        None => Err(format!("int() cannot convert {} to an integer.", n)),
+    }
+}
+
+// Native float conversion function
+/// Converts a number or a numeric string to a float.
+/// Returns `Value::Nil` for a string that is not a number.
+pub fn float_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    match args[0].0.deref() {
+        Object::Decimal(d) => Ok(Value::number(ctx, d.to_f64())),
+        Object::String(s) => match s.trim().parse::<f64>() {
+            Ok(n) => Ok(Value::number(ctx, n)),
+            Err(_) => Ok(Value::nil(ctx)),
+        },
+        number => match number.as_number() {
+            Some(n) => Ok(Value::number(ctx, n)),
+            None => Err(format!(
+                "float() expected a number or a string, but got type '{}'.",
+                args[0].type_of()
+            )),
+        },
+    }
+}
+
+// Native decimal conversion function
+/// Converts a string, an integer or a float to an exact decimal. A float converts by its
+/// shortest representation, so `decimal(0.1)` is `0.1` rather than the binary value nearest
+/// to it. Returns `Value::Nil` for a string that is not a number.
+pub fn decimal_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let decimal = match args[0].0.deref() {
+        Object::Decimal(_) => return Ok(args[0].clone()),
+        Object::String(s) => Decimal::parse(s),
+        Object::Number(n) if n.is_finite() => Decimal::parse(&n.to_string()),
+        Object::Number(n) => return Err(format!("decimal() cannot convert {}.", n)),
+        other => match other.as_big_integer() {
+            Some(b) => Some(Decimal::from_integer(b)),
+            None => {
+                return Err(format!(
+                    "decimal() expected a number or a string, but got type '{}'.",
+                    args[0].type_of()
+                ))
+            }
+        },
+    };
+    match decimal {
+        Some(d) => Ok(Value::decimal(ctx, d)),
+        None => Ok(Value::nil(ctx)),
+    }
+}
+
+/// Reads an optional rounding-mode argument such as `"half_up"`.
+fn rounding_arg(
+    function: &str,
+    arg: Option<&Value>,
+    default: Rounding,
+) -> Result<Rounding, String> {
+    match arg.map(|arg| arg.0.deref()) {
+        None | Some(Object::Nil) => Ok(default),
+        Some(Object::String(name)) => Rounding::parse(name).ok_or_else(|| {
+            format!(
+                "{}() rounding must be one of {}, but got \"{}\".",
+                function,
+                Rounding::NAMES.join(", "),
+                name
+            )
+        }),
+        Some(_) => Err(format!(
+            "{}() rounding must be a string, but got type '{}'.",
+            function,
+            arg.unwrap().type_of()
+        )),
+    }
+}
+
+// Native set_decimal_context function: sets the precision (significant digits) and rounding
+// of decimal division
+pub fn set_decimal_context_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let precision = args[0].expect_integer("set_decimal_context() precision")?;
+    if !(1..=1000).contains(&precision) {
+        return Err(format!(
+            "set_decimal_context() precision must be between 1 and 1000, but got {}.",
+            precision
+        ));
+    }
+    let current = ctx.interpreter().decimal_context.get().rounding;
+    let rounding = rounding_arg("set_decimal_context", args.get(1), current)?;
+    ctx.interpreter().decimal_context.set(DecimalContext {
+        precision: precision as u32,
+        rounding,
+    });
+    Ok(Value::nil(ctx))
+}
+
+// Native decimal round method: rounds (or pads) to a number of places after the point
+pub fn decimal_round_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 && args.len() != 3 {
+        return Err(format!(
+            "round() expected 2 or 3 arguments (self, places, rounding), but got {}",
+            args.len()
+        ));
     }
+    let Object::Decimal(decimal) = args[0].0.deref() else {
+        return Err(format!(
+            "round() method expected a decimal as the receiver, but got type '{}'.",
+            args[0].type_of()
+        ));
+    };
+    let places = args[1].expect_integer("round() places")?;
+    let places = u32::try_from(places)
+        .map_err(|_| format!("round() places cannot be negative, but got {}.", places))?;
+    let default = ctx.interpreter().decimal_context.get().rounding;
+    let rounding = rounding_arg("round", args.get(2), default)?;
+    Ok(Value::decimal(ctx, decimal.round(places, rounding)))
 }
 
 // Native input function
-pub fn input_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Result<Value, String> {
+pub fn input_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() > 1 {
         return Err(format!(
             "input() expected 0 or 1 argument, but got {}",
@@ -479,7 +1152,7 @@ pub fn input_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Res
 
     let mut line = String::new();
 
-    if let Some(prompt_value) = args.get(0) {
+    if let Some(prompt_value) = args.first() {
         print!("{}", prompt_value);
         std::io::stdout().flush().map_err(|e| e.to_string())?;
     }
@@ -489,13 +1162,13 @@ pub fn input_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Res
         .map_err(|e| e.to_string())?;
 
     Ok(Value::string(
-        heap, // Use the provided heap
+        ctx, // Use the provided ctx
         line.trim_end_matches(&['\n', '\r'][..]).to_string(),
     ))
 }
 
 // Native bool conversion function
-pub fn bool_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Result<Value, String> {
+pub fn bool_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!(
             "bool() expected 1 argument, but got {}",
@@ -503,11 +1176,11 @@ pub fn bool_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Resu
         ));
     }
 
-    Ok(Value::boolean(heap, args[0].is_truthy()))
+    Ok(Value::boolean(ctx, args[0].is_truthy()))
 }
 
 // Native repr conversion function
-pub fn repr_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Result<Value, String> {
+pub fn repr_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!(
             "repr() expected 1 argument, but got {}",
@@ -516,11 +1189,54 @@ pub fn repr_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Resu
     }
 
     // Use the custom repr_string() for Python-like repr()
-    Ok(Value::string(heap, args[0].repr_string()))
+    Ok(Value::string(ctx, args[0].repr_string()))
+}
+
+// Native help function: prints a function's signature and its `##` doc comment
+pub fn help_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let entry = help_entry(ctx, &args[0])?;
+    writeln!(io::stdout(), "{}", entry).map_err(|e| e.to_string())?;
+    Ok(Value::nil(ctx))
+}
+
+fn help_entry(ctx: &NativeContext, value: &Value) -> Result<String, String> {
+    match value.0.deref() {
+        Object::Function(FunctionObjectInner::User {
+            name, params, doc, ..
+        }) => {
+            let specs: Vec<_> = params.iter().map(|p| p.spec()).collect();
+            let header = format_signature(name.as_deref().unwrap_or("<anonymous>"), &specs);
+            Ok(render_entry(&header, doc.as_deref()))
+        }
+        Object::Function(FunctionObjectInner::Native { signature, .. }) => Ok(match signature {
+            Some(signature) => format_signature(signature.name, &signature.params),
+            None => "<native function>(...)".to_string(),
+        }),
+        Object::BoundMethod(bound) => match &bound.function {
+            Some(function) => help_entry(ctx, function),
+            // A builtin method, shown without its `self` parameter, e.g. `list.push(element)`.
+            None => {
+                let type_name = bound.receiver.type_of();
+                let (_, signature) = ctx
+                    .interpreter()
+                    .builtin_method(type_name, &bound.method_name)
+                    .ok_or_else(|| format!("Unknown method '{}'.", bound.method_name))?;
+                Ok(format!(
+                    "{}.{}",
+                    type_name,
+                    format_signature(signature.name, &signature.params[1..])
+                ))
+            }
+        },
+        _ => Err(format!(
+            "help() expected a function, but got type '{}'.",
+            value.type_of()
+        )),
+    }
 }
 
 // Native keys method
-pub fn keys_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Result<Value, String> {
+pub fn keys_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!(
             "keys() expected 1 argument (self), but got {}.",
@@ -530,8 +1246,8 @@ pub fn keys_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Resu
 
     match args[0].0.deref() {
         Object::Map(m) => {
-            let keys: Vec<Value> = m.keys().map(|k| k.clone()).collect();
-            Ok(Value::list(heap, keys))
+            let keys: Vec<Value> = m.entries.keys().cloned().collect();
+            Ok(Value::list(ctx, keys))
         }
         _other => Err(format!(
             "keys() method expected a map, but got type '{}'.",
@@ -541,11 +1257,7 @@ pub fn keys_fn(heap: &mut Heap, _env: &EnvironmentRef, args: Vec<Value>) -> Resu
 }
 
 // Native values method
-pub fn values_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn values_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!(
             "values() expected 1 argument (self), but got {}.",
@@ -555,8 +1267,8 @@ pub fn values_fn(
 
     match args[0].0.deref() {
         Object::Map(m) => {
-            let values: Vec<Value> = m.values().cloned().collect();
-            Ok(Value::list(heap, values))
+            let values: Vec<Value> = m.entries.values().cloned().collect();
+            Ok(Value::list(ctx, values))
         }
         _other => Err(format!(
             "values() method expected a map, but got type '{}'.",
@@ -566,11 +1278,7 @@ pub fn values_fn(
 }
 
 // Native list push method
-pub fn list_push_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    mut args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn list_push_fn(ctx: &mut NativeContext, mut args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 2 {
         return Err(format!(
             "push() expected 2 arguments (self, element), but got {}",
@@ -584,11 +1292,12 @@ pub fn list_push_fn(
     // 然后再获取 args[0] 的可变引用
     let list_value = &mut args[0];
 
+    list_value.ensure_mutable()?;
     match list_value.0.deref_mut() {
         // Directly deref_mut the GcRef to get Object
         Object::List(list) => {
             list.push(element_to_push); // Push to the mutable Vec directly
-            Ok(Value::nil(heap))
+            Ok(Value::nil(ctx))
         }
         _other => Err(format!(
             "push() method expected a list as the receiver, but got type '{}'.",
@@ -598,11 +1307,7 @@ pub fn list_push_fn(
 }
 
 // Native list pop method
-pub fn list_pop_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    mut args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn list_pop_fn(ctx: &mut NativeContext, mut args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!(
             "pop() expected 1 argument (self), but got {}",
@@ -612,12 +1317,13 @@ pub fn list_pop_fn(
 
     let list_value = &mut args[0];
 
+    list_value.ensure_mutable()?;
     match list_value.0.deref_mut() {
         Object::List(list) => {
             if let Some(popped_element) = list.pop() {
                 Ok(popped_element)
             } else {
-                Ok(Value::nil(heap)) // Return nil if list is empty
+                Ok(Value::nil(ctx)) // Return nil if list is empty
             }
         }
         _other => Err(format!(
@@ -628,11 +1334,7 @@ pub fn list_pop_fn(
 }
 
 // Native list remove method
-pub fn list_remove_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    mut args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn list_remove_fn(_ctx: &mut NativeContext, mut args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 2 {
         return Err(format!(
             "remove() expected 2 arguments (self, index), but got {}",
@@ -641,26 +1343,18 @@ pub fn list_remove_fn(
     }
 
     // 克隆 index_value 的值，解除对 args 的借用，避免冲突
-    let index_val_copy = args[1].clone(); 
+    let index_val_copy = args[1].clone();
 
     // 现在可以安全地获取 list_value 的可变引用了
     let list_value = &mut args[0];
 
+    list_value.ensure_mutable()?;
     match list_value.0.deref_mut() {
         Object::List(list) => {
-            if let Some(idx_float) = index_val_copy.0.deref().as_number() { // 使用克隆的值
-                let index = (*idx_float as i64) as usize; // 更安全的转换
-                if index < list.len() {
-                    let removed_element = list.remove(index);
-                    Ok(removed_element)
-                } else {
-                    Err(format!("List index out of bounds: {}", index))
-                }
-            } else {
-                Err(format!(
-                    "remove() method expected a number for index, but got type '{}'.",
-                    index_val_copy.type_of() // 使用克隆的值
-                ))
+            let index = index_val_copy.expect_integer("List index")?;
+            match usize::try_from(index) {
+                Ok(i) if i < list.len() => Ok(list.remove(i)),
+                _ => Err(format!("List index out of bounds: {}", index)),
             }
         }
         _other => Err(format!(
@@ -672,8 +1366,7 @@ pub fn list_remove_fn(
 
 // Native list insert method
 pub fn list_insert_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
+    ctx: &mut NativeContext,
     mut args: Vec<Value>, // Mark args as mutable to allow taking &mut args[0]
 ) -> Result<Value, String> {
     if args.len() != 3 {
@@ -687,26 +1380,25 @@ pub fn list_insert_fn(
     let element_to_insert = args[2].clone();
 
     // Extract index value and convert to usize, releasing borrow on args[1]
-    let index_usize = match args[1].0.deref().as_number() {
-        Some(idx_float) => (*idx_float as i64) as usize, // Robust conversion
-        _ => {
-            return Err(format!(
-                "insert() method expected a number for index, but got type '{}'.",
-                args[1].type_of()
-            ));
-        }
-    };
+    let index = args[1].expect_integer("List index")?;
+    let index_usize = usize::try_from(index).unwrap_or(usize::MAX);
 
     // Now safely get mutable reference to args[0]
     let list_value = &mut args[0];
 
+    list_value.ensure_mutable()?;
     match list_value.0.deref_mut() {
         Object::List(list) => {
-            if index_usize <= list.len() { // index can be list.len() for appending
+            if index_usize <= list.len() {
+                // index can be list.len() for appending
                 list.insert(index_usize, element_to_insert);
-                Ok(Value::nil(heap))
+                Ok(Value::nil(ctx))
             } else {
-                Err(format!("List insert index out of bounds: {} (list has {} elements).", index_usize, list.len()))
+                Err(format!(
+                    "List insert index out of bounds: {} (list has {} elements).",
+                    index_usize,
+                    list.len()
+                ))
             }
         }
         _other => Err(format!(
@@ -717,11 +1409,7 @@ pub fn list_insert_fn(
 }
 
 // Native function to create a map from a list of key-value pairs
-pub fn make_map_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn make_map_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 1 {
         return Err(format!(
             "make_map() expected 1 argument (a list of key-value pairs), but got {}",
@@ -739,7 +1427,7 @@ pub fn make_map_fn(
         }
     };
 
-    let mut new_map = HashMap::new();
+    let mut new_map = OrderedMap::new();
     for pair_value in input_list.iter() {
         let pair_list = match pair_value.0.deref() {
             Object::List(l) => l,
@@ -761,60 +1449,15 @@ pub fn make_map_fn(
         let key = pair_list[0].clone();
         let value = pair_list[1].clone();
 
-        match key.type_of() {
-            "string" | "number" | "boolean" => {
-                new_map.insert(key, value);
-            }
-            _ => {
-                return Err(format!(
-                    "make_map() map keys must be primitive types (String, Number, Boolean), but got '{}'.",
-                    key.type_of()
-                ));
-            }
-        }
-    }
-
-    Ok(Value::map(heap, new_map))
-}
-
-// Native GC collection function
-pub fn gc_collect_fn(
-    heap: &mut Heap,
-    env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
-    if !args.is_empty() {
-        return Err(format!(
-            "gc_collect() expected 0 arguments, but got {}",
-            args.len()
-        ));
-    }
-
-    let mut roots = Vec::new();
-    let mut current_env = Some(Rc::clone(env));
-    while let Some(env_ref) = current_env {
-        let env_borrow = env_ref.borrow();
-        for value in env_borrow.values.values() {
-            roots.push(value.clone());
-        }
-        current_env = env_borrow.parent.as_ref().map(Rc::clone);
+        key.expect_key()?;
+        new_map.insert(key, value);
     }
 
-    // Also add the arguments passed to this function as roots.
-    // Although for gc_collect() it's empty, it's good practice for other potential GC-aware functions.
-    roots.extend_from_slice(&args);
-
-    let collected_count = heap.collect(&roots);
-
-    Ok(Value::number(heap, collected_count as f64))
+    Ok(Value::map(ctx, new_map))
 }
 
 // Native list join method
-pub fn list_join_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn list_join_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 2 {
         return Err(format!(
             "join() expected 2 arguments (self, separator), but got {}",
@@ -838,7 +1481,7 @@ pub fn list_join_fn(
             };
 
             let parts: Vec<String> = list.iter().map(|item| format!("{}", item)).collect();
-            Ok(Value::string(heap, parts.join(&separator)))
+            Ok(Value::string(ctx, parts.join(&separator)))
         }
         _other => Err(format!(
             "join() method expected a list as the receiver, but got type '{}'.",
@@ -848,11 +1491,7 @@ pub fn list_join_fn(
 }
 
 // Native string ends_with method
-pub fn str_ends_with_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn str_ends_with_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 2 {
         return Err(format!(
             "ends_with() expected 2 arguments (self, suffix), but got {}",
@@ -880,15 +1519,11 @@ pub fn str_ends_with_fn(
         }
     };
 
-    Ok(Value::boolean(heap, self_string.ends_with(suffix)))
+    Ok(Value::boolean(ctx, self_string.ends_with(suffix)))
 }
 
 // Native string substring method
-pub fn str_substring_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn str_substring_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() < 2 || args.len() > 3 {
         return Err(format!(
             "substring() expected 2 or 3 arguments (self, start, end), but got {}",
@@ -906,26 +1541,14 @@ pub fn str_substring_fn(
         }
     };
 
-    let start_index = match &args[1].0.deref().as_number() {
-        Some(n) => (**n as i64) as usize,
-        _ => {
-            return Err(format!(
-                "substring() method expected a number for start index, but got type '{}'.",
-                args[1].type_of()
-            ));
-        }
-    };
+    // Negative indices map past the end, so they fail the range checks below
+    let start_index =
+        usize::try_from(args[1].expect_integer("substring() start index")?).unwrap_or(usize::MAX);
 
     let end_index = if args.len() == 3 {
-        match &args[2].0.deref().as_number() {
-            Some(n) => Some((**n as i64) as usize),
-            _ => {
-                return Err(format!(
-                    "substring() method expected a number for end index, but got type '{}'.",
-                    args[2].type_of()
-                ));
-            }
-        }
+        Some(
+            usize::try_from(args[2].expect_integer("substring() end index")?).unwrap_or(usize::MAX),
+        )
     } else {
         None
     };
@@ -934,28 +1557,24 @@ pub fn str_substring_fn(
     let len = chars.len();
 
     if start_index > len {
-        return Ok(Value::string(heap, "".to_string()));
+        return Ok(Value::string(ctx, "".to_string()));
     }
 
     let actual_end_index = end_index.unwrap_or(len);
 
     if start_index >= actual_end_index {
-        return Ok(Value::string(heap, "".to_string()));
+        return Ok(Value::string(ctx, "".to_string()));
     }
 
     let sub: String = chars[start_index..std::cmp::min(actual_end_index, len)]
         .iter()
         .collect();
 
-    Ok(Value::string(heap, sub))
+    Ok(Value::string(ctx, sub))
 }
 
 // Native map has_key method
-pub fn map_has_key_fn(
-    heap: &mut Heap,
-    _env: &EnvironmentRef,
-    args: Vec<Value>,
-) -> Result<Value, String> {
+pub fn map_has_key_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
     if args.len() != 2 {
         return Err(format!(
             "has_key() expected 2 arguments (self, key), but got {}",
@@ -968,18 +1587,8 @@ pub fn map_has_key_fn(
 
     match map_value.0.deref() {
         Object::Map(map) => {
-            // Map keys must be primitive types (String, Number, Boolean)
-            match key_to_check.type_of() {
-                "string" | "number" | "boolean" => {
-                    Ok(Value::boolean(heap, map.contains_key(key_to_check)))
-                },
-                _ => {
-                    return Err(format!(
-                        "Map keys must be primitive types (String, Number, Boolean) for has_key(). Got: '{}'.",
-                        key_to_check.type_of()
-                    ))
-                }
-            }
+            key_to_check.expect_key()?;
+            Ok(Value::boolean(ctx, map.entries.contains_key(key_to_check)))
         }
         _other => Err(format!(
             "has_key() method expected a map as the receiver, but got type '{}'.",
@@ -987,3 +1596,868 @@ pub fn map_has_key_fn(
         )),
     }
 }
+
+/// Converts an iterable value into an iterator object.
+///
+/// Lists iterate over their elements and iterators are returned as-is. Maps implementing
+/// the iterator protocol (an `__iter` function returning an iterator, or a `next` function
+/// returning `nil` when done) are iterated through it; other maps iterate over their keys.
+/// Tuples and sets iterate over a snapshot of their elements, like map keys, and bytes over
+/// their values as integers.
+pub fn make_iterator(heap: &Heap, value: &Value) -> Result<Value, String> {
+    let inner = match value.0.deref() {
+        Object::Iterator(_) => return Ok(value.clone()),
+        Object::List(_) => IteratorInner::List {
+            list: value.clone(),
+            index: 0,
+        },
+        Object::Map(map) => {
+            if value
+                .lookup_property_str("__iter")
+                .is_some_and(|f| f.is_callable())
+            {
+                IteratorInner::Iterable {
+                    object: value.clone(),
+                }
+            } else if value
+                .lookup_property_str("next")
+                .is_some_and(|f| f.is_callable())
+            {
+                IteratorInner::Protocol {
+                    object: value.clone(),
+                }
+            } else {
+                IteratorInner::Keys {
+                    keys: map.entries.keys().cloned().collect(),
+                    index: 0,
+                }
+            }
+        }
+        Object::Tuple(items) => IteratorInner::Keys {
+            keys: items.clone(),
+            index: 0,
+        },
+        Object::Set(set) => IteratorInner::Keys {
+            keys: set.keys().cloned().collect(),
+            index: 0,
+        },
+        Object::Bytes(bytes) => IteratorInner::Keys {
+            keys: bytes
+                .clone()
+                .into_iter()
+                .map(|byte| Value::integer(heap, byte as i64))
+                .collect(),
+            index: 0,
+        },
+        _other => {
+            return Err(format!(
+                "Cannot iterate over type '{}'. Expected a list, tuple, map, set, bytes or iterator.",
+                value.type_of()
+            ));
+        }
+    };
+    Ok(Value::iterator(heap, inner))
+}
+
+// Native iter function
+pub fn iter_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err(format!(
+            "iter() expected 1 argument, but got {}",
+            args.len()
+        ));
+    }
+
+    make_iterator(ctx, &args[0])
+}
+
+// Native range function: range(end), range(start, end) or range(start, end, step)
+pub fn range_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.is_empty() || args.len() > 3 {
+        return Err(format!(
+            "range() expected 1 to 3 arguments (start, end, step), but got {}",
+            args.len()
+        ));
+    }
+
+    let mut numbers = Vec::new();
+    for arg in args.iter() {
+        match arg.0.deref().as_number() {
+            Some(n) => numbers.push(n),
+            None => {
+                return Err(format!(
+                    "range() expected number arguments, but got type '{}'.",
+                    arg.type_of()
+                ));
+            }
+        }
+    }
+
+    // All-integer arguments give an integer range; any float makes it a float range.
+    let integers: Option<Vec<i64>> = args.iter().map(|a| a.0.deref().as_integer()).collect();
+    if let Some(integers) = integers {
+        let (start, end, step) = match integers[..] {
+            [end] => (0, end, 1),
+            [start, end] => (start, end, 1),
+            [start, end, step] => (start, end, step),
+            _ => unreachable!(),
+        };
+        if step == 0 {
+            return Err("range() step cannot be zero.".to_string());
+        }
+        return Ok(Value::iterator(
+            ctx,
+            IteratorInner::IntegerRange {
+                next: start,
+                end,
+                step,
+            },
+        ));
+    }
+
+    let (start, end, step) = match numbers[..] {
+        [end] => (0.0, end, 1.0),
+        [start, end] => (start, end, 1.0),
+        [start, end, step] => (start, end, step),
+        _ => unreachable!(),
+    };
+    if step == 0.0 {
+        return Err("range() step cannot be zero.".to_string());
+    }
+
+    Ok(Value::iterator(
+        ctx,
+        IteratorInner::Range {
+            next: start,
+            end,
+            step,
+        },
+    ))
+}
+
+// Native lines function: a lazy iterator over the lines of standard input
+pub fn lines_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if !args.is_empty() {
+        return Err(format!(
+            "lines() expected 0 arguments, but got {}",
+            args.len()
+        ));
+    }
+
+    Ok(Value::iterator(ctx, IteratorInner::Lines))
+}
+
+// Checks that the receiver of an iterator method is an iterator.
+fn expect_iterator_receiver(method: &str, receiver: &Value) -> Result<Value, String> {
+    match receiver.0.deref() {
+        Object::Iterator(_) => Ok(receiver.clone()),
+        _other => Err(format!(
+            "{}() method expected an iterator as the receiver, but got type '{}'.",
+            method,
+            receiver.type_of()
+        )),
+    }
+}
+
+// Checks that a callback argument of a method is callable.
+fn expect_callable(method: &str, func: &Value) -> Result<Value, String> {
+    if func.is_callable() {
+        Ok(func.clone())
+    } else {
+        Err(format!(
+            "{}() method expected a function, but got type '{}'.",
+            method,
+            func.type_of()
+        ))
+    }
+}
+
+// Native iterator map method
+pub fn iter_map_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err(format!(
+            "map() expected 2 arguments (self, function), but got {}",
+            args.len()
+        ));
+    }
+
+    let source = expect_iterator_receiver("map", &args[0])?;
+    let func = expect_callable("map", &args[1])?;
+    Ok(Value::iterator(ctx, IteratorInner::Map { source, func }))
+}
+
+// Native iterator filter method
+pub fn iter_filter_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err(format!(
+            "filter() expected 2 arguments (self, predicate), but got {}",
+            args.len()
+        ));
+    }
+
+    let source = expect_iterator_receiver("filter", &args[0])?;
+    let func = expect_callable("filter", &args[1])?;
+    Ok(Value::iterator(ctx, IteratorInner::Filter { source, func }))
+}
+
+// Native iterator take method
+pub fn iter_take_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err(format!(
+            "take() expected 2 arguments (self, count), but got {}",
+            args.len()
+        ));
+    }
+
+    let source = expect_iterator_receiver("take", &args[0])?;
+    let remaining = match args[1].expect_integer("take() count")? {
+        n if n >= 0 => n as usize,
+        n => return Err(format!("take() count cannot be negative, but got {}.", n)),
+    };
+    Ok(Value::iterator(
+        ctx,
+        IteratorInner::Take { source, remaining },
+    ))
+}
+
+// Native iterator zip method: yields [left, right] pairs until either side is exhausted
+pub fn iter_zip_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err(format!(
+            "zip() expected 2 arguments (self, other), but got {}",
+            args.len()
+        ));
+    }
+
+    let left = expect_iterator_receiver("zip", &args[0])?;
+    let right = make_iterator(ctx, &args[1])?;
+    Ok(Value::iterator(ctx, IteratorInner::Zip { left, right }))
+}
+
+// Native iterator enumerate method: yields [index, item] pairs
+pub fn iter_enumerate_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err(format!(
+            "enumerate() expected 1 argument (self), but got {}",
+            args.len()
+        ));
+    }
+
+    let source = expect_iterator_receiver("enumerate", &args[0])?;
+    Ok(Value::iterator(
+        ctx,
+        IteratorInner::Enumerate { source, index: 0 },
+    ))
+}
+
+// Native iterator chain method
+pub fn iter_chain_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err(format!(
+            "chain() expected 2 arguments (self, other), but got {}",
+            args.len()
+        ));
+    }
+
+    let first = expect_iterator_receiver("chain", &args[0])?;
+    let second = make_iterator(ctx, &args[1])?;
+    Ok(Value::iterator(
+        ctx,
+        IteratorInner::Chain {
+            first,
+            second,
+            on_second: false,
+        },
+    ))
+}
+
+// Native channel function
+pub fn channel_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if !args.is_empty() {
+        return Err(format!(
+            "channel() expected 0 arguments, but got {}",
+            args.len()
+        ));
+    }
+
+    Ok(Value::channel(ctx))
+}
+
+// Native channel send method
+pub fn channel_send_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 2 {
+        return Err(format!(
+            "send() expected 2 arguments (self, value), but got {}",
+            args.len()
+        ));
+    }
+
+    // Channels are unbounded, so sending never blocks; the scheduler wakes a receiver.
+    let mut handle = args[0].0;
+    match handle.deref_mut() {
+        Object::Channel(queue) => queue.push_back(args[1].clone()),
+        _ => {
+            return Err(format!(
+                "send() method expected a channel as receiver, but got type '{}'.",
+                args[0].type_of()
+            ))
+        }
+    }
+    Ok(Value::nil(ctx))
+}
+
+// Native channel recv method: blocks the current task until a value arrives
+pub fn channel_recv_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err(format!(
+            "recv() expected 1 argument (self), but got {}",
+            args.len()
+        ));
+    }
+
+    ctx.interpreter()
+        .channel_recv(&args[0])
+        .map_err(|e| e.message().to_string())
+}
+
+// Native iterator next method: the next item, or nil once the iterator is exhausted
+pub fn iter_next_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err(format!(
+            "next() expected 1 argument (self), but got {}",
+            args.len()
+        ));
+    }
+
+    let iterator = expect_iterator_receiver("next", &args[0])?;
+    match ctx.interpreter().iterator_next(&iterator) {
+        Ok(Some(item)) => Ok(item),
+        Ok(None) => Ok(Value::nil(ctx)),
+        Err(e) => Err(e.message().to_string()),
+    }
+}
+
+// Native iterator collect method: drains the iterator into a list
+pub fn iter_collect_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err(format!(
+            "collect() expected 1 argument (self), but got {}",
+            args.len()
+        ));
+    }
+
+    let iterator = expect_iterator_receiver("collect", &args[0])?;
+    ctx.interpreter()
+        .iterator_collect(&iterator)
+        .map_err(|e| e.message().to_string())
+}
+
+// Native gc_collect function: runs a collection and returns the number of objects freed
+pub fn gc_collect_fn(ctx: &mut NativeContext, _args: Vec<Value>) -> Result<Value, String> {
+    let freed = ctx.interpreter().collect_garbage();
+    Ok(Value::integer(ctx, freed as i64))
+}
+
+// Native freeze function: makes a list or map (and everything nested in it) immutable.
+// Returns the value itself; other values are returned unchanged.
+pub fn freeze_fn(_ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    args[0].freeze();
+    Ok(args[0].clone())
+}
+
+// Native is_frozen function
+pub fn is_frozen_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let frozen = args[0].is_frozen();
+    Ok(Value::boolean(ctx, frozen))
+}
+
+// Native set_proto function: makes dot access on `object` fall back to `proto` (a map, or
+// nil to remove the prototype). Returns `object`.
+pub fn set_proto_fn(_ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (object, proto) = (&args[0], &args[1]);
+    if object.type_of() != "map" {
+        return Err(format!(
+            "set_proto() expected a map as the object, but got type '{}'.",
+            object.type_of()
+        ));
+    }
+    let proto = match proto.0.deref() {
+        Object::Nil => None,
+        Object::Map(_) => Some(proto.clone()),
+        _ => {
+            return Err(format!(
+                "set_proto() expected a map or nil as the prototype, but got type '{}'.",
+                proto.type_of()
+            ))
+        }
+    };
+
+    // Prototype chains must stay finite, so `object` cannot appear in `proto`'s chain.
+    let mut ancestor = proto.clone();
+    while let Some(current) = ancestor {
+        if current.0 == object.0 {
+            return Err("set_proto() would create a prototype cycle.".to_string());
+        }
+        ancestor = match current.0.deref() {
+            Object::Map(map) => map.proto.clone(),
+            _ => None,
+        };
+    }
+
+    object.ensure_mutable()?;
+    let mut handle = object.0;
+    if let Object::Map(map) = handle.deref_mut() {
+        map.proto = proto;
+    }
+    Ok(object.clone())
+}
+
+// Native get_proto function: the prototype of a map, or nil
+pub fn get_proto_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    match args[0].0.deref() {
+        Object::Map(map) => match &map.proto {
+            Some(proto) => Ok(proto.clone()),
+            None => Ok(Value::nil(ctx)),
+        },
+        _ => Err(format!(
+            "get_proto() expected a map, but got type '{}'.",
+            args[0].type_of()
+        )),
+    }
+}
+
+// Native spawn function: receives [function, args] as bound by its signature
+pub fn spawn_fn(ctx: &mut NativeContext, mut args: Vec<Value>) -> Result<Value, String> {
+    let spawn_args = match args.pop().as_ref().map(|list| list.0.deref()) {
+        Some(Object::List(items)) => items.clone(),
+        _ => Vec::new(),
+    };
+    let function = args.pop().ok_or("spawn() expected a function.")?;
+    if !function.is_callable() {
+        return Err(format!(
+            "spawn() expected a function as its first argument, but got {}.",
+            function.type_of()
+        ));
+    }
+    ctx.interpreter().spawn_task(function, spawn_args);
+    Ok(Value::nil(ctx))
+}
+
+// Native yield_now function: lets the other ready tasks run
+pub fn yield_now_fn(ctx: &mut NativeContext, _args: Vec<Value>) -> Result<Value, String> {
+    ctx.interpreter()
+        .yield_now()
+        .map_err(|e| e.message().to_string())?;
+    Ok(Value::nil(ctx))
+}
+
+// Native sleep function: blocks the current task for a number of milliseconds
+pub fn sleep_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let ms = match args.first().and_then(|ms| ms.0.deref().as_number()) {
+        Some(ms) if ms >= 0.0 => ms,
+        _ => {
+            return Err(format!(
+                "sleep() expected a non-negative number of milliseconds, but got {}.",
+                args.first()
+                    .map_or("nothing".to_string(), |v| v.repr_string())
+            ))
+        }
+    };
+    ctx.interpreter()
+        .sleep(ms)
+        .map_err(|e| e.message().to_string())?;
+    Ok(Value::nil(ctx))
+}
+
+// --- HIGHER-ORDER LIST AND MAP METHODS ---
+// These call back into script functions through the native context. They work on a
+// snapshot of the receiver, so a callback that mutates the collection does not affect
+// the iteration, and they always return a new collection. The snapshot and the results
+// built so far are rooted, since a callback may run a collection.
+
+// Checks the receiver and callback of a higher-order list method, returning the items.
+fn list_callback_args(
+    ctx: &mut NativeContext,
+    method: &str,
+    params: &str,
+    args: &[Value],
+    expected: usize,
+) -> Result<(Vec<Value>, Value), String> {
+    if args.len() != expected {
+        return Err(format!(
+            "{}() expected {} arguments ({}), but got {}",
+            method,
+            expected,
+            params,
+            args.len()
+        ));
+    }
+    let items = match args[0].0.deref() {
+        Object::List(items) => items.clone(),
+        _ => {
+            return Err(format!(
+                "{}() method expected a list as the receiver, but got type '{}'.",
+                method,
+                args[0].type_of()
+            ))
+        }
+    };
+    let func = expect_callable(method, &args[1])?;
+    for item in &items {
+        ctx.root(item.clone());
+    }
+    Ok((items, func))
+}
+
+// Native list map method: [f(item) for each item]
+pub fn list_map_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (items, func) = list_callback_args(ctx, "map", "self, function", &args, 2)?;
+    let mut mapped = Vec::with_capacity(items.len());
+    for item in items {
+        let value = ctx.call(&func, vec![item])?;
+        ctx.root(value.clone());
+        mapped.push(value);
+    }
+    Ok(Value::list(ctx, mapped))
+}
+
+// Native list filter method: the items for which the predicate is truthy
+pub fn list_filter_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (items, func) = list_callback_args(ctx, "filter", "self, predicate", &args, 2)?;
+    let mut kept = Vec::new();
+    for item in items {
+        if ctx.call(&func, vec![item.clone()])?.is_truthy() {
+            kept.push(item);
+        }
+    }
+    Ok(Value::list(ctx, kept))
+}
+
+// Native list reduce method: folds the items with f(accumulator, item). Without an initial
+// value the first item is used, so reducing an empty list then is an error.
+pub fn list_reduce_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let expected = if args.len() == 3 { 3 } else { 2 };
+    let (items, func) =
+        list_callback_args(ctx, "reduce", "self, function, initial?", &args, expected)?;
+    let mut items = items.into_iter();
+    let mut accumulator = match args.get(2) {
+        Some(initial) => initial.clone(),
+        None => items
+            .next()
+            .ok_or("reduce() of an empty list with no initial value.")?,
+    };
+    for item in items {
+        accumulator = ctx.call(&func, vec![accumulator, item])?;
+        ctx.root(accumulator.clone());
+    }
+    Ok(accumulator)
+}
+
+// Native list sort_by method: a new list sorted (stably) by key(item). Keys must be all
+// numbers or all strings.
+pub fn list_sort_by_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (items, func) = list_callback_args(ctx, "sort_by", "self, key", &args, 2)?;
+    let mut keyed = Vec::with_capacity(items.len());
+    for item in items {
+        let key = ctx.call(&func, vec![item.clone()])?;
+        ctx.root(key.clone());
+        keyed.push((key, item));
+    }
+    check_sort_keys("sort_by", keyed.iter().map(|(key, _)| key))?;
+    keyed.sort_by(|(a, _), (b, _)| compare_sort_keys(a, b));
+    let sorted = keyed.into_iter().map(|(_, item)| item).collect();
+    Ok(Value::list(ctx, sorted))
+}
+
+// Sort keys must be all numbers or all strings, so that they have a total order.
+fn check_sort_keys<'a>(
+    method: &str,
+    mut keys: impl Iterator<Item = &'a Value>,
+) -> Result<(), String> {
+    let Some(first) = keys.next() else {
+        return Ok(());
+    };
+    let kind = first.type_of();
+    if kind != "number" && kind != "string" {
+        return Err(format!(
+            "{}() keys must be numbers or strings, but got type '{}'.",
+            method, kind
+        ));
+    }
+    if let Some(other) = keys.find(|key| key.type_of() != kind) {
+        return Err(format!(
+            "{}() keys must all have the same type, but got '{}' and '{}'.",
+            method,
+            kind,
+            other.type_of()
+        ));
+    }
+    Ok(())
+}
+
+// Orders two keys accepted by `check_sort_keys`.
+fn compare_sort_keys(a: &Value, b: &Value) -> std::cmp::Ordering {
+    match (a.0.deref(), b.0.deref()) {
+        (Object::String(a), Object::String(b)) => a.cmp(b),
+        (a, b) => compare_numbers(a, b).unwrap_or(std::cmp::Ordering::Equal),
+    }
+}
+
+// Native map sort_keys method: reorders the map's entries by key, in place, and returns it
+pub fn map_sort_keys_fn(_ctx: &mut NativeContext, mut args: Vec<Value>) -> Result<Value, String> {
+    if args.len() != 1 {
+        return Err(format!(
+            "sort_keys() expected 1 argument (self), but got {}",
+            args.len()
+        ));
+    }
+    let map_value = args[0].clone();
+    map_value.ensure_mutable()?;
+    let Object::Map(map) = args[0].0.deref_mut() else {
+        return Err(format!(
+            "sort_keys() method expected a map as the receiver, but got type '{}'.",
+            map_value.type_of()
+        ));
+    };
+    check_sort_keys("sort_keys", map.entries.keys())?;
+    map.entries.sort_keys_by(compare_sort_keys);
+    Ok(map_value)
+}
+
+// Native set function: `set()` is empty; `set(values)` collects a list, map, set or iterator
+pub fn set_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let Some(values) = args.first() else {
+        return Ok(Value::set(ctx, OrderedMap::new()));
+    };
+    let iterator = make_iterator(ctx, values).map_err(|_| {
+        format!(
+            "set() expected a list, map, set or iterator, but got type '{}'.",
+            values.type_of()
+        )
+    })?;
+    let collected = ctx
+        .interpreter()
+        .iterator_collect(&iterator)
+        .map_err(|e| e.message().to_string())?;
+    let mut set = OrderedMap::new();
+    if let Object::List(items) = collected.0.deref() {
+        for item in items {
+            item.expect_element()?;
+            set.insert(item.clone(), ());
+        }
+    }
+    Ok(Value::set(ctx, set))
+}
+
+/// Checks the argument count of a set method and returns its receiver's elements.
+fn set_receiver<'a>(
+    method: &str,
+    params: &str,
+    args: &'a [Value],
+    expected: usize,
+) -> Result<&'a SetElements, String> {
+    if args.len() != expected {
+        return Err(format!(
+            "{}() expected {} arguments ({}), but got {}",
+            method,
+            expected,
+            params,
+            args.len()
+        ));
+    }
+    match args[0].0.deref() {
+        Object::Set(set) => Ok(set),
+        _ => Err(format!(
+            "{}() method expected a set as the receiver, but got type '{}'.",
+            method,
+            args[0].type_of()
+        )),
+    }
+}
+
+/// The receiver and the other set of a binary set method such as `union()`.
+fn set_operands<'a>(
+    method: &str,
+    args: &'a [Value],
+) -> Result<(&'a SetElements, &'a SetElements), String> {
+    let set = set_receiver(method, "self, other", args, 2)?;
+    match args[1].0.deref() {
+        Object::Set(other) => Ok((set, other)),
+        _ => Err(format!(
+            "{}() expected a set, but got type '{}'.",
+            method,
+            args[1].type_of()
+        )),
+    }
+}
+
+// Native set add method: adds an element; adding one already present changes nothing
+pub fn set_add_fn(ctx: &mut NativeContext, mut args: Vec<Value>) -> Result<Value, String> {
+    set_receiver("add", "self, element", &args, 2)?;
+    let element = args[1].clone();
+    element.expect_element()?;
+    args[0].ensure_mutable()?;
+    if let Object::Set(set) = args[0].0.deref_mut() {
+        set.insert(element, ());
+    }
+    Ok(Value::nil(ctx))
+}
+
+// Native set remove method: whether the element was present
+pub fn set_remove_fn(ctx: &mut NativeContext, mut args: Vec<Value>) -> Result<Value, String> {
+    set_receiver("remove", "self, element", &args, 2)?;
+    let element = args[1].clone();
+    element.expect_element()?;
+    args[0].ensure_mutable()?;
+    let removed = match args[0].0.deref_mut() {
+        Object::Set(set) => set.remove(&element).is_some(),
+        _ => false,
+    };
+    Ok(Value::boolean(ctx, removed))
+}
+
+// Native set contains method
+pub fn set_contains_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let set = set_receiver("contains", "self, element", &args, 2)?;
+    args[1].expect_element()?;
+    let contains = set.contains_key(&args[1]);
+    Ok(Value::boolean(ctx, contains))
+}
+
+// Native set union method: a new set with the elements of both, the receiver's first
+pub fn set_union_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (set, other) = set_operands("union", &args)?;
+    let union = set
+        .keys()
+        .chain(other.keys())
+        .map(|v| (v.clone(), ()))
+        .collect();
+    Ok(Value::set(ctx, union))
+}
+
+// Native set intersection method: a new set with the receiver's elements that are in both
+pub fn set_intersection_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (set, other) = set_operands("intersection", &args)?;
+    let intersection = set
+        .keys()
+        .filter(|v| other.contains_key(v))
+        .map(|v| (v.clone(), ()))
+        .collect();
+    Ok(Value::set(ctx, intersection))
+}
+
+// Native set difference method: a new set with the receiver's elements that are not in other
+pub fn set_difference_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (set, other) = set_operands("difference", &args)?;
+    let difference = set
+        .keys()
+        .filter(|v| !other.contains_key(v))
+        .map(|v| (v.clone(), ()))
+        .collect();
+    Ok(Value::set(ctx, difference))
+}
+
+// Native set is_subset method: whether every element of the receiver is in other
+pub fn set_is_subset_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (set, other) = set_operands("is_subset", &args)?;
+    let is_subset = set.keys().all(|v| other.contains_key(v));
+    Ok(Value::boolean(ctx, is_subset))
+}
+
+// Native list any method: whether the predicate is truthy for some item (stops at the first)
+pub fn list_any_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (items, func) = list_callback_args(ctx, "any", "self, predicate", &args, 2)?;
+    for item in items {
+        if ctx.call(&func, vec![item])?.is_truthy() {
+            return Ok(Value::boolean(ctx, true));
+        }
+    }
+    Ok(Value::boolean(ctx, false))
+}
+
+// Native list all method: whether the predicate is truthy for every item (stops at the first
+// that is not)
+pub fn list_all_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (items, func) = list_callback_args(ctx, "all", "self, predicate", &args, 2)?;
+    for item in items {
+        if !ctx.call(&func, vec![item])?.is_truthy() {
+            return Ok(Value::boolean(ctx, false));
+        }
+    }
+    Ok(Value::boolean(ctx, true))
+}
+
+// Native list find method: the first item for which the predicate is truthy, or nil
+pub fn list_find_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (items, func) = list_callback_args(ctx, "find", "self, predicate", &args, 2)?;
+    for item in items {
+        if ctx.call(&func, vec![item.clone()])?.is_truthy() {
+            return Ok(item);
+        }
+    }
+    Ok(Value::nil(ctx))
+}
+
+// Checks the receiver and callback of a higher-order map method, returning the entries.
+fn map_callback_args(
+    ctx: &mut NativeContext,
+    method: &str,
+    params: &str,
+    args: &[Value],
+) -> Result<(Vec<(Value, Value)>, Value), String> {
+    if args.len() != 2 {
+        return Err(format!(
+            "{}() expected 2 arguments ({}), but got {}",
+            method,
+            params,
+            args.len()
+        ));
+    }
+    let entries: Vec<(Value, Value)> = match args[0].0.deref() {
+        Object::Map(m) => m
+            .entries
+            .iter()
+            .map(|(k, v)| (k.clone(), v.clone()))
+            .collect(),
+        _ => {
+            return Err(format!(
+                "{}() method expected a map as the receiver, but got type '{}'.",
+                method,
+                args[0].type_of()
+            ))
+        }
+    };
+    let func = expect_callable(method, &args[1])?;
+    for (key, value) in &entries {
+        ctx.root(key.clone());
+        ctx.root(value.clone());
+    }
+    Ok((entries, func))
+}
+
+// Native map map_values method: a map with the same keys and f(value) as values
+pub fn map_map_values_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (entries, func) = map_callback_args(ctx, "map_values", "self, function", &args)?;
+    let mut mapped = OrderedMap::with_capacity(entries.len());
+    for (key, value) in entries {
+        let value = ctx.call(&func, vec![value])?;
+        ctx.root(value.clone());
+        mapped.insert(key, value);
+    }
+    Ok(Value::map(ctx, mapped))
+}
+
+// Native map filter method: the entries for which predicate(key, value) is truthy
+pub fn map_filter_fn(ctx: &mut NativeContext, args: Vec<Value>) -> Result<Value, String> {
+    let (entries, func) = map_callback_args(ctx, "filter", "self, predicate", &args)?;
+    let mut kept = OrderedMap::new();
+    for (key, value) in entries {
+        if ctx
+            .call(&func, vec![key.clone(), value.clone()])?
+            .is_truthy()
+        {
+            kept.insert(key, value);
+        }
+    }
+    Ok(Value::map(ctx, kept))
+}
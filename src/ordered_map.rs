@@ -0,0 +1,144 @@
+//! `OrderedMap`, the entries of an EasyScript map: a hash map that remembers insertion order.
+//!
+//! Entries are kept in a vector in the order their keys were first inserted, and a hash index
+//! maps each key to its position. Lookups stay constant-time, while iteration, printing and
+//! `keys()` are deterministic. Assigning to an existing key keeps its position.
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, Clone)]
+pub struct OrderedMap<K, V> {
+    entries: Vec<(K, V)>,
+    index: HashMap<K, usize>,
+}
+
+impl<K: Hash + Eq + Clone, V> OrderedMap<K, V> {
+    pub fn new() -> Self {
+        OrderedMap {
+            entries: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        OrderedMap {
+            entries: Vec::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Like `HashMap::get`, the key may be any borrowed form of `K` that hashes alike.
+    pub fn get<Q: Hash + Eq + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.index.get(key).map(|&i| &self.entries[i].1)
+    }
+
+    pub fn contains_key(&self, key: &K) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Sets `key` to `value`, returning the previous value. A new key goes last.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        match self.index.get(&key) {
+            Some(&i) => Some(std::mem::replace(&mut self.entries[i].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Removes `key`, returning its value. The entries after it move up to keep the order.
+    pub fn remove(&mut self, key: &K) -> Option<V> {
+        let position = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(position);
+        for (key, _) in &self.entries[position..] {
+            *self.index.get_mut(key).unwrap() -= 1;
+        }
+        Some(value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.entries.iter().map(|(k, _)| k)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.entries.iter().map(|(_, v)| v)
+    }
+
+    /// Reorders the entries by key. The sort is stable.
+    pub fn sort_keys_by(&mut self, mut compare: impl FnMut(&K, &K) -> std::cmp::Ordering) {
+        self.entries.sort_by(|(a, _), (b, _)| compare(a, b));
+        for (i, (key, _)) in self.entries.iter().enumerate() {
+            *self.index.get_mut(key).unwrap() = i;
+        }
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> Default for OrderedMap<K, V> {
+    fn default() -> Self {
+        OrderedMap::new()
+    }
+}
+
+impl<K: Hash + Eq + Clone, V> FromIterator<(K, V)> for OrderedMap<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Self {
+        let mut map = OrderedMap::new();
+        for (key, value) in iter {
+            map.insert(key, value);
+        }
+        map
+    }
+}
+
+impl<'a, K: Hash + Eq + Clone, V> IntoIterator for &'a OrderedMap<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = std::iter::Map<std::slice::Iter<'a, (K, V)>, fn(&'a (K, V)) -> (&'a K, &'a V)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter().map(|(k, v)| (k, v))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insertion_order() {
+        let mut map = OrderedMap::new();
+        for key in ["b", "c", "a"] {
+            map.insert(key, key.len());
+        }
+        assert_eq!(map.insert("c", 10), Some(1));
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["b", "c", "a"]);
+        assert_eq!(map.get(&"c"), Some(&10));
+
+        map.sort_keys_by(|a, b| a.cmp(b));
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(map.get(&"b"), Some(&1));
+        assert!(!map.contains_key(&"d"));
+
+        assert_eq!(map.remove(&"a"), Some(1));
+        assert_eq!(map.remove(&"a"), None);
+        assert_eq!(map.keys().copied().collect::<Vec<_>>(), ["b", "c"]);
+        assert_eq!(map.get(&"c"), Some(&10));
+    }
+}
@@ -1,23 +1,75 @@
 use crate::ast::{
-    AccessType, BinaryOperator, Block, Expression, LValue, LiteralValue, UnaryOperator,
+    AccessType, Argument, BinaryOperator, Block, EnumVariantDef, Expression, LValue, LiteralValue,
+    MatchArm, Param, Pattern, SliceBounds, TypeAnnotation, UnaryOperator,
 };
-use crate::error::EasyScriptError;
+use crate::error::{EasyScriptError, SourceLocation};
 use crate::token::{Literal, Token};
+use std::collections::HashMap;
 
 pub struct Parser {
     tokens: Vec<Token>,
+    // The source location of each token, when the lexer provided them.
+    locations: Vec<Option<SourceLocation>>,
     current: usize,
+    // One entry per enclosing function body: whether a `yield` has been seen in it.
+    generator_scopes: Vec<bool>,
+    // `##` doc comments, keyed by the index of the token that follows them.
+    docs: HashMap<usize, String>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Parser { tokens, current: 0 }
+        Parser::with_optional_locations(tokens.into_iter().map(|token| (token, None)))
+    }
+
+    /// A parser that records source locations in the AST, for `--check` to report.
+    pub fn with_locations(tokens: Vec<(Token, SourceLocation)>) -> Self {
+        Parser::with_optional_locations(
+            tokens
+                .into_iter()
+                .map(|(token, location)| (token, Some(location))),
+        )
+    }
+
+    fn with_optional_locations(
+        tokens: impl Iterator<Item = (Token, Option<SourceLocation>)>,
+    ) -> Self {
+        // Doc comments are not part of the grammar: pull them out of the token stream,
+        // merging consecutive lines, and remember which token each one precedes.
+        let mut docs = HashMap::new();
+        let mut pending: Option<String> = None;
+        let mut code_tokens = Vec::new();
+        let mut locations = Vec::new();
+        for (token, location) in tokens {
+            match token {
+                Token::DocComment(line) => {
+                    pending = Some(match pending {
+                        Some(doc) => format!("{}\n{}", doc, line),
+                        None => line,
+                    });
+                }
+                token => {
+                    if let Some(doc) = pending.take() {
+                        docs.insert(code_tokens.len(), doc);
+                    }
+                    code_tokens.push(token);
+                    locations.push(location);
+                }
+            }
+        }
+        Parser {
+            tokens: code_tokens,
+            locations,
+            current: 0,
+            generator_scopes: Vec::new(),
+            docs,
+        }
     }
 
     pub fn parse(mut self) -> Result<Block, EasyScriptError> {
         let mut expressions = Vec::new();
         while !self.is_at_end() {
-            let expr = self.expression()?;
+            let expr = self.block_item()?;
             let mut terminated_by_semicolon = false;
             // Allow multiple semicolons or no semicolon after the last expression
             while self.match_tokens(&[Token::Semicolon]) {
@@ -32,6 +84,7 @@ impl Parser {
 
     // This function assumes the "fun" keyword has NOT been consumed by its caller.
     fn function_definition(&mut self) -> Result<Expression, EasyScriptError> {
+        let doc = self.docs.remove(&self.current);
         self.consume(&Token::KeywordFun, "Expect 'fun' keyword.")?; // Consume 'fun'
 
         // Optional: Function Name (for named functions, though EasyScript is anonymous functions for now)
@@ -40,12 +93,68 @@ impl Parser {
 
         self.consume(&Token::LeftParen, "Expect '(' after 'fun' for parameters.")?;
 
-        let mut params = Vec::new();
+        let mut params: Vec<Param> = Vec::new();
         if !self.check(&Token::RightParen) {
             loop {
-                // 参数必须是标识符
-                let param_name = self.consume_identifier("Expect parameter name.")?;
-                params.push(param_name);
+                // 参数必须是标识符，可带默认值 (`y = 10`) 或作为剩余参数 (`...rest`)
+                let rest = self.match_tokens(&[Token::Ellipsis]);
+                let name = self.consume_identifier("Expect parameter name.")?;
+                let annotation = if self.match_tokens(&[Token::Colon]) {
+                    if rest {
+                        return Err(EasyScriptError::ParserError {
+                            message: format!(
+                                "Rest parameter '...{}' cannot have a type annotation; it is always a list.",
+                                name
+                            ),
+                            location: None,
+                        });
+                    }
+                    Some(self.type_annotation()?)
+                } else {
+                    None
+                };
+                let default = if !rest && self.match_tokens(&[Token::Equal]) {
+                    Some(self.expression()?)
+                } else {
+                    None
+                };
+
+                if params.iter().any(|p| p.name == name) {
+                    return Err(EasyScriptError::ParserError {
+                        message: format!("Duplicate parameter name '{}'.", name),
+                        location: None,
+                    });
+                }
+                // Parameters after the rest parameter can only be passed by keyword.
+                let after_rest = params.iter().any(|p| p.rest);
+                if rest && after_rest {
+                    return Err(EasyScriptError::ParserError {
+                        message: format!(
+                            "Only one rest parameter is allowed, found '...{}'.",
+                            name
+                        ),
+                        location: None,
+                    });
+                }
+                if !rest
+                    && !after_rest
+                    && default.is_none()
+                    && params.iter().any(|p| p.default.is_some())
+                {
+                    return Err(EasyScriptError::ParserError {
+                        message: format!(
+                            "Parameter '{}' without a default cannot follow a parameter with a default.",
+                            name
+                        ),
+                        location: None,
+                    });
+                }
+                params.push(Param {
+                    name,
+                    default,
+                    rest,
+                    annotation,
+                });
 
                 if !self.match_tokens(&[Token::Comma]) {
                     break;
@@ -53,12 +162,27 @@ impl Parser {
             }
         }
         self.consume(&Token::RightParen, "Expect ')' after parameters.")?;
+        let return_type = if self.match_tokens(&[Token::Arrow]) {
+            Some(self.type_annotation()?)
+        } else {
+            None
+        };
 
         self.consume(&Token::LeftBrace, "Expect '{' before function body.")?;
-        let body = self.block()?; // Parse the function body as a block
-
-        // Create an Expression::FunctionDef { params, body }
-        Ok(Expression::FunctionDef { params, body })
+        self.generator_scopes.push(false);
+        let body = self.block(); // Parse the function body as a block
+                                 // A function whose body contains `yield` is a generator function.
+        let is_generator = self.generator_scopes.pop().unwrap_or(false);
+
+        // Create an Expression::FunctionDef { name, params, body, is_generator }
+        Ok(Expression::FunctionDef {
+            name: None,
+            params,
+            body: body?,
+            is_generator,
+            doc,
+            return_type,
+        })
     }
 
     // This function assumes the "for" keyword has NOT been consumed by its caller.
@@ -67,16 +191,8 @@ impl Parser {
 
         // Check if it's a 'for-in' loop by looking for an Identifier followed by 'in'
         // Need to be careful with `self.peek()` and `self.tokens.get(self.current + 1)`
-        let is_for_in = if let Some(current_token) = self.tokens.get(self.current) {
-            if let Token::Identifier(_) = current_token {
-                if let Some(next_token) = self.tokens.get(self.current + 1) {
-                    matches!(next_token, Token::KeywordIn)
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
+        let is_for_in = if let Some(Token::Identifier(_)) = self.tokens.get(self.current) {
+            matches!(self.tokens.get(self.current + 1), Some(Token::KeywordIn))
         } else {
             false
         };
@@ -112,20 +228,96 @@ impl Parser {
         Ok(Expression::ForCondition { condition, body })
     }
 
-    // This function assumes the "let" keyword has NOT been consumed by its caller.
+    // This function assumes the "let" or "const" keyword has NOT been consumed by its caller.
     fn let_declaration(&mut self) -> Result<Expression, EasyScriptError> {
-        self.consume(&Token::KeywordLet, "Expect 'let' keyword.")?; // Consume 'let'
+        let doc = self.docs.remove(&self.current);
+        let constant = self.check(&Token::KeywordConst);
+        let keyword = if constant { "const" } else { "let" };
+        self.advance(); // Consume 'let' or 'const'
 
-        let identifier = self.consume_identifier("Expect variable name after 'let'.")?;
+        if self.match_tokens(&[Token::LeftParen]) {
+            return self.let_destructure(constant, keyword);
+        }
+
+        let identifier =
+            self.consume_identifier(&format!("Expect variable name after '{}'.", keyword))?;
+        let annotation = if self.match_tokens(&[Token::Colon]) {
+            Some(self.type_annotation()?)
+        } else {
+            None
+        };
 
         self.consume(
             &Token::Equal,
-            "Expect '=' after variable name in let declaration.",
+            &format!("Expect '=' after variable name in {} declaration.", keyword),
         )?;
 
-        let value = Box::new(self.expression()?); // Parse the initial value expression
+        let mut value = Box::new(self.expression()?); // Parse the initial value expression
+
+        // `let add = fun(...)` names the function after its variable, for error messages,
+        // and hands it the binding's doc comment for help().
+        if let Expression::FunctionDef {
+            name,
+            doc: function_doc,
+            ..
+        } = value.as_mut()
+        {
+            name.get_or_insert_with(|| identifier.clone());
+            if function_doc.is_none() {
+                function_doc.clone_from(&doc);
+            }
+        }
 
-        Ok(Expression::Let { identifier, value })
+        Ok(Expression::Let {
+            identifier,
+            value,
+            constant,
+            doc,
+            annotation,
+        })
+    }
+
+    // TypeAnnotation ::= Identifier | "nil"; which names are types is up to `--check`
+    fn type_annotation(&mut self) -> Result<TypeAnnotation, EasyScriptError> {
+        let location = self.location();
+        let name = if self.match_tokens(&[Token::KeywordNil]) {
+            "nil".to_string()
+        } else {
+            self.consume_identifier("Expect type name.")?
+        };
+        Ok(TypeAnnotation { name, location })
+    }
+
+    // Parse the rest of `let (a, b, ...) = value`, after the opening parenthesis
+    fn let_destructure(
+        &mut self,
+        constant: bool,
+        keyword: &str,
+    ) -> Result<Expression, EasyScriptError> {
+        let mut identifiers = Vec::new();
+        while !self.check(&Token::RightParen) {
+            identifiers.push(self.consume_identifier(&format!(
+                "Expect variable name in '{}' destructuring.",
+                keyword
+            ))?);
+            if !self.match_tokens(&[Token::Comma]) {
+                break;
+            }
+        }
+        self.consume(&Token::RightParen, "Expect ')' after destructured names.")?;
+        self.consume(
+            &Token::Equal,
+            &format!(
+                "Expect '=' after destructured names in {} declaration.",
+                keyword
+            ),
+        )?;
+        let value = Box::new(self.expression()?);
+        Ok(Expression::LetDestructure {
+            identifiers,
+            value,
+            constant,
+        })
     }
 
     // Expression ::= IfExpression | ForExpression | FunctionDefinition | LetDeclaration | AssignmentExpression
@@ -139,14 +331,202 @@ impl Parser {
         if self.check(&Token::KeywordFor) {
             return self.for_expression();
         }
-        if self.check(&Token::KeywordLet) {
-            // Handle let declarations
+        if self.check(&Token::KeywordLet) || self.check(&Token::KeywordConst) {
+            // Handle let and const declarations
             return self.let_declaration();
         }
+        if self.check(&Token::KeywordYield) {
+            return self.yield_expression();
+        }
+        if self.check(&Token::KeywordEnum) {
+            return self.enum_declaration();
+        }
+        if self.check(&Token::KeywordMatch) {
+            return self.match_expression();
+        }
+        if self.check(&Token::KeywordDefer) {
+            return Err(EasyScriptError::ParserError {
+                message: "'defer' can only be used directly inside a block.".to_string(),
+                location: None,
+            });
+        }
 
         self.assignment()
     }
 
+    // BlockItem ::= "defer" Expression | Expression
+    fn block_item(&mut self) -> Result<Expression, EasyScriptError> {
+        if self.match_tokens(&[Token::KeywordDefer]) {
+            return Ok(Expression::Defer(Box::new(self.expression()?)));
+        }
+        self.expression()
+    }
+
+    // EnumDeclaration ::= "enum" Identifier "{" Variant { "," Variant } [ "," ] "}"
+    fn enum_declaration(&mut self) -> Result<Expression, EasyScriptError> {
+        self.consume(&Token::KeywordEnum, "Expect 'enum' keyword.")?;
+        let name = self.consume_identifier("Expect enum name after 'enum'.")?;
+        self.consume(&Token::LeftBrace, "Expect '{' after enum name.")?;
+
+        let mut variants: Vec<EnumVariantDef> = Vec::new();
+        while !self.check(&Token::RightBrace) {
+            let variant = self.consume_identifier("Expect variant name in enum.")?;
+            if variants.iter().any(|v| v.name == variant) {
+                return Err(EasyScriptError::ParserError {
+                    message: format!("Duplicate variant '{}' in enum '{}'.", variant, name),
+                    location: None,
+                });
+            }
+            let mut fields = None;
+            if self.match_tokens(&[Token::LeftParen]) {
+                let mut names: Vec<String> = Vec::new();
+                while !self.check(&Token::RightParen) {
+                    let field = self.consume_identifier("Expect field name in enum variant.")?;
+                    if names.contains(&field) {
+                        return Err(EasyScriptError::ParserError {
+                            message: format!(
+                                "Duplicate field '{}' in variant '{}'.",
+                                field, variant
+                            ),
+                            location: None,
+                        });
+                    }
+                    names.push(field);
+                    if !self.match_tokens(&[Token::Comma]) {
+                        break;
+                    }
+                }
+                self.consume(&Token::RightParen, "Expect ')' after variant fields.")?;
+                if names.is_empty() {
+                    return Err(EasyScriptError::ParserError {
+                        message: format!(
+                            "Variant '{}' has no fields; declare it without parentheses.",
+                            variant
+                        ),
+                        location: None,
+                    });
+                }
+                fields = Some(names);
+            }
+            variants.push(EnumVariantDef {
+                name: variant,
+                fields,
+            });
+            if !self.match_tokens(&[Token::Comma]) {
+                break;
+            }
+        }
+        self.consume(&Token::RightBrace, "Expect '}' after enum variants.")?;
+        Ok(Expression::EnumDef { name, variants })
+    }
+
+    // MatchExpression ::= "match" Expression "{" MatchArm { "," MatchArm } [ "," ] "}"
+    fn match_expression(&mut self) -> Result<Expression, EasyScriptError> {
+        self.consume(&Token::KeywordMatch, "Expect 'match' keyword.")?;
+        let subject = Box::new(self.expression()?);
+        self.consume(&Token::LeftBrace, "Expect '{' after match value.")?;
+
+        let mut arms = Vec::new();
+        while !self.check(&Token::RightBrace) && !self.is_at_end() {
+            let pattern = self.pattern()?;
+            let guard = if self.match_tokens(&[Token::KeywordIf]) {
+                Some(self.expression()?)
+            } else {
+                None
+            };
+            self.consume(&Token::FatArrow, "Expect '=>' after match pattern.")?;
+            let body = self.expression()?;
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+            });
+            // A block body may end an arm without a comma, as in `x => { ... }`.
+            if !self.match_tokens(&[Token::Comma])
+                && !self.check(&Token::RightBrace)
+                && self.previous() != &Token::RightBrace
+            {
+                return Err(EasyScriptError::ParserError {
+                    message: "Expect ',' between match arms.".to_string(),
+                    location: None,
+                });
+            }
+        }
+        self.consume(&Token::RightBrace, "Expect '}' after match arms.")?;
+        Ok(Expression::Match { subject, arms })
+    }
+
+    // Pattern ::= "_" | Identifier | Literal | Identifier "." Identifier [ "(" Pattern { "," Pattern } ")" ]
+    fn pattern(&mut self) -> Result<Pattern, EasyScriptError> {
+        if let Token::Identifier(name) = self.peek() {
+            let name = name.clone();
+            self.advance();
+            if !self.match_tokens(&[Token::Dot]) {
+                return Ok(if name == "_" {
+                    Pattern::Wildcard
+                } else {
+                    Pattern::Binding(name)
+                });
+            }
+            let variant = self.consume_identifier("Expect variant name after '.' in pattern.")?;
+            let mut fields = None;
+            if self.match_tokens(&[Token::LeftParen]) {
+                let mut patterns = Vec::new();
+                while !self.check(&Token::RightParen) {
+                    patterns.push(self.pattern()?);
+                    if !self.match_tokens(&[Token::Comma]) {
+                        break;
+                    }
+                }
+                self.consume(&Token::RightParen, "Expect ')' after variant patterns.")?;
+                fields = Some(patterns);
+            }
+            return Ok(Pattern::Variant {
+                enum_name: name,
+                variant,
+                fields,
+            });
+        }
+        if matches!(
+            self.peek(),
+            Token::Literal(_) | Token::KeywordTrue | Token::KeywordFalse | Token::KeywordNil
+        ) {
+            if let Expression::Literal(literal) = self.primary()? {
+                return Ok(Pattern::Literal(literal));
+            }
+        }
+        Err(EasyScriptError::ParserError {
+            message: format!("Expected a pattern, found {:?}", self.peek()),
+            location: None,
+        })
+    }
+
+    // This function assumes the "yield" keyword has NOT been consumed by its caller.
+    fn yield_expression(&mut self) -> Result<Expression, EasyScriptError> {
+        self.consume(&Token::KeywordYield, "Expect 'yield' keyword.")?; // Consume 'yield'
+
+        match self.generator_scopes.last_mut() {
+            Some(is_generator) => *is_generator = true,
+            None => {
+                return Err(EasyScriptError::ParserError {
+                    message: "'yield' can only be used inside a function body.".to_string(),
+                    location: None,
+                })
+            }
+        }
+
+        // A bare `yield` (followed by a terminator) yields nil.
+        if self.check(&Token::Semicolon)
+            || self.check(&Token::RightBrace)
+            || self.check(&Token::RightParen)
+            || self.check(&Token::RightBracket)
+            || self.check(&Token::Comma)
+        {
+            return Ok(Expression::Yield(None));
+        }
+        Ok(Expression::Yield(Some(Box::new(self.expression()?))))
+    }
+
     // This function assumes the "if" keyword has NOT been consumed by its caller.
     fn if_expression(&mut self) -> Result<Expression, EasyScriptError> {
         self.consume(&Token::KeywordIf, "Expect 'if' keyword.")?; // Consume 'if'
@@ -176,50 +556,97 @@ impl Parser {
         })
     }
 
-    // AssignmentExpression ::= LValue "=" Assignment | TermExpression
+    // AssignmentExpression ::= LValue ( "=" | CompoundOperator ) Assignment | TermExpression
     fn assignment(&mut self) -> Result<Expression, EasyScriptError> {
         let expr = self.logical_group()?;
 
         if self.match_tokens(&[Token::Equal]) {
+            let location = self.previous_location();
             // The right-hand side of an assignment can be any Expression.
             let value = self.expression()?; // FIX: Allow any expression on the RHS
+            return Ok(Expression::Assignment {
+                lvalue: Self::to_lvalue(expr)?,
+                value: Box::new(value),
+                location,
+            });
+        }
 
-            // Convert the left-hand expression to an LValue
-            return match expr {
-                Expression::Identifier(name) => Ok(Expression::Assignment {
-                    lvalue: LValue::Identifier(name),
-                    value: Box::new(value),
-                }),
-                Expression::Accessor { target, access } => match access {
-                    AccessType::Index(key) => Ok(Expression::Assignment {
-                        lvalue: LValue::IndexAccess { target, key },
-                        value: Box::new(value),
-                    }),
-                    AccessType::Dot(property_name) => Ok(Expression::Assignment {
-                        lvalue: LValue::DotAccess {
-                            target,
-                            property_name,
-                        },
-                        value: Box::new(value),
-                    }),
-                },
-                _ => Err(EasyScriptError::ParserError {
-                    message: format!("Invalid assignment target: {:?}", expr),
-                    location: None,
-                }), // <-- 修改这里
+        if self.match_tokens(&[
+            Token::PlusEqual,
+            Token::MinusEqual,
+            Token::StarEqual,
+            Token::SlashEqual,
+            Token::SlashSlashEqual,
+            Token::PercentEqual,
+            Token::ShiftLeftEqual,
+            Token::ShiftRightEqual,
+            Token::AmpersandEqual,
+            Token::PipeEqual,
+            Token::CaretEqual,
+        ]) {
+            let location = self.previous_location();
+            let op = match self.previous() {
+                Token::PlusEqual => BinaryOperator::Add,
+                Token::MinusEqual => BinaryOperator::Sub,
+                Token::StarEqual => BinaryOperator::Mul,
+                Token::SlashEqual => BinaryOperator::Div,
+                Token::SlashSlashEqual => BinaryOperator::FloorDiv,
+                Token::PercentEqual => BinaryOperator::Mod,
+                Token::ShiftLeftEqual => BinaryOperator::ShL,
+                Token::ShiftRightEqual => BinaryOperator::ShR,
+                Token::AmpersandEqual => BinaryOperator::BitAnd,
+                Token::PipeEqual => BinaryOperator::BitOr,
+                Token::CaretEqual => BinaryOperator::BitXor,
+                _ => unreachable!(),
             };
+            let value = self.expression()?;
+            return Ok(Expression::CompoundAssignment {
+                lvalue: Self::to_lvalue(expr)?,
+                op,
+                value: Box::new(value),
+                location,
+            });
         }
         Ok(expr)
     }
 
-    // LogicalGroupExpression ::= EqualityComparisonGroupExpression { ( "||" | "&&" ) EqualityComparisonGroupExpression }
+    // Converts the left-hand side of an assignment to an LValue.
+    fn to_lvalue(expr: Expression) -> Result<LValue, EasyScriptError> {
+        match expr {
+            Expression::Identifier(name) => Ok(LValue::Identifier(name)),
+            Expression::Accessor {
+                target,
+                access,
+                optional: false,
+            } => match access {
+                AccessType::Index(key) => Ok(LValue::IndexAccess { target, key }),
+                AccessType::Slice(bounds) => Ok(LValue::SliceAccess { target, bounds }),
+                AccessType::Dot(property_name) => Ok(LValue::DotAccess {
+                    target,
+                    property_name,
+                }),
+            },
+            Expression::Accessor { optional: true, .. } => Err(EasyScriptError::ParserError {
+                message: "Cannot assign to an optional chain ('?.' or '?[').".to_string(),
+                location: None,
+            }),
+            _ => Err(EasyScriptError::ParserError {
+                message: format!("Invalid assignment target: {:?}", expr),
+                location: None,
+            }),
+        }
+    }
+
+    // LogicalGroupExpression ::= EqualityComparisonGroupExpression { ( "||" | "&&" | "??" ) EqualityComparisonGroupExpression }
     fn logical_group(&mut self) -> Result<Expression, EasyScriptError> {
         let mut expr = self.equality_comparison_group()?; // 调用更高优先级的 equality_comparison_group()
 
-        while self.match_tokens(&[Token::Or, Token::And]) {
+        while self.match_tokens(&[Token::Or, Token::And, Token::QuestionQuestion]) {
+            let location = self.previous_location();
             let op = match self.previous() {
                 Token::Or => BinaryOperator::Or,
                 Token::And => BinaryOperator::And,
+                Token::QuestionQuestion => BinaryOperator::NilCoalesce,
                 _ => unreachable!(), // 应该在 match_tokens 中被处理
             };
             let right = self.equality_comparison_group()?; // 再次调用 equality_comparison_group() 来处理右侧操作数
@@ -227,6 +654,7 @@ impl Parser {
                 left: Box::new(expr),
                 op,
                 right: Box::new(right),
+                location,
             };
         }
         Ok(expr)
@@ -243,7 +671,10 @@ impl Parser {
             Token::LessEqual,
             Token::Greater,
             Token::GreaterEqual,
+            Token::KeywordIn,
+            Token::KeywordIs,
         ]) {
+            let location = self.previous_location();
             let op = match self.previous() {
                 Token::EqualEqual => BinaryOperator::Eq,
                 Token::BangEqual => BinaryOperator::Neq,
@@ -251,6 +682,8 @@ impl Parser {
                 Token::LessEqual => BinaryOperator::Lte,
                 Token::Greater => BinaryOperator::Gt,
                 Token::GreaterEqual => BinaryOperator::Gte,
+                Token::KeywordIn => BinaryOperator::In,
+                Token::KeywordIs => BinaryOperator::Is,
                 _ => unreachable!(), // 应该在 match_tokens 中被处理
             };
             let right = self.bitwise_group()?; // 再次调用 bitwise_group() 来处理右侧操作数
@@ -258,6 +691,7 @@ impl Parser {
                 left: Box::new(expr),
                 op,
                 right: Box::new(right),
+                location,
             };
         }
         Ok(expr)
@@ -274,6 +708,7 @@ impl Parser {
             Token::ShiftLeft,
             Token::ShiftRight,
         ]) {
+            let location = self.previous_location();
             let op = match self.previous() {
                 Token::Pipe => BinaryOperator::BitOr,
                 Token::Caret => BinaryOperator::BitXor,
@@ -287,6 +722,7 @@ impl Parser {
                 left: Box::new(expr),
                 op,
                 right: Box::new(right),
+                location,
             };
         }
         Ok(expr)
@@ -297,6 +733,7 @@ impl Parser {
         let mut expr = self.multiplicative()?; // 调用更高优先级的 multiplicative()
 
         while self.match_tokens(&[Token::Plus, Token::Minus]) {
+            let location = self.previous_location();
             let op = match self.previous() {
                 Token::Plus => BinaryOperator::Add,
                 Token::Minus => BinaryOperator::Sub,
@@ -307,19 +744,22 @@ impl Parser {
                 left: Box::new(expr),
                 op,
                 right: Box::new(right),
+                location,
             };
         }
         Ok(expr)
     }
 
-    // MultiplicativeExpression ::= UnaryExpression { ( "*" | "/" | "%" ) UnaryExpression }
+    // MultiplicativeExpression ::= UnaryExpression { ( "*" | "/" | "//" | "%" ) UnaryExpression }
     fn multiplicative(&mut self) -> Result<Expression, EasyScriptError> {
         let mut expr = self.unary()?; // 调用更高优先级的 unary()
 
-        while self.match_tokens(&[Token::Star, Token::Slash, Token::Percent]) {
+        while self.match_tokens(&[Token::Star, Token::Slash, Token::SlashSlash, Token::Percent]) {
+            let location = self.previous_location();
             let op = match self.previous() {
                 Token::Star => BinaryOperator::Mul,
                 Token::Slash => BinaryOperator::Div,
+                Token::SlashSlash => BinaryOperator::FloorDiv,
                 Token::Percent => BinaryOperator::Mod,
                 _ => unreachable!(), // 应该在 match_tokens 中被处理
             };
@@ -328,6 +768,7 @@ impl Parser {
                 left: Box::new(expr),
                 op,
                 right: Box::new(right),
+                location,
             };
         }
         Ok(expr)
@@ -353,25 +794,70 @@ impl Parser {
         self.call_and_access()
     }
 
-    // CallAndAccessExpression ::= PrimaryExpression { "(" Arguments? ")" | "[" Expression "]" | "." Identifier }
+    // IndexOrSlice ::= Expression | [ Expression ] ":" [ Expression ] [ ":" [ Expression ] ]
+    fn index_or_slice(&mut self) -> Result<AccessType, EasyScriptError> {
+        let start = if self.check(&Token::Colon) {
+            None
+        } else {
+            Some(Box::new(self.expression()?))
+        };
+        if !self.match_tokens(&[Token::Colon]) {
+            // Without a ':' this is a plain index, which the branch above always parsed.
+            return Ok(AccessType::Index(start.unwrap()));
+        }
+        let end = self.optional_slice_bound()?;
+        let step = if self.match_tokens(&[Token::Colon]) {
+            self.optional_slice_bound()?
+        } else {
+            None
+        };
+        Ok(AccessType::Slice(SliceBounds { start, end, step }))
+    }
+
+    // A slice bound, omitted when the next token is ':' or ']'.
+    fn optional_slice_bound(&mut self) -> Result<Option<Box<Expression>>, EasyScriptError> {
+        if self.check(&Token::Colon) || self.check(&Token::RightBracket) {
+            Ok(None)
+        } else {
+            Ok(Some(Box::new(self.expression()?)))
+        }
+    }
+
+    // CallAndAccessExpression ::= PrimaryExpression { "(" Arguments? ")" | "[" IndexOrSlice "]" | "." Identifier }
     fn call_and_access(&mut self) -> Result<Expression, EasyScriptError> {
         let mut expr = self.primary()?;
 
         loop {
             if self.match_tokens(&[Token::LeftParen]) {
-                expr = self.finish_call(expr)?;
-            } else if self.match_tokens(&[Token::LeftBracket]) {
-                let key = self.expression()?;
+                expr = self.finish_call(expr, false)?;
+            } else if self.match_tokens(&[Token::LeftBracket, Token::QuestionBracket]) {
+                let optional = *self.previous() == Token::QuestionBracket;
+                let access = self.index_or_slice()?;
                 self.consume(&Token::RightBracket, "Expect ']' after index.")?;
                 expr = Expression::Accessor {
                     target: Box::new(expr),
-                    access: AccessType::Index(Box::new(key)),
+                    access,
+                    optional,
                 };
+            } else if self.match_tokens(&[Token::QuestionDot]) {
+                // `?.(` is an optional call, `?.name` an optional property access.
+                if self.match_tokens(&[Token::LeftParen]) {
+                    expr = self.finish_call(expr, true)?;
+                } else {
+                    let property_name =
+                        self.consume_identifier("Expect property name or '(' after '?.'.")?;
+                    expr = Expression::Accessor {
+                        target: Box::new(expr),
+                        access: AccessType::Dot(property_name),
+                        optional: true,
+                    };
+                }
             } else if self.match_tokens(&[Token::Dot]) {
                 let property_name = self.consume_identifier("Expect property name after '.'.")?;
                 expr = Expression::Accessor {
                     target: Box::new(expr),
                     access: AccessType::Dot(property_name),
+                    optional: false,
                 };
             } else {
                 break;
@@ -381,7 +867,7 @@ impl Parser {
         Ok(expr)
     }
 
-    // PrimaryExpression ::= Literal | Identifier | "(" Expression ")" | ListLiteral | MapLiteral | BlockExpression
+    // PrimaryExpression ::= Literal | Identifier | "(" Expression ")" | TupleLiteral | ListLiteral | MapLiteral | BlockExpression
     fn primary(&mut self) -> Result<Expression, EasyScriptError> {
         if self.match_tokens(&[Token::KeywordFalse]) {
             return Ok(Expression::Literal(LiteralValue::Boolean(false)));
@@ -398,7 +884,10 @@ impl Parser {
             self.advance();
             return Ok(match owned_literal {
                 Literal::Number(n) => Expression::Literal(LiteralValue::Number(n)),
+                Literal::Integer(n) => Expression::Literal(LiteralValue::Integer(n)),
+                Literal::BigInteger(n) => Expression::Literal(LiteralValue::BigInteger(n)),
                 Literal::String(s) => Expression::Literal(LiteralValue::String(s)),
+                Literal::Bytes(b) => Expression::Literal(LiteralValue::Bytes(b)),
             });
         }
 
@@ -409,30 +898,30 @@ impl Parser {
         }
 
         if self.match_tokens(&[Token::LeftParen]) {
-            let expr = self.expression()?;
-            self.consume(&Token::RightParen, "Expect ')' after expression.")?;
-            return Ok(expr);
+            return self.group_or_tuple();
         }
 
         if self.match_tokens(&[Token::LeftBracket]) {
             return self.list_literal();
         }
 
+        if self.match_tokens(&[Token::HashBrace]) {
+            return self.set_literal();
+        }
+
         if self.match_tokens(&[Token::LeftBrace]) {
-            // Check for map first, then fall back to block.
-            // A map is distinguished by a key followed by a colon.
-            // An empty `{}` will be parsed as an empty map.
-            // A block is `{ <expr> ... }`
-            if self.check_next(&Token::Colon) {
-                return self.map_literal();
-            } else if self.check(&Token::RightBrace) {
-                // Empty {} is a map
-                return self.map_literal();
-            } else {
-                // It's a block expression
-                let block = self.block()?;
-                return Ok(Expression::Block(block));
+            // An empty `{}` is an empty map. Otherwise parse the first item, then decide:
+            // a map if a colon follows it, so any expression (a tuple, a call, ...) can be
+            // a key; a block `{ <expr> ... }` otherwise.
+            if self.check(&Token::RightBrace) {
+                return self.map_literal(None);
+            }
+            let first = self.block_item()?;
+            if !matches!(first, Expression::Defer(_)) && self.match_tokens(&[Token::Colon]) {
+                return self.map_literal(Some(first));
             }
+            let block = self.finish_block(Some(first))?;
+            return Ok(Expression::Block(block));
         }
 
         Err(EasyScriptError::ParserError {
@@ -446,10 +935,19 @@ impl Parser {
     // Parse a block `{...}`
     // Assumes the opening brace has already been consumed.
     fn block(&mut self) -> Result<Block, EasyScriptError> {
+        self.finish_block(None)
+    }
+
+    // Parse the rest of a block whose first item, if given, has already been parsed.
+    fn finish_block(&mut self, mut first: Option<Expression>) -> Result<Block, EasyScriptError> {
         let mut expressions = Vec::new();
 
-        while !self.check(&Token::RightBrace) && !self.is_at_end() {
-            let expr = self.expression()?;
+        loop {
+            let expr = match first.take() {
+                Some(expr) => expr,
+                None if self.check(&Token::RightBrace) || self.is_at_end() => break,
+                None => self.block_item()?,
+            };
             let mut terminated_by_semicolon = false;
             // Eat trailing semicolons and mark if this expression was terminated by one
             while self.match_tokens(&[Token::Semicolon]) {
@@ -477,13 +975,60 @@ impl Parser {
         Ok(Expression::ListLiteral(elements))
     }
 
-    // Parse a map literal `{...}`
-    fn map_literal(&mut self) -> Result<Expression, EasyScriptError> {
-        let mut pairs = Vec::new();
+    // Parse what follows `(`: `()` and anything with a comma, like `(x,)` or `(x, y)`, is a
+    // tuple; a single expression without a comma is just grouped.
+    fn group_or_tuple(&mut self) -> Result<Expression, EasyScriptError> {
+        if self.match_tokens(&[Token::RightParen]) {
+            return Ok(Expression::TupleLiteral(Vec::new()));
+        }
+        let first = self.expression()?;
+        if !self.match_tokens(&[Token::Comma]) {
+            self.consume(&Token::RightParen, "Expect ')' after expression.")?;
+            return Ok(first);
+        }
+        let mut elements = vec![first];
+        while !self.check(&Token::RightParen) {
+            elements.push(self.expression()?);
+            if !self.match_tokens(&[Token::Comma]) {
+                break;
+            }
+        }
+        self.consume(&Token::RightParen, "Expect ')' after tuple elements.")?;
+        Ok(Expression::TupleLiteral(elements))
+    }
+
+    // Parse a set literal `#{...}`; `#{}` is the empty set
+    fn set_literal(&mut self) -> Result<Expression, EasyScriptError> {
+        let mut elements = Vec::new();
         if !self.check(&Token::RightBrace) {
             loop {
-                let key = self.expression()?;
-                self.consume(&Token::Colon, "Expect ':' after map key.")?;
+                elements.push(self.expression()?);
+                if !self.match_tokens(&[Token::Comma]) {
+                    break;
+                }
+            }
+        }
+        self.consume(&Token::RightBrace, "Expect '}' after set elements.")?;
+        Ok(Expression::SetLiteral(elements))
+    }
+
+    // Parse a map literal `{...}`. `first_key` is the first key, when the caller has already
+    // parsed it along with its colon.
+    fn map_literal(
+        &mut self,
+        mut first_key: Option<Expression>,
+    ) -> Result<Expression, EasyScriptError> {
+        let mut pairs = Vec::new();
+        if first_key.is_some() || !self.check(&Token::RightBrace) {
+            loop {
+                let key = match first_key.take() {
+                    Some(key) => key,
+                    None => {
+                        let key = self.expression()?;
+                        self.consume(&Token::Colon, "Expect ':' after map key.")?;
+                        key
+                    }
+                };
                 let value = self.expression()?;
                 pairs.push((key, value));
 
@@ -497,11 +1042,39 @@ impl Parser {
     }
 
     // Finish parsing a function call
-    fn finish_call(&mut self, callee: Expression) -> Result<Expression, EasyScriptError> {
+    fn finish_call(
+        &mut self,
+        callee: Expression,
+        optional: bool,
+    ) -> Result<Expression, EasyScriptError> {
+        let location = self.previous_location(); // the '('
         let mut args = Vec::new();
         if !self.check(&Token::RightParen) {
             loop {
-                args.push(self.expression()?);
+                let is_keyword = matches!(self.peek(), Token::Identifier(_))
+                    && matches!(self.tokens.get(self.current + 1), Some(Token::Colon));
+                let arg = if is_keyword {
+                    let name = self.consume_identifier("Expect argument name.")?;
+                    self.consume(&Token::Colon, "Expect ':' after argument name.")?;
+                    Argument::Keyword {
+                        name,
+                        value: self.expression()?,
+                    }
+                } else {
+                    if matches!(args.last(), Some(Argument::Keyword { .. })) {
+                        return Err(EasyScriptError::ParserError {
+                            message: "Positional argument cannot follow keyword arguments."
+                                .to_string(),
+                            location: None,
+                        });
+                    }
+                    if self.match_tokens(&[Token::Ellipsis]) {
+                        Argument::Spread(self.expression()?)
+                    } else {
+                        Argument::Positional(self.expression()?)
+                    }
+                };
+                args.push(arg);
                 if !self.match_tokens(&[Token::Comma]) {
                     break;
                 }
@@ -511,6 +1084,8 @@ impl Parser {
         Ok(Expression::Call {
             callee: Box::new(callee),
             args,
+            optional,
+            location,
         })
     }
 
@@ -553,21 +1128,6 @@ impl Parser {
             && std::mem::discriminant(self.peek()) == std::mem::discriminant(token_type)
     }
 
-    fn check_next(&self, token_type: &Token) -> bool {
-        if self.is_at_end() {
-            return false;
-        }
-        if matches!(self.tokens.get(self.current + 1), Some(&Token::Eof)) {
-            return false;
-        }
-
-        if let Some(next_token) = self.tokens.get(self.current + 1) {
-            std::mem::discriminant(next_token) == std::mem::discriminant(token_type)
-        } else {
-            false
-        }
-    }
-
     fn advance(&mut self) -> &Token {
         if !self.is_at_end() {
             self.current += 1;
@@ -584,4 +1144,10 @@ impl Parser {
     fn previous(&self) -> &Token {
         &self.tokens[self.current - 1]
     }
+    fn location(&self) -> Option<SourceLocation> {
+        self.locations[self.current].clone()
+    }
+    fn previous_location(&self) -> Option<SourceLocation> {
+        self.locations[self.current - 1].clone()
+    }
 }
@@ -0,0 +1,166 @@
+//! Function signatures and argument binding, shared by user functions and natives.
+//!
+//! A call site produces `CallArgs` (positional values, with spreads already expanded, followed
+//! by keyword values). Binding matches them against the parameters of a signature and reports
+//! arity problems by function and parameter name.
+
+use crate::value::Value;
+
+/// How a parameter is filled when the call does not mention it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamKind {
+    /// Must be given, positionally or by keyword.
+    Required,
+    /// May be omitted; a user function then evaluates its default.
+    Optional,
+    /// `...name`: collects the remaining positional arguments. Parameters after it can only
+    /// be given by keyword.
+    Rest,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ParamSpec<'a> {
+    pub name: &'a str,
+    pub kind: ParamKind,
+}
+
+/// The signature a native function declares, e.g. `print(...values, sep)`.
+#[derive(Debug, Clone)]
+pub struct Signature {
+    pub name: &'static str,
+    pub params: Vec<ParamSpec<'static>>,
+}
+
+impl Signature {
+    pub fn new(name: &'static str, params: &[(&'static str, ParamKind)]) -> Self {
+        Signature {
+            name,
+            params: params
+                .iter()
+                .map(|&(name, kind)| ParamSpec { name, kind })
+                .collect(),
+        }
+    }
+}
+
+/// Evaluated arguments of a call.
+#[derive(Debug, Default)]
+pub struct CallArgs {
+    pub positional: Vec<Value>,
+    pub keyword: Vec<(String, Value)>,
+}
+
+impl CallArgs {
+    pub fn positional(values: Vec<Value>) -> Self {
+        CallArgs {
+            positional: values,
+            keyword: Vec::new(),
+        }
+    }
+}
+
+/// The argument bound to one parameter.
+#[derive(Debug)]
+pub enum BoundArg {
+    Value(Value),
+    /// An omitted optional parameter.
+    Missing,
+    /// The values collected by a rest parameter.
+    Rest(Vec<Value>),
+}
+
+/// Matches `args` against `params`, returning one `BoundArg` per parameter.
+///
+/// `function_name` is used in error messages, e.g. "add() missing required argument 'y'.".
+pub fn bind_arguments(
+    function_name: &str,
+    params: &[ParamSpec],
+    args: CallArgs,
+) -> Result<Vec<BoundArg>, String> {
+    let mut bound: Vec<Option<BoundArg>> = params.iter().map(|_| None).collect();
+
+    // Positional arguments fill the parameters before the rest parameter, in order.
+    let rest_index = params.iter().position(|p| p.kind == ParamKind::Rest);
+    let positional_slots = rest_index.unwrap_or(params.len());
+    let given = args.positional.len();
+    let mut positional = args.positional.into_iter();
+    for slot in bound.iter_mut().take(positional_slots) {
+        match positional.next() {
+            Some(value) => *slot = Some(BoundArg::Value(value)),
+            None => break,
+        }
+    }
+    match rest_index {
+        Some(index) => bound[index] = Some(BoundArg::Rest(positional.collect())),
+        None if given > positional_slots => {
+            let required = params
+                .iter()
+                .filter(|p| p.kind == ParamKind::Required)
+                .count();
+            let expected = if required == positional_slots {
+                plural(required, "argument")
+            } else {
+                format!("{} to {}", required, plural(positional_slots, "argument"))
+            };
+            return Err(format!(
+                "{} takes {} but got {}.",
+                function_name, expected, given
+            ));
+        }
+        None => {}
+    }
+
+    for (name, value) in args.keyword {
+        let Some(index) = params
+            .iter()
+            .position(|p| p.name == name && p.kind != ParamKind::Rest)
+        else {
+            return Err(format!(
+                "{} got an unexpected keyword argument '{}'.",
+                function_name, name
+            ));
+        };
+        if bound[index].is_some() {
+            return Err(format!(
+                "{} got multiple values for argument '{}'.",
+                function_name, name
+            ));
+        }
+        bound[index] = Some(BoundArg::Value(value));
+    }
+
+    params
+        .iter()
+        .zip(bound)
+        .map(|(param, arg)| match (arg, param.kind) {
+            (Some(arg), _) => Ok(arg),
+            (None, ParamKind::Required) => Err(format!(
+                "{} missing required argument '{}'.",
+                function_name, param.name
+            )),
+            (None, _) => Ok(BoundArg::Missing),
+        })
+        .collect()
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+/// Renders a signature the way the docs write one, e.g. `range(start, end[, step])`.
+pub fn format_signature(function_name: &str, params: &[ParamSpec]) -> String {
+    let mut rendered = String::new();
+    for (i, param) in params.iter().enumerate() {
+        let separator = if i == 0 { "" } else { ", " };
+        match param.kind {
+            ParamKind::Required => rendered += &format!("{}{}", separator, param.name),
+            ParamKind::Optional => rendered += &format!("[{}{}]", separator, param.name),
+            ParamKind::Rest => rendered += &format!("{}...{}", separator, param.name),
+        }
+    }
+    format!("{}({})", function_name, rendered)
+}
@@ -0,0 +1,75 @@
+//! Index and slice arithmetic shared by lists and strings.
+//!
+//! Indices count from the end when negative (`xs[-1]` is the last element). Slices follow
+//! Python: out-of-range bounds are clamped instead of reported, and a negative step walks
+//! backwards, with omitted bounds defaulting to the ends of the sequence in that direction.
+//! Strings are indexed by Unicode scalar value, like `string.substring()`.
+
+/// The position `index` refers to in a sequence of `len` elements, if it is in bounds.
+pub fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    let len = len as i64;
+    let index = if index < 0 { index + len } else { index };
+    (0..len).contains(&index).then_some(index as usize)
+}
+
+/// Evaluated bounds of `target[start:end:step]`; omitted (or nil) bounds are `None`.
+#[derive(Debug, Clone, Copy)]
+pub struct Slice {
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+    pub step: Option<i64>,
+}
+
+impl Slice {
+    /// The step, validated: a slice cannot stand still.
+    pub fn step(&self) -> Result<i64, String> {
+        match self.step {
+            Some(0) => Err("Slice step cannot be zero.".to_string()),
+            step => Ok(step.unwrap_or(1)),
+        }
+    }
+
+    /// The clamped `(start, end)` of the slice in a sequence of `len` elements. With a
+    /// negative step, -1 stands for "before the first element".
+    fn bounds(&self, len: usize, step: i64) -> (i64, i64) {
+        let len = len as i64;
+        let (lowest, highest) = if step > 0 { (0, len) } else { (-1, len - 1) };
+        let clamp = |bound: Option<i64>, default: i64| match bound {
+            None => default,
+            Some(b) if b < 0 => (b + len).max(lowest),
+            Some(b) => b.min(highest),
+        };
+        if step > 0 {
+            (clamp(self.start, 0), clamp(self.end, len))
+        } else {
+            (clamp(self.start, len - 1), clamp(self.end, -1))
+        }
+    }
+
+    /// The positions the slice selects, in order.
+    pub fn positions(&self, len: usize) -> Result<Vec<usize>, String> {
+        let step = self.step()?;
+        let (start, end) = self.bounds(len, step);
+        let mut positions = Vec::new();
+        let mut i = start;
+        while (step > 0 && i < end) || (step < 0 && i > end) {
+            positions.push(i as usize);
+            // A step beyond the sequence can overflow; the next position would be out of range.
+            match i.checked_add(step) {
+                Some(next) => i = next,
+                None => break,
+            }
+        }
+        Ok(positions)
+    }
+
+    /// For a step of 1, the contiguous range the slice covers; replacing it may change the
+    /// length of the sequence. `None` for any other step.
+    pub fn contiguous_range(&self, len: usize) -> Result<Option<std::ops::Range<usize>>, String> {
+        if self.step()? != 1 {
+            return Ok(None);
+        }
+        let (start, end) = self.bounds(len, 1);
+        Ok(Some(start as usize..end.max(start) as usize))
+    }
+}
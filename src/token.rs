@@ -1,8 +1,13 @@
+use crate::bigint::BigInt;
+
 // 新增 Literal 枚举来存储字符串和数字的实际值
 #[derive(Debug, Clone, PartialEq)]
 pub enum Literal {
     String(String),
+    Bytes(Vec<u8>), // b"..." 字面量
     Number(f64),
+    Integer(i64),
+    BigInteger(BigInt), // 超出 i64 范围的整数
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -19,18 +24,25 @@ pub enum Token {
     KeywordFor,
     KeywordFun,
     KeywordIn,
+    KeywordIs,
     KeywordTrue,
     KeywordFalse,
     KeywordNil,
     KeywordLet,
+    KeywordConst,
+    KeywordYield,
+    KeywordEnum,
+    KeywordMatch,
+    KeywordDefer,
 
     // --- 运算符 (Operators) ---
     // 算术
-    Plus,    // +
-    Minus,   // -
-    Star,    // *
-    Slash,   // /
-    Percent, // %
+    Plus,       // +
+    Minus,      // -
+    Star,       // *
+    Slash,      // /
+    SlashSlash, // //
+    Percent,    // %
 
     // 位移/位运算
     ShiftLeft,  // <<
@@ -52,20 +64,45 @@ pub enum Token {
     And, // &&
     Or,  // ||
 
+    // 空值安全 (Nil-safe)
+    QuestionDot,      // ?.
+    QuestionBracket,  // ?[
+    QuestionQuestion, // ??
+
     // --- 标点符号 (Punctuation) / 单个字符 ---
     LeftParen,    // (
     RightParen,   // )
     LeftBracket,  // [
     RightBracket, // ]
     LeftBrace,    // {
+    HashBrace,    // #{ (集合字面量的开头)
     RightBrace,   // }
     Comma,        // ,
     Dot,          // .
+    Ellipsis,     // ... (剩余参数 / 展开参数)
     Colon,        // :
     Semicolon,    // ;
+    Arrow,        // -> (返回值类型注解)
 
     // --- 赋值 (Assignment) ---
-    Equal, // =
+    Equal,    // =
+    FatArrow, // => (match 分支)
+
+    // 复合赋值
+    PlusEqual,       // +=
+    MinusEqual,      // -=
+    StarEqual,       // *=
+    SlashEqual,      // /=
+    SlashSlashEqual, // //=
+    PercentEqual,    // %=
+    ShiftLeftEqual,  // <<=
+    ShiftRightEqual, // >>=
+    AmpersandEqual,  // &=
+    PipeEqual,       // |=
+    CaretEqual,      // ^=
+
+    // --- 注释 ---
+    DocComment(String), // `## ...` 文档注释的一行（去掉 `##` 与一个前导空格）
 
     // --- 文件结束 ---
     Eof,
@@ -3,31 +3,47 @@
 
 use std::alloc::{self, Layout};
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
 use std::fmt;
 use std::ptr::NonNull; // For safe raw pointers
 use std::rc::Rc; // For NativeFunction's internal Rc // For custom allocation
 
-use crate::ast::Block; // For FunctionObjectInner
+use crate::ast::{Block, Param}; // For FunctionObjectInner
+use crate::bigint::BigInt; // For Object::BigInt
+use crate::decimal::Decimal; // For Object::Decimal
 use crate::environment::EnvironmentRef; // For FunctionObjectInner
-                                        // NOTE: This recursive use is fine, as self::Value refers to the Value struct defined below.
-                                        // It's used within Object::List and Object::Map
+use crate::interpreter::coroutine::Coroutine; // For IteratorInner::Generator
+use crate::interpreter::NativeContext; // For NativeFunction
+use crate::ordered_map::OrderedMap; // For MapInner
+use crate::signature::Signature; // For FunctionObjectInner::Native
+                                 // NOTE: This recursive use is fine, as self::Value refers to the Value struct defined below.
+                                 // It's used within Object::List and Object::Map
 
 // --- Type Aliases for Function Objects ---
 /// Defines the signature for a native Rust function that can be called from EasyScript.
-pub type NativeFunction =
-    Rc<dyn for<'a> Fn(&'a mut Heap, &EnvironmentRef, Vec<Value>) -> Result<Value, String>>;
+/// The context gives access to the heap and can call back into script functions.
+pub type NativeFunction = Rc<dyn Fn(&mut NativeContext, Vec<Value>) -> Result<Value, String>>;
 
 /// Represents a user-defined or native function in EasyScript.
 #[derive(Clone)]
 pub enum FunctionObjectInner {
-    /// A native Rust function (e.g., `print`).
-    Native(NativeFunction),
+    /// A native Rust function (e.g., `print`). With a `signature`, arguments are bound like
+    /// a user function's: one value per declared parameter (a rest parameter gets a list,
+    /// trailing omitted optional parameters are left out and inner ones are nil). Without
+    /// one, the native receives the positional arguments as given.
+    Native {
+        function: NativeFunction,
+        signature: Option<Rc<Signature>>,
+    },
     /// A user-defined function written in EasyScript.
     User {
-        params: Vec<String>,
+        name: Option<String>, // Set for `let name = fun...`
+        params: Vec<Param>,
         body: Rc<Block>,             // Function body is an AST Block
         defined_env: EnvironmentRef, // Closure environment
+        is_generator: bool,          // Calling it returns a generator instead of running the body
+        doc: Option<String>,         // The `##` doc comment written above it, for help()
     },
 }
 
@@ -35,24 +51,184 @@ pub enum FunctionObjectInner {
 impl fmt::Debug for FunctionObjectInner {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            FunctionObjectInner::Native(_) => write!(f, "NativeFunction"),
-            FunctionObjectInner::User {
-                params,
-                body: _,
-                defined_env: _,
-            } => {
+            FunctionObjectInner::Native { .. } => write!(f, "NativeFunction"),
+            FunctionObjectInner::User { params, .. } => {
                 write!(f, "UserFunction {{ params: {:?} }}", params)
             }
         }
     }
 }
 
+/// The state of a native iterator object.
+///
+/// Iterators are lazy: every step is driven by `Interpreter::iterator_next`,
+/// because adaptors such as `map` and `filter` have to call back into script functions.
+#[derive(Debug, Clone)]
+pub enum IteratorInner {
+    /// Walks a list by position, so elements pushed during iteration are still visited.
+    List {
+        list: Value,
+        index: usize,
+    },
+    /// Walks a snapshot of a map's keys taken when the iterator was created.
+    Keys {
+        keys: Vec<Value>,
+        index: usize,
+    },
+    /// Counts from `next` towards `end` (exclusive) by `step`.
+    Range {
+        next: f64,
+        end: f64,
+        step: f64,
+    },
+    /// The integer form of `Range`, used when every `range()` argument is an integer.
+    IntegerRange {
+        next: i64,
+        end: i64,
+        step: i64,
+    },
+    /// Reads standard input one line at a time.
+    Lines,
+    /// A map whose `__iter` function has not been called yet.
+    Iterable {
+        object: Value,
+    },
+    /// A map implementing the protocol: `next()` returns the next item, or `nil` when done.
+    Protocol {
+        object: Value,
+    },
+    /// Adaptors. Their sources are always iterator values.
+    Map {
+        source: Value,
+        func: Value,
+    },
+    Filter {
+        source: Value,
+        func: Value,
+    },
+    Take {
+        source: Value,
+        remaining: usize,
+    },
+    Zip {
+        left: Value,
+        right: Value,
+    },
+    Enumerate {
+        source: Value,
+        index: usize,
+    },
+    Chain {
+        first: Value,
+        second: Value,
+        on_second: bool,
+    },
+    /// A suspended call of a generator function, resumed on every step.
+    Generator(Rc<Coroutine>),
+}
+
+impl GcTrace for IteratorInner {
+    fn trace(&self, heap: &Heap) {
+        match self {
+            IteratorInner::List { list, .. } => list.trace(heap),
+            IteratorInner::Keys { keys, .. } => {
+                for key in keys {
+                    key.trace(heap);
+                }
+            }
+            IteratorInner::Range { .. }
+            | IteratorInner::IntegerRange { .. }
+            | IteratorInner::Lines => {}
+            IteratorInner::Iterable { object } | IteratorInner::Protocol { object } => {
+                object.trace(heap)
+            }
+            IteratorInner::Map { source, func } | IteratorInner::Filter { source, func } => {
+                source.trace(heap);
+                func.trace(heap);
+            }
+            IteratorInner::Take { source, .. } | IteratorInner::Enumerate { source, .. } => {
+                source.trace(heap)
+            }
+            IteratorInner::Zip { left, right } => {
+                left.trace(heap);
+                right.trace(heap);
+            }
+            IteratorInner::Chain { first, second, .. } => {
+                first.trace(heap);
+                second.trace(heap);
+            }
+            IteratorInner::Generator(coroutine) => coroutine.trace(heap),
+        }
+    }
+}
+
 /// Represents a method bound to a specific receiver object.
 #[derive(Debug, Clone)]
 pub struct BoundMethodInner {
-    pub receiver: Value, // The object (self) to which the method is bound
+    pub receiver: Value,     // The object (self) to which the method is bound
     pub method_name: String, // The name of the method (e.g., "push", "len")
-                         // Note: The actual NativeFunction is looked up at call time based on method_name
+    /// The function found on a map or its prototype chain, called with the receiver as its
+    /// first argument. `None` for builtin methods, whose NativeFunction is looked up at call
+    /// time based on `method_name`.
+    pub function: Option<Value>,
+}
+
+/// The elements of a set, in insertion order. Only hashable values (see
+/// `Value::is_hashable`) are ever inserted.
+pub type SetElements = OrderedMap<Value, ()>;
+
+/// A variant of an `enum` declaration, shared by every value built from it.
+#[derive(Debug)]
+pub struct EnumVariant {
+    pub enum_name: String,
+    pub name: String,
+    pub fields: Vec<String>, // Empty for a variant declared without parentheses
+}
+
+impl EnumVariant {
+    /// The variant as written in scripts, e.g. `Shape.Circle`.
+    pub fn qualified_name(&self) -> String {
+        format!("{}.{}", self.enum_name, self.name)
+    }
+}
+
+/// The payload of a tagged enum value: its variant and one value per declared field.
+#[derive(Debug, Clone)]
+pub struct EnumValue {
+    pub variant: Rc<EnumVariant>,
+    pub fields: Vec<Value>,
+}
+
+impl EnumValue {
+    /// Whether this is the variant `enum_name.name`. Variants are told apart by name, so a
+    /// value still matches after its enum is declared again, e.g. by a second call to the
+    /// function declaring it.
+    pub fn is_variant(&self, enum_name: &str, name: &str) -> bool {
+        self.variant.enum_name == enum_name && self.variant.name == name
+    }
+
+    /// The value of the field called `name`.
+    pub fn field(&self, name: &str) -> Option<&Value> {
+        let position = self.variant.fields.iter().position(|f| f == name)?;
+        self.fields.get(position)
+    }
+}
+
+/// The payload of a map: its own entries, plus the prototype that dot access falls back to
+/// when a key is missing (see `set_proto`).
+#[derive(Debug, Clone, Default)]
+pub struct MapInner {
+    pub entries: OrderedMap<Value, Value>,
+    pub proto: Option<Value>,
+}
+
+impl MapInner {
+    pub fn new(entries: OrderedMap<Value, Value>) -> Self {
+        MapInner {
+            entries,
+            proto: None,
+        }
+    }
 }
 
 // --- 1. GcRef Handle ---
@@ -82,12 +258,15 @@ impl std::hash::Hash for GcRef {
 
 impl GcRef {
     /// Internal method to create a GcRef handle from a raw pointer to a GcObjectHeader.
-    /// This is `unsafe` because the caller must guarantee the pointer is valid.
+    ///
+    /// # Safety
+    /// The caller must guarantee the pointer is valid.
     pub unsafe fn from_raw(ptr: NonNull<GcObjectHeader>) -> Self {
         GcRef { ptr }
     }
 
     /// Dereferences the GcRef handle to get an immutable reference to the managed `Object`.
+    #[allow(clippy::should_implement_trait)]
     pub fn deref(&self) -> &Object {
         unsafe {
             let header_ptr = self.ptr.as_ptr();
@@ -105,6 +284,7 @@ impl GcRef {
     /// Dereferences the GcRef handle to get a mutable reference to the managed `Object`.
     /// This is `unsafe` because the caller must guarantee no other mutable references exist
     /// to this object (GC ensures this during Stop-the-World phases).
+    #[allow(clippy::should_implement_trait)]
     pub fn deref_mut(&mut self) -> &mut Object {
         unsafe {
             let header_ptr = self.ptr.as_ptr();
@@ -126,6 +306,7 @@ impl GcRef {
 #[repr(C)] // Force specific memory layout for header followed by payload
 pub struct GcObjectHeader {
     pub marked: RefCell<bool>, // The 'color' field: Whether the object is marked as reachable during GC cycle
+    pub frozen: RefCell<bool>, // Set by `freeze()`: the object rejects every mutation
     pub obj_type: GcObjectType, // Type of the Object (Number, List, Map, etc.)
                                // Data for the actual object (Object) follows this header in memory
 }
@@ -135,12 +316,21 @@ pub struct GcObjectHeader {
 pub enum GcObjectType {
     Number,
     Boolean,
+    Integer,
+    BigInt,
+    Decimal,
     String,
+    Bytes,
     Nil,
     List,
+    Tuple,
     Map,
+    Set,
+    Enum,
     Function,
     BoundMethod,
+    Iterator,
+    Channel,
     // ... potentially other GC'd types
 }
 
@@ -152,11 +342,20 @@ pub enum Object {
     Nil,
     Boolean(bool),
     Number(f64),
+    Integer(i64),   // Also of type "number"; exact 64-bit integer arithmetic
+    BigInt(BigInt), // Also of type "number"; only for integers that do not fit an i64
+    Decimal(Decimal),
     String(String),
+    Bytes(Vec<u8>),                // Immutable binary data
     List(Vec<Value>),              // Contains Value handles
-    Map(HashMap<Value, Value>),    // Keys and values are Value handles
+    Tuple(Vec<Value>),             // Like a list, but immutable; hashable if its elements are
+    Map(MapInner),                 // Keys and values are Value handles
+    Set(SetElements),              // Hashable elements, in insertion order
+    Enum(EnumValue),               // A value of an `enum` variant, with its fields
     Function(FunctionObjectInner), // User-defined or native functions
     BoundMethod(BoundMethodInner), // Method bound to a receiver
+    Iterator(IteratorInner),       // Lazy iterator (see `IteratorInner`)
+    Channel(VecDeque<Value>),      // Unbounded FIFO of values sent between tasks
 }
 
 // Convert Object variant to GcObjectType for the header
@@ -164,17 +363,38 @@ impl From<Object> for GcObjectType {
     fn from(payload: Object) -> Self {
         match payload {
             Object::Number(_) => GcObjectType::Number,
+            Object::Integer(_) => GcObjectType::Integer,
+            Object::BigInt(_) => GcObjectType::BigInt,
+            Object::Decimal(_) => GcObjectType::Decimal,
             Object::Boolean(_) => GcObjectType::Boolean,
             Object::String(_) => GcObjectType::String,
+            Object::Bytes(_) => GcObjectType::Bytes,
             Object::Nil => GcObjectType::Nil,
             Object::List(_) => GcObjectType::List,
+            Object::Tuple(_) => GcObjectType::Tuple,
             Object::Map(_) => GcObjectType::Map,
+            Object::Set(_) => GcObjectType::Set,
+            Object::Enum(_) => GcObjectType::Enum,
             Object::Function(_) => GcObjectType::Function,
             Object::BoundMethod(_) => GcObjectType::BoundMethod,
+            Object::Iterator(_) => GcObjectType::Iterator,
+            Object::Channel(_) => GcObjectType::Channel,
         }
     }
 }
 
+/// Traces every value visible from `env`, walking up through its parent scopes.
+pub fn trace_environment(env: &EnvironmentRef, heap: &Heap) {
+    let mut current_env = Some(Rc::clone(env));
+    while let Some(env_ref) = current_env {
+        let env_borrow = env_ref.borrow();
+        for value in env_borrow.values.values() {
+            value.trace(heap);
+        }
+        current_env = env_borrow.parent.as_ref().map(Rc::clone);
+    }
+}
+
 /// Trait for types that can be managed by the GC and can be traced.
 ///
 /// Every type that can live on the GC heap, or contain `GcRef` references,
@@ -187,58 +407,114 @@ pub trait GcTrace {
 // Implement Display for Object for printing EasyScript values
 impl fmt::Display for Object {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_display(f, &mut Vec::new())
+    }
+}
+
+/// The lists and maps being rendered or compared, outermost first. A structure that contains
+/// itself is found on this path when it is revisited, which is what stops the recursion.
+type Path = Vec<*const Object>;
+
+/// Runs `visit` with `object` pushed on `path`, or returns `None` if it is already there.
+fn with_visited<T>(
+    object: &Object,
+    path: &mut Path,
+    visit: impl FnOnce(&mut Path) -> T,
+) -> Option<T> {
+    let address = object as *const Object;
+    if path.contains(&address) {
+        return None;
+    }
+    path.push(address);
+    let result = visit(path);
+    path.pop();
+    Some(result)
+}
+
+impl Object {
+    /// Display, rendering a list or map already being displayed as `[...]` or `{...}`.
+    fn fmt_display(&self, f: &mut fmt::Formatter<'_>, path: &mut Path) -> fmt::Result {
+        // Strings nested in lists and maps are quoted; everything else keeps its Display form.
+        let fmt_nested =
+            |f: &mut fmt::Formatter<'_>, item: &Value, path: &mut Path| match item.0.deref() {
+                Object::String(s) => write!(f, "{:?}", s),
+                object => object.fmt_display(f, path),
+            };
         match self {
             Object::Nil => write!(f, "nil"),
             Object::Boolean(b) => write!(f, "{}", b),
-            Object::Number(n) => {
-                // Handle integer display without .0
-                if n.fract() == 0.0 {
-                    write!(f, "{}", *n as i64)
-                } else {
-                    write!(f, "{}", n)
-                }
-            }
+            Object::Number(n) => write!(f, "{}", format_float(*n, false)),
+            Object::Integer(i) => write!(f, "{}", i),
+            Object::BigInt(b) => write!(f, "{}", b),
+            Object::Decimal(d) => write!(f, "{}", d),
             Object::String(s) => write!(f, "{}", s), // No quotes
-            Object::List(list) => {
+            Object::Bytes(b) => write!(f, "{}", format_bytes(b)),
+            Object::List(list) => with_visited(self, path, |path| {
                 write!(f, "[")?;
-                let mut first = true;
-                for item in list.iter() {
-                    if !first {
+                for (i, item) in list.iter().enumerate() {
+                    if i > 0 {
                         write!(f, ", ")?;
                     }
-                    if let Object::String(s) = item.0.deref() {
-                        write!(f, "{:?}", s)?; // String elements within lists should be quoted
-                    } else {
-                        write!(f, "{}", item)?; // Other types use their normal Display
-                    }
-                    first = false;
+                    fmt_nested(f, item, path)?;
                 }
                 write!(f, "]")
-            }
-            Object::Map(map) => {
-                write!(f, "{{")?;
-                let mut first = true;
-                for (key, val) in map.iter() {
-                    if !first {
+            })
+            .unwrap_or_else(|| write!(f, "[...]")),
+            // A one-element tuple keeps its trailing comma, as in the literal `(x,)`.
+            Object::Tuple(items) => with_visited(self, path, |path| {
+                write!(f, "(")?;
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
                         write!(f, ", ")?;
                     }
-                    if let Object::String(s) = key.0.deref() {
-                        write!(f, "{:?}", s)?; // String keys within maps should be quoted
-                    } else {
-                        write!(f, "{}", key)?; // Other key types use their normal Display
+                    fmt_nested(f, item, path)?;
+                }
+                write!(f, "{})", if items.len() == 1 { "," } else { "" })
+            })
+            .unwrap_or_else(|| write!(f, "(...)")),
+            Object::Map(map) => with_visited(self, path, |path| {
+                write!(f, "{{")?;
+                for (i, (key, val)) in map.entries.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
                     }
+                    fmt_nested(f, key, path)?;
                     write!(f, ": ")?;
-                    if let Object::String(s) = val.0.deref() {
-                        write!(f, "{:?}", s)?; // String values within maps should be quoted
-                    } else {
-                        write!(f, "{}", val)?; // Other value types use their normal Display
+                    fmt_nested(f, val, path)?;
+                }
+                write!(f, "}}")
+            })
+            .unwrap_or_else(|| write!(f, "{{...}}")),
+            // Elements are scalars, so a set cannot contain itself.
+            Object::Set(set) => {
+                write!(f, "#{{")?;
+                for (i, item) in set.keys().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
                     }
-                    first = false;
+                    fmt_nested(f, item, path)?;
                 }
                 write!(f, "}}")
             }
+            Object::Enum(value) => {
+                write!(f, "{}", value.variant.qualified_name())?;
+                if value.fields.is_empty() {
+                    return Ok(());
+                }
+                write!(f, "(")?;
+                for (i, item) in value.fields.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    fmt_nested(f, item, path)?;
+                }
+                write!(f, ")")
+            }
             Object::Function(_) => write!(f, "<function>"),
             Object::BoundMethod(_) => write!(f, "<bound method>"),
+            Object::Iterator(IteratorInner::Generator(_)) => write!(f, "<generator>"),
+            Object::Iterator(_) => write!(f, "<iterator>"),
+            Object::Channel(_) => write!(f, "<channel>"),
         }
     }
 }
@@ -248,32 +524,45 @@ impl GcTrace for Object {
     fn trace(&self, heap: &Heap) {
         // This method only needs to recursively trace its children.
         match self {
-            Object::List(list) => {
+            Object::List(list) | Object::Tuple(list) => {
                 for item in list {
                     item.trace(heap); // Recursively trace the Value handles
                 }
             }
             Object::Map(map) => {
-                for (key, val) in map {
+                for (key, val) in &map.entries {
                     key.trace(heap); // Trace keys
                     val.trace(heap); // Trace values
                 }
+                if let Some(proto) = &map.proto {
+                    proto.trace(heap);
+                }
             }
-            Object::Function(func_inner) => {
-                if let FunctionObjectInner::User { defined_env, .. } = func_inner {
-                    // A closure roots all values in its captured environment. We must trace them.
-                    let mut current_env = Some(Rc::clone(defined_env));
-                    while let Some(env_ref) = current_env {
-                        let env_borrow = env_ref.borrow();
-                        for value in env_borrow.values.values() {
-                            value.trace(heap);
-                        }
-                        current_env = env_borrow.parent.as_ref().map(Rc::clone);
-                    }
+            Object::Set(set) => {
+                for item in set.keys() {
+                    item.trace(heap);
+                }
+            }
+            Object::Enum(value) => {
+                for field in &value.fields {
+                    field.trace(heap);
                 }
             }
+            Object::Function(FunctionObjectInner::User { defined_env, .. }) => {
+                // A closure roots all values in its captured environment. We must trace them.
+                trace_environment(defined_env, heap);
+            }
             Object::BoundMethod(bound_method_inner) => {
                 bound_method_inner.receiver.trace(heap); // Trace the receiver
+                if let Some(function) = &bound_method_inner.function {
+                    function.trace(heap);
+                }
+            }
+            Object::Iterator(iterator_inner) => iterator_inner.trace(heap),
+            Object::Channel(queue) => {
+                for item in queue {
+                    item.trace(heap);
+                }
             }
             _ => { /* Primitives (Number, Boolean, String, Nil) do not contain GcRef */ }
         }
@@ -282,9 +571,47 @@ impl GcTrace for Object {
 
 // Helper methods for Object to safely access internal data
 impl Object {
-    pub fn as_number(&self) -> Option<&f64> {
-        if let Object::Number(n) = self {
-            Some(n)
+    /// The value of a number as a float; integers are converted. Decimals are not numbers
+    /// here: converting them is lossy, so it takes an explicit `float()`.
+    pub fn as_number(&self) -> Option<f64> {
+        match self {
+            Object::Number(n) => Some(*n),
+            Object::Integer(i) => Some(*i as f64),
+            Object::BigInt(b) => Some(b.to_f64()),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a number of any kind, decimals included.
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            Object::Number(_) | Object::Integer(_) | Object::BigInt(_) | Object::Decimal(_)
+        )
+    }
+
+    /// The value of an integer of either size.
+    pub fn as_big_integer(&self) -> Option<BigInt> {
+        match self {
+            Object::Integer(i) => Some(BigInt::from_i64(*i)),
+            Object::BigInt(b) => Some(b.clone()),
+            _ => None,
+        }
+    }
+
+    /// The exact value of an integer, a decimal or a finite float as a decimal.
+    pub fn as_exact_decimal(&self) -> Option<Decimal> {
+        match self {
+            Object::Number(n) => Decimal::from_f64(*n),
+            Object::Decimal(d) => Some(d.clone()),
+            _ => self.as_big_integer().map(Decimal::from_integer),
+        }
+    }
+
+    /// The value of an integer. Floats are never integers, even when integral.
+    pub fn as_integer(&self) -> Option<i64> {
+        if let Object::Integer(i) = self {
+            Some(*i)
         } else {
             None
         }
@@ -314,14 +641,19 @@ impl Object {
         }
     }
 
-    pub fn as_map(&self) -> Option<&HashMap<Value, Value>> {
+    pub fn as_map(&self) -> Option<&OrderedMap<Value, Value>> {
         if let Object::Map(m) = self {
-            Some(m)
+            Some(&m.entries)
         } else {
             None
         }
     }
 
+    /// Looks up a string key in a map without allocating a key value.
+    pub fn map_get_str(&self, key: &str) -> Option<&Value> {
+        self.as_map()?.get(&key as &dyn MapKey)
+    }
+
     pub fn as_bound_method(&self) -> Option<&BoundMethodInner> {
         if let Object::BoundMethod(bm) = self {
             Some(bm)
@@ -331,16 +663,155 @@ impl Object {
     }
 }
 
+/// Formats bytes as a literal that reads back as the same bytes: printable ASCII as is,
+/// everything else (and `"`, which a literal cannot contain) as `\xHH`.
+fn format_bytes(bytes: &[u8]) -> String {
+    let mut out = String::from("b\"");
+    for &byte in bytes {
+        match byte {
+            b'\\' => out.push_str("\\\\"),
+            b'"' => out.push_str("\\x22"),
+            b' '..=b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("\\x{:02x}", byte)),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Formats a float so that it reads back as the same float: the shortest digits that do,
+/// in scientific notation for very large and very small magnitudes. With `mark_float`, an
+/// integral value keeps a `.0` so that it does not read back as an integer.
+fn format_float(n: f64, mark_float: bool) -> String {
+    let magnitude = n.abs();
+    if n.is_finite() && magnitude != 0.0 && !(1e-4..1e16).contains(&magnitude) {
+        format!("{:e}", n)
+    } else if mark_float && n.is_finite() && n.fract() == 0.0 {
+        format!("{:.1}", n)
+    } else {
+        n.to_string()
+    }
+}
+
+/// The integer a float is exactly equal to, if any.
+pub fn float_to_integer(f: f64) -> Option<i64> {
+    // -2^63 is exactly representable; 2^63 is just out of range.
+    if f.fract() == 0.0 && (-9_223_372_036_854_775_808.0..9_223_372_036_854_775_808.0).contains(&f)
+    {
+        Some(f as i64)
+    } else {
+        None
+    }
+}
+
+/// Compares two numbers of any kind exactly: an integer is not converted to a float (which
+/// would round above 2^53). `None` if either is not a number, or for NaN.
+pub fn compare_numbers(a: &Object, b: &Object) -> Option<Ordering> {
+    match (a, b) {
+        (Object::Integer(a), Object::Integer(b)) => Some(a.cmp(b)),
+        (Object::Number(a), Object::Number(b)) => a.partial_cmp(b),
+        (Object::Integer(i), Object::Number(f)) => compare_integer_float(*i, *f),
+        (Object::Number(f), Object::Integer(i)) => {
+            compare_integer_float(*i, *f).map(Ordering::reverse)
+        }
+        _ if a.is_numeric() && b.is_numeric() => {
+            // Big integers and decimals compare through their exact decimal values, which
+            // every finite float has. An infinity is beyond all of them.
+            match (a.as_exact_decimal(), b.as_exact_decimal()) {
+                (Some(a), Some(b)) => Some(a.cmp(&b)),
+                _ => a.as_number()?.partial_cmp(&b.as_number()?),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The float exactly equal to a big integer or a decimal, if there is one.
+fn exact_float(decimal: &Decimal) -> Option<f64> {
+    let f = decimal.to_f64();
+    (Decimal::from_f64(f).as_ref() == Some(decimal)).then_some(f)
+}
+
+fn compare_integer_float(i: i64, f: f64) -> Option<Ordering> {
+    if f.is_nan() {
+        return None;
+    }
+    if f >= 9_223_372_036_854_775_808.0 {
+        return Some(Ordering::Less);
+    }
+    if f < -9_223_372_036_854_775_808.0 {
+        return Some(Ordering::Greater);
+    }
+    // Compare with the integer part first; the fraction only breaks a tie.
+    let whole = f.trunc();
+    Some(i.cmp(&(whole as i64)).then(if f > whole {
+        Ordering::Less
+    } else if f < whole {
+        Ordering::Greater
+    } else {
+        Ordering::Equal
+    }))
+}
+
 // Implement PartialEq for Object for Map keys
 impl PartialEq for Object {
     fn eq(&self, other: &Self) -> bool {
+        self.eq_on_path(other, &mut Vec::new())
+    }
+}
+
+impl Object {
+    /// Structural equality. `path` holds the pairs of lists or maps being compared: meeting a
+    /// pair again means it is equal so far, so two structures are equal when no difference
+    /// is found by unrolling their cycles.
+    fn eq_on_path(&self, other: &Self, path: &mut Vec<(*const Object, *const Object)>) -> bool {
+        let pair = (self as *const Object, other as *const Object);
+        let mut compare_within = |compare: &mut dyn FnMut(&mut Vec<_>) -> bool| {
+            if path.contains(&pair) {
+                return true;
+            }
+            path.push(pair);
+            let equal = compare(path);
+            path.pop();
+            equal
+        };
         match (self, other) {
             (Object::Nil, Object::Nil) => true,
             (Object::Boolean(a), Object::Boolean(b)) => a == b,
-            (Object::Number(a), Object::Number(b)) => a == b,
+            (a, b) if a.is_numeric() && b.is_numeric() => {
+                compare_numbers(self, other) == Some(Ordering::Equal)
+            }
             (Object::String(a), Object::String(b)) => a == b,
-            (Object::List(a), Object::List(b)) => a == b, // Structural comparison
-            (Object::Map(a), Object::Map(b)) => a == b,   // Structural comparison
+            (Object::Bytes(a), Object::Bytes(b)) => a == b,
+            (Object::List(a), Object::List(b)) | (Object::Tuple(a), Object::Tuple(b)) => {
+                a.len() == b.len()
+                    && compare_within(&mut |path| {
+                        a.iter()
+                            .zip(b)
+                            .all(|(x, y)| x.0.deref().eq_on_path(y.0.deref(), path))
+                    })
+            }
+            (Object::Map(a), Object::Map(b)) => {
+                a.entries.len() == b.entries.len()
+                    && compare_within(&mut |path| {
+                        a.entries.iter().all(|(key, x)| {
+                            b.entries
+                                .get(key)
+                                .is_some_and(|y| x.0.deref().eq_on_path(y.0.deref(), path))
+                        })
+                    })
+            }
+            (Object::Set(a), Object::Set(b)) => {
+                a.len() == b.len() && a.keys().all(|item| b.contains_key(item))
+            }
+            (Object::Enum(a), Object::Enum(b)) => {
+                b.is_variant(&a.variant.enum_name, &a.variant.name)
+                    && a.fields.len() == b.fields.len()
+                    && a.fields
+                        .iter()
+                        .zip(&b.fields)
+                        .all(|(x, y)| x.0.deref().eq_on_path(y.0.deref(), path))
+            }
             (Object::Function(_a), Object::Function(_b)) => {
                 // For function equality, we can compare their internal representation
                 // or simply return false for now if not identical native functions.
@@ -356,7 +827,8 @@ impl PartialEq for Object {
             }
             (Object::BoundMethod(a), Object::BoundMethod(b)) => {
                 // Compare receiver and method_name
-                a.receiver == b.receiver && a.method_name == b.method_name
+                a.method_name == b.method_name
+                    && a.receiver.0.deref().eq_on_path(b.receiver.0.deref(), path)
             }
             _ => false, // Different enum variants are not equal
         }
@@ -371,18 +843,37 @@ impl std::hash::Hash for Object {
         match self {
             Object::Nil => 0.hash(state),
             Object::Boolean(b) => b.hash(state),
-            Object::Number(n) => n.to_bits().hash(state),
-            Object::String(s) => s.hash(state),
-            // List, Map, Function, BoundMethod can't be used as HashMap keys (by Rust's default Hash)
-            // or require more complex structural hashing that can lead to cycles.
-            // For now, these will panic if used as keys. A robust solution needs identity hashing
-            // for these types as map keys.
-            Object::List(_) => panic!("List values cannot be used as HashMap keys"),
-            Object::Map(_) => panic!("Map values cannot be used as HashMap keys"),
-            Object::Function(_) => panic!("Function values cannot be used as HashMap keys"),
-            Object::BoundMethod(_) => {
-                panic!("BoundMethod values cannot be used as HashMap keys")
+            // Equal numbers must hash alike, so an integral float hashes as the integer.
+            Object::Integer(i) => i.hash(state),
+            Object::Number(n) => match float_to_integer(*n) {
+                Some(i) => i.hash(state),
+                None => n.to_bits().hash(state),
+            },
+            // Likewise a big number equal to a float hashes as the float; one that no float
+            // or i64 equals can only equal other big numbers, which normalize alike.
+            Object::BigInt(_) | Object::Decimal(_) => {
+                let decimal = self.as_exact_decimal().unwrap().normalized();
+                match decimal.to_integer().and_then(|i| i.to_i64()) {
+                    Some(i) => i.hash(state),
+                    None => match exact_float(&decimal) {
+                        Some(f) => f.to_bits().hash(state),
+                        None => decimal.to_string().hash(state),
+                    },
+                }
             }
+            Object::String(s) => s.hash(state),
+            Object::Bytes(b) => b.hash(state),
+            Object::Tuple(items) => items.hash(state),
+            // `Value::expect_hashable` keeps these out of maps and sets. Hashing only the kind
+            // is still consistent with `==`, so a lookup that slips through is slow, never wrong.
+            Object::List(_)
+            | Object::Map(_)
+            | Object::Set(_)
+            | Object::Enum(_)
+            | Object::Function(_)
+            | Object::BoundMethod(_)
+            | Object::Iterator(_)
+            | Object::Channel(_) => std::mem::discriminant(self).hash(state),
         }
     }
 }
@@ -394,32 +885,49 @@ impl std::hash::Hash for Object {
 /// For the first version, this will be a simple `Vec` of `NonNull<GcObjectHeader>`
 /// storing raw pointers to allocated memory blocks.
 pub struct Heap {
-    // Stores raw pointers to the GcObjectHeader of all allocated objects.
-    objects: Vec<NonNull<GcObjectHeader>>,
+    // Stores raw pointers to the GcObjectHeader of all allocated objects. Allocation only
+    // needs a shared reference, since the interpreter is shared with suspended coroutines.
+    objects: RefCell<Vec<NonNull<GcObjectHeader>>>,
     // We also need to keep track of the roots for the GC cycle.
     // This will be provided to the `collect` method for now.
 }
 
+impl Default for Heap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Heap {
     /// Creates a new, empty GC heap.
     pub fn new() -> Self {
         Heap {
-            objects: Vec::new(),
+            objects: RefCell::new(Vec::new()),
         }
     }
 
     /// Allocates a new Object on the GC heap.
-    /// This is `unsafe` because it involves raw memory allocation and pointer casting.
-    pub unsafe fn allocate(&mut self, payload: Object) -> GcRef {
+    ///
+    /// # Safety
+    /// This involves raw memory allocation and pointer casting; the returned
+    /// handle is only valid until the object is swept.
+    pub unsafe fn allocate(&self, payload: Object) -> GcRef {
         let type_str = match &payload {
             Object::Nil => "nil",
             Object::Boolean(_) => "boolean",
-            Object::Number(_) => "number",
+            Object::Number(_) | Object::Integer(_) | Object::BigInt(_) => "number",
+            Object::Decimal(_) => "decimal",
             Object::String(_) => "string",
+            Object::Bytes(_) => "bytes",
             Object::List(_) => "list",
+            Object::Tuple(_) => "tuple",
             Object::Map(_) => "map",
+            Object::Set(_) => "set",
+            Object::Enum(_) => "enum",
             Object::Function(_) => "function",
             Object::BoundMethod(_) => "method",
+            Object::Iterator(_) => "iterator",
+            Object::Channel(_) => "channel",
         };
         if std::env::var("DEBUG_GC").unwrap_or_default() == "1" {
             eprintln!("[GC ALLOC] type={}, content={:?}", type_str, payload);
@@ -442,6 +950,7 @@ impl Heap {
         // Write header
         ptr.write(GcObjectHeader {
             marked: RefCell::new(false),
+            frozen: RefCell::new(false),
             obj_type,
         });
 
@@ -450,64 +959,121 @@ impl Heap {
         obj_data_ptr.write(payload);
 
         let gc_handle = GcRef::from_raw(NonNull::new_unchecked(ptr));
-        self.objects.push(NonNull::new_unchecked(ptr)); // Keep track of all allocated objects
+        self.objects.borrow_mut().push(NonNull::new_unchecked(ptr)); // Keep track of all allocated objects
         gc_handle
     }
 
     /// Allocates a Nil object on the GC heap.
-    pub fn allocate_nil(&mut self) -> Value {
+    pub fn allocate_nil(&self) -> Value {
         Value(unsafe { self.allocate(Object::Nil) })
     }
 
     /// Allocates a Boolean object on the GC heap.
-    pub fn allocate_boolean(&mut self, b: bool) -> Value {
+    pub fn allocate_boolean(&self, b: bool) -> Value {
         Value(unsafe { self.allocate(Object::Boolean(b)) })
     }
 
     /// Allocates a Number object on the GC heap.
-    pub fn allocate_number(&mut self, n: f64) -> Value {
+    pub fn allocate_number(&self, n: f64) -> Value {
         Value(unsafe { self.allocate(Object::Number(n)) })
     }
 
+    /// Allocates an Integer object on the GC heap.
+    pub fn allocate_integer(&self, i: i64) -> Value {
+        Value(unsafe { self.allocate(Object::Integer(i)) })
+    }
+
+    /// Allocates an integer too large for an i64 on the GC heap; one that fits is
+    /// allocated as a plain Integer, so each integer has a single representation.
+    pub fn allocate_big_integer(&self, b: BigInt) -> Value {
+        match b.to_i64() {
+            Some(i) => self.allocate_integer(i),
+            None => Value(unsafe { self.allocate(Object::BigInt(b)) }),
+        }
+    }
+
+    /// Allocates a Decimal object on the GC heap.
+    pub fn allocate_decimal(&self, d: Decimal) -> Value {
+        Value(unsafe { self.allocate(Object::Decimal(d)) })
+    }
+
     /// Allocates a String object on the GC heap.
-    pub fn allocate_string(&mut self, s: String) -> Value {
+    pub fn allocate_string(&self, s: String) -> Value {
         Value(unsafe { self.allocate(Object::String(s)) })
     }
 
+    /// Allocates a Bytes object on the GC heap.
+    pub fn allocate_bytes(&self, b: Vec<u8>) -> Value {
+        Value(unsafe { self.allocate(Object::Bytes(b)) })
+    }
+
     /// Allocates a List object on the GC heap.
-    pub fn allocate_list(&mut self, l: Vec<Value>) -> Value {
+    pub fn allocate_list(&self, l: Vec<Value>) -> Value {
         Value(unsafe { self.allocate(Object::List(l)) })
     }
 
+    /// Allocates a Tuple object on the GC heap.
+    pub fn allocate_tuple(&self, t: Vec<Value>) -> Value {
+        Value(unsafe { self.allocate(Object::Tuple(t)) })
+    }
+
     /// Allocates a Map object on the GC heap.
-    pub fn allocate_map(&mut self, m: HashMap<Value, Value>) -> Value {
-        Value(unsafe { self.allocate(Object::Map(m)) })
+    pub fn allocate_map(&self, m: OrderedMap<Value, Value>) -> Value {
+        Value(unsafe { self.allocate(Object::Map(MapInner::new(m))) })
+    }
+
+    /// Allocates a Set object on the GC heap.
+    pub fn allocate_set(&self, s: SetElements) -> Value {
+        Value(unsafe { self.allocate(Object::Set(s)) })
+    }
+
+    /// Allocates an Enum value on the GC heap.
+    pub fn allocate_enum(&self, e: EnumValue) -> Value {
+        Value(unsafe { self.allocate(Object::Enum(e)) })
     }
 
     /// Allocates a Function object on the GC heap.
-    pub fn allocate_function(&mut self, f: FunctionObjectInner) -> Value {
+    pub fn allocate_function(&self, f: FunctionObjectInner) -> Value {
         Value(unsafe { self.allocate(Object::Function(f)) })
     }
 
     /// Allocates a BoundMethod object on the GC heap.
-    pub fn allocate_bound_method(&mut self, bm: BoundMethodInner) -> Value {
+    pub fn allocate_bound_method(&self, bm: BoundMethodInner) -> Value {
         Value(unsafe { self.allocate(Object::BoundMethod(bm)) })
     }
 
+    /// Allocates an Iterator object on the GC heap.
+    pub fn allocate_iterator(&self, it: IteratorInner) -> Value {
+        Value(unsafe { self.allocate(Object::Iterator(it)) })
+    }
+
+    /// Allocates an empty Channel object on the GC heap.
+    pub fn allocate_channel(&self) -> Value {
+        Value(unsafe { self.allocate(Object::Channel(VecDeque::new())) })
+    }
+
     /// Triggers a garbage collection cycle. (Stop-the-World Mark-and-Sweep)
     /// `roots` are the starting points for tracing reachable objects.
-    pub fn collect(&mut self, roots: &[Value]) -> usize {
+    pub fn collect(&self, roots: &[Value]) -> usize {
+        self.collect_with(|heap| {
+            //    Trace from roots. Each Value is a GcRef<Object>, so we trace its payload.
+            for root in roots {
+                root.trace(heap); // Call the GcTrace for Value
+            }
+        })
+    }
+
+    /// Like `collect`, but `mark_roots` traces the roots itself, for roots that are not
+    /// plain values (environments, suspended coroutines).
+    pub fn collect_with(&self, mark_roots: impl FnOnce(&Heap)) -> usize {
         eprintln!(
             "[GC] Starting collection phase. {} objects on heap.",
-            self.objects.len()
+            self.objects.borrow().len()
         );
         // 1. Mark Phase:
         //    Reset all mark bits to false for the current sweep cycle.
         self.unmark_all();
-        //    Trace from roots. Each Value is a GcRef<Object>, so we trace its payload.
-        for root in roots {
-            root.trace(self); // Call the GcTrace for Value
-        }
+        mark_roots(self);
 
         // 2. Sweep Phase and return collected count
         self.sweep()
@@ -515,7 +1081,7 @@ impl Heap {
 
     /// Resets the mark bit for all objects on the heap to `false`.
     fn unmark_all(&self) {
-        for &ptr in &self.objects {
+        for &ptr in self.objects.borrow().iter() {
             unsafe {
                 ptr.as_ref().marked.replace(false);
             }
@@ -523,14 +1089,15 @@ impl Heap {
     }
 
     /// Sweeps through the heap, freeing unmarked objects.
-    fn sweep(&mut self) -> usize {
-        let before_count = self.objects.len();
+    fn sweep(&self) -> usize {
+        let mut objects = self.objects.borrow_mut();
+        let before_count = objects.len();
 
-        self.objects.retain(|&ptr| {
+        objects.retain(|&ptr| {
             unsafe {
                 // Get a reference to the header
                 let header = ptr.as_ref();
-                if *header.marked.borrow() == false {
+                if !*header.marked.borrow() {
                     // Object is not marked, so it's garbage. Deallocate.
                     let payload_layout = Self::layout_for_type(header.obj_type); // Get payload layout
 
@@ -550,36 +1117,29 @@ impl Heap {
             }
         });
 
-        let collected_count = before_count - self.objects.len();
+        let collected_count = before_count - objects.len();
         if collected_count > 0 {
             eprintln!(
                 "[GC] Swept and freed {} objects. {} remaining.",
                 collected_count,
-                self.objects.len()
+                objects.len()
             );
         }
         collected_count
     }
 
-    /// Helper to get layout for Object variants (needed for deallocation)
-    unsafe fn layout_for_type(obj_type: GcObjectType) -> Layout {
-        match obj_type {
-            GcObjectType::Number => Layout::new::<f64>(),
-            GcObjectType::Boolean => Layout::new::<bool>(),
-            GcObjectType::String => Layout::new::<String>(),
-            GcObjectType::Nil => Layout::new::<()>(), // Nil has no data in Object variant
-            GcObjectType::List => Layout::new::<Vec<Value>>(),
-            GcObjectType::Map => Layout::new::<HashMap<Value, Value>>(),
-            GcObjectType::Function => Layout::new::<FunctionObjectInner>(),
-            GcObjectType::BoundMethod => Layout::new::<BoundMethodInner>(),
-        }
+    /// Helper to get the payload layout (needed for deallocation).
+    /// `allocate` always writes a full `Object`, whatever its variant, so the layout
+    /// (and the payload offset derived from it) must match `Layout::new::<Object>()`.
+    unsafe fn layout_for_type(_obj_type: GcObjectType) -> Layout {
+        Layout::new::<Object>()
     }
 }
 
 impl Drop for Heap {
     fn drop(&mut self) {
         // Ensure all allocated memory is freed when the Heap is dropped.
-        for &ptr in &self.objects {
+        for &ptr in self.objects.get_mut().iter() {
             unsafe {
                 let header = ptr.as_ref();
                 let payload_layout = Self::layout_for_type(header.obj_type);
@@ -620,37 +1180,73 @@ impl GcTrace for Value {
 
 // Helper for nil, true, false, etc.
 impl Value {
-    pub fn nil(heap: &mut Heap) -> Value {
+    pub fn nil(heap: &Heap) -> Value {
         heap.allocate_nil()
     }
 
-    pub fn boolean(heap: &mut Heap, b: bool) -> Value {
+    pub fn boolean(heap: &Heap, b: bool) -> Value {
         heap.allocate_boolean(b)
     }
 
-    pub fn number(heap: &mut Heap, n: f64) -> Value {
+    pub fn number(heap: &Heap, n: f64) -> Value {
         heap.allocate_number(n)
     }
 
-    pub fn string(heap: &mut Heap, s: String) -> Value {
+    pub fn integer(heap: &Heap, i: i64) -> Value {
+        heap.allocate_integer(i)
+    }
+
+    pub fn big_integer(heap: &Heap, b: BigInt) -> Value {
+        heap.allocate_big_integer(b)
+    }
+
+    pub fn decimal(heap: &Heap, d: Decimal) -> Value {
+        heap.allocate_decimal(d)
+    }
+
+    pub fn string(heap: &Heap, s: String) -> Value {
         heap.allocate_string(s)
     }
 
-    pub fn list(heap: &mut Heap, l: Vec<Value>) -> Value {
+    pub fn bytes(heap: &Heap, b: Vec<u8>) -> Value {
+        heap.allocate_bytes(b)
+    }
+
+    pub fn list(heap: &Heap, l: Vec<Value>) -> Value {
         heap.allocate_list(l)
     }
 
-    pub fn map(heap: &mut Heap, m: HashMap<Value, Value>) -> Value {
+    pub fn tuple(heap: &Heap, t: Vec<Value>) -> Value {
+        heap.allocate_tuple(t)
+    }
+
+    pub fn map(heap: &Heap, m: OrderedMap<Value, Value>) -> Value {
         heap.allocate_map(m)
     }
 
-    pub fn function(heap: &mut Heap, f: FunctionObjectInner) -> Value {
+    pub fn set(heap: &Heap, s: SetElements) -> Value {
+        heap.allocate_set(s)
+    }
+
+    pub fn enum_value(heap: &Heap, e: EnumValue) -> Value {
+        heap.allocate_enum(e)
+    }
+
+    pub fn function(heap: &Heap, f: FunctionObjectInner) -> Value {
         heap.allocate_function(f)
     }
 
-    pub fn bound_method(heap: &mut Heap, bm: BoundMethodInner) -> Value {
+    pub fn bound_method(heap: &Heap, bm: BoundMethodInner) -> Value {
         heap.allocate_bound_method(bm)
     }
+
+    pub fn iterator(heap: &Heap, it: IteratorInner) -> Value {
+        heap.allocate_iterator(it)
+    }
+
+    pub fn channel(heap: &Heap) -> Value {
+        heap.allocate_channel()
+    }
 }
 
 impl fmt::Display for Value {
@@ -666,57 +1262,259 @@ impl Value {
         match self.0.deref() {
             Object::Nil => "nil",
             Object::Boolean(_) => "boolean",
-            Object::Number(_) => "number",
+            Object::Number(_) | Object::Integer(_) | Object::BigInt(_) => "number",
+            Object::Decimal(_) => "decimal",
             Object::String(_) => "string",
+            Object::Bytes(_) => "bytes",
             Object::List(_) => "list",
+            Object::Tuple(_) => "tuple",
             Object::Map(_) => "map",
+            Object::Set(_) => "set",
+            Object::Enum(_) => "enum",
             Object::Function(_) => "function",
             Object::BoundMethod(_) => "method",
+            Object::Iterator(_) => "iterator",
+            Object::Channel(_) => "channel",
+        }
+    }
+
+    /// Whether the value can be a map key or set element. These are hashed by value, so only
+    /// immutable values qualify: strings, numbers (decimals included), booleans, and tuples
+    /// whose elements all qualify.
+    pub fn is_hashable(&self) -> bool {
+        self.unhashable_part().is_none()
+    }
+
+    /// The value, or element nested in a tuple, that keeps this value from being hashed.
+    fn unhashable_part(&self) -> Option<&Value> {
+        match self.0.deref() {
+            Object::Tuple(items) => items.iter().find_map(Value::unhashable_part),
+            _ if matches!(self.type_of(), "string" | "number" | "decimal" | "boolean") => None,
+            _ => Some(self),
+        }
+    }
+
+    /// Checks that the value can be a map key.
+    pub fn expect_key(&self) -> Result<(), String> {
+        self.expect_hashable("Map keys")
+    }
+
+    /// Checks that the value can be a set element.
+    pub fn expect_element(&self) -> Result<(), String> {
+        self.expect_hashable("Set elements")
+    }
+
+    /// Every map and set operation that takes a key or element from a script goes through
+    /// here, so they all accept and reject the same values. `what` names them in the error.
+    fn expect_hashable(&self, what: &str) -> Result<(), String> {
+        let Some(part) = self.unhashable_part() else {
+            return Ok(());
+        };
+        let got = if self.type_of() == "tuple" {
+            format!("a tuple containing type '{}'", part.type_of())
+        } else {
+            format!("type '{}'", part.type_of())
+        };
+        Err(format!(
+            "{} must be strings, numbers, booleans or tuples of these, but got {}.",
+            what, got
+        ))
+    }
+
+    /// Looks up `key` in a map, falling back to its prototype chain. `None` for a missing
+    /// key or a value that is not a map.
+    pub fn lookup_property(&self, key: &Value) -> Option<Value> {
+        let mut current = self.clone();
+        loop {
+            let next = match current.0.deref() {
+                Object::Map(map) => match map.entries.get(key) {
+                    Some(value) => return Some(value.clone()),
+                    None => map.proto.clone()?,
+                },
+                _ => return None,
+            };
+            current = next;
+        }
+    }
+
+    /// `lookup_property` for a string key, without allocating a key value.
+    pub fn lookup_property_str(&self, name: &str) -> Option<Value> {
+        let mut current = self.clone();
+        loop {
+            let next = match current.0.deref() {
+                Object::Map(map) => match current.0.deref().map_get_str(name) {
+                    Some(value) => return Some(value.clone()),
+                    None => map.proto.clone()?,
+                },
+                _ => return None,
+            };
+            current = next;
+        }
+    }
+
+    /// Finds the function a map's prototype chain defines under a metamethod name such as
+    /// `__add`. `None` for other values, maps without a prototype and keys that do not hold a
+    /// callable. The map's own entries are data and never searched, so indexing a plain map
+    /// costs nothing extra.
+    pub fn metamethod(&self, name: &str) -> Option<Value> {
+        let Object::Map(map) = self.0.deref() else {
+            return None;
+        };
+        let mut current = map.proto.clone()?;
+        loop {
+            let next = match current.0.deref() {
+                Object::Map(map) => match current.0.deref().map_get_str(name) {
+                    Some(value) if value.is_callable() => return Some(value.clone()),
+                    Some(_) => return None,
+                    None => map.proto.clone()?,
+                },
+                _ => return None,
+            };
+            current = next;
+        }
+    }
+
+    /// The value of an integer argument such as an index or a count. `what` starts the error
+    /// message, e.g. "List index must be an integer. Got: 1.5".
+    pub fn expect_integer(&self, what: &str) -> Result<i64, String> {
+        self.0.deref().as_integer().ok_or_else(|| {
+            let got = if self.type_of() == "number" {
+                self.repr_string()
+            } else {
+                self.type_of().to_string()
+            };
+            format!("{} must be an integer. Got: {}", what, got)
+        })
+    }
+
+    /// Whether `freeze()` has made this value immutable.
+    pub fn is_frozen(&self) -> bool {
+        unsafe { *self.0.ptr.as_ref().frozen.borrow() }
+    }
+
+    /// Makes a list, map or set immutable, together with every list and map nested in it
+    /// (also through tuples and enum values, which are immutable already). Prototypes are shared, so they
+    /// are left alone.
+    pub fn freeze(&self) {
+        if self.is_frozen()
+            || !matches!(
+                self.0.deref(),
+                Object::List(_)
+                    | Object::Tuple(_)
+                    | Object::Map(_)
+                    | Object::Set(_)
+                    | Object::Enum(_)
+            )
+        {
+            return; // Already frozen (which also stops at cycles), or nothing to freeze
+        }
+        unsafe { self.0.ptr.as_ref().frozen.replace(true) };
+        match self.0.deref() {
+            Object::List(items) | Object::Tuple(items) => items.iter().for_each(Value::freeze),
+            Object::Enum(value) => value.fields.iter().for_each(Value::freeze),
+            Object::Map(map) => map.entries.values().for_each(Value::freeze),
+            _ => {}
         }
     }
 
+    /// Fails with "Cannot modify a frozen list." (or map) if the value is frozen.
+    pub fn ensure_mutable(&self) -> Result<(), String> {
+        if self.is_frozen() {
+            return Err(format!("Cannot modify a frozen {}.", self.type_of()));
+        }
+        Ok(())
+    }
+
+    /// Whether the value can be called like a function.
+    pub fn is_callable(&self) -> bool {
+        matches!(self.0.deref(), Object::Function(_) | Object::BoundMethod(_))
+    }
+
     /// Determines the truthiness of a value based on EasyScript's rules.
-    /// Falsy values are: nil, false, 0, "", [], and {}.
+    /// Falsy values are: nil, false, 0, "", b"", [], (), {} and #{}.
     /// All other values are truthy.
     pub fn is_truthy(&self) -> bool {
         match self.0.deref() {
             Object::Nil => false,
             Object::Boolean(b) => *b,
             Object::Number(n) => *n != 0.0,
+            Object::Integer(i) => *i != 0,
+            Object::BigInt(_) => true, // Never zero, which fits an i64
+            Object::Decimal(d) => !d.is_zero(),
             Object::String(s) => !s.is_empty(),
-            Object::List(l) => !l.is_empty(),
-            Object::Map(m) => !m.is_empty(),
+            Object::Bytes(b) => !b.is_empty(),
+            Object::List(l) | Object::Tuple(l) => !l.is_empty(),
+            Object::Map(m) => !m.entries.is_empty(),
+            Object::Set(s) => !s.is_empty(),
+            Object::Enum(_) => true,
             Object::Function(_) => true,
             Object::BoundMethod(_) => true, // Bound methods are always truthy
+            Object::Iterator(_) => true,
+            Object::Channel(_) => true,
         }
     }
 
     /// Returns a Python-like developer-friendly representation of the value (repr).
     pub fn repr_string(&self) -> String {
-        match self.0.deref() {
+        self.repr_on_path(&mut Vec::new())
+    }
+
+    /// repr, rendering a list or map already being rendered as `[...]` or `{...}`.
+    fn repr_on_path(&self, path: &mut Path) -> String {
+        let object = self.0.deref();
+        match object {
             Object::Nil => "nil".to_string(),
             Object::Boolean(b) => b.to_string(),
-            Object::Number(n) => {
-                if n.fract() == 0.0 {
-                    (*n as i64).to_string()
-                } else {
-                    n.to_string()
-                }
-            }
+            // A float always shows a fractional part, so repr tells 2.0 from 2.
+            Object::Number(n) => format_float(*n, true),
+            Object::Integer(i) => i.to_string(),
+            Object::BigInt(b) => b.to_string(),
+            Object::Decimal(d) => format!("decimal(\"{}\")", d),
             Object::String(s) => format!("{:?}", s), // Explicitly quote strings for repr
-            Object::List(list) => {
-                let elements: Vec<String> = list.iter().map(|item| item.repr_string()).collect();
+            Object::Bytes(b) => format_bytes(b),
+            Object::List(list) => with_visited(object, path, |path| {
+                let elements: Vec<String> =
+                    list.iter().map(|item| item.repr_on_path(path)).collect();
                 format!("[{}]", elements.join(", "))
-            }
-            Object::Map(map) => {
+            })
+            .unwrap_or_else(|| "[...]".to_string()),
+            Object::Tuple(items) => with_visited(object, path, |path| {
+                let elements: Vec<String> =
+                    items.iter().map(|item| item.repr_on_path(path)).collect();
+                let comma = if items.len() == 1 { "," } else { "" };
+                format!("({}{})", elements.join(", "), comma)
+            })
+            .unwrap_or_else(|| "(...)".to_string()),
+            Object::Map(map) => with_visited(object, path, |path| {
                 let pairs: Vec<String> = map
+                    .entries
                     .iter()
-                    .map(|(key, val)| format!("{}: {}", key.repr_string(), val.repr_string()))
+                    .map(|(key, val)| {
+                        format!("{}: {}", key.repr_on_path(path), val.repr_on_path(path))
+                    })
                     .collect();
                 format!("{{{}}}", pairs.join(", "))
+            })
+            .unwrap_or_else(|| "{...}".to_string()),
+            Object::Set(set) => {
+                let elements: Vec<String> =
+                    set.keys().map(|item| item.repr_on_path(path)).collect();
+                format!("#{{{}}}", elements.join(", "))
+            }
+            Object::Enum(value) if value.fields.is_empty() => value.variant.qualified_name(),
+            Object::Enum(value) => {
+                let fields: Vec<String> = value
+                    .fields
+                    .iter()
+                    .map(|field| field.repr_on_path(path))
+                    .collect();
+                format!("{}({})", value.variant.qualified_name(), fields.join(", "))
             }
             Object::Function(_) => "<function>".to_string(),
             Object::BoundMethod(_) => "<bound method>".to_string(),
+            Object::Iterator(IteratorInner::Generator(_)) => "<generator>".to_string(),
+            Object::Iterator(_) => "<iterator>".to_string(),
+            Object::Channel(_) => "<channel>".to_string(),
         }
     }
 }
@@ -724,6 +1522,34 @@ impl Value {
 // GcRef 句柄已经实现了 PartialEq, Eq, Hash，因此 Value 只需要派生这些特性。
 // 不需要再手动实现。
 
+impl Value {
+    /// Identity, for `is`. Lists, maps, functions and other objects with state are the same
+    /// only when they are one object. Nil, booleans, numbers, strings and bytes are immutable
+    /// and have no observable identity, so they are the same when they have the same kind and
+    /// value.
+    pub fn is_same(&self, other: &Value) -> bool {
+        let is_scalar = |object: &Object| {
+            matches!(
+                object,
+                Object::Nil
+                    | Object::Boolean(_)
+                    | Object::Number(_)
+                    | Object::Integer(_)
+                    | Object::BigInt(_)
+                    | Object::Decimal(_)
+                    | Object::String(_)
+                    | Object::Bytes(_)
+            )
+        };
+        let (a, b) = (self.0.deref(), other.0.deref());
+        if is_scalar(a) && is_scalar(b) {
+            std::mem::discriminant(a) == std::mem::discriminant(b) && a == b
+        } else {
+            self.0 == other.0
+        }
+    }
+}
+
 impl PartialEq for Value {
     fn eq(&self, other: &Self) -> bool {
         // Delegate to the Object's PartialEq implementation
@@ -738,3 +1564,58 @@ impl std::hash::Hash for Value {
         self.0.deref().hash(state);
     }
 }
+
+/// A map key as either a stored `Value` or a borrowed string, so that `map_get_str` can use
+/// the map's hash index without allocating a string value. A string hashes like the string
+/// value it equals (see `Object`'s `Hash`).
+pub trait MapKey {
+    fn as_str(&self) -> Option<&str>;
+    fn as_value(&self) -> Option<&Value>;
+}
+
+impl MapKey for Value {
+    fn as_str(&self) -> Option<&str> {
+        self.0.deref().as_string().map(String::as_str)
+    }
+
+    fn as_value(&self) -> Option<&Value> {
+        Some(self)
+    }
+}
+
+impl MapKey for &str {
+    fn as_str(&self) -> Option<&str> {
+        Some(self)
+    }
+
+    fn as_value(&self) -> Option<&Value> {
+        None
+    }
+}
+
+impl<'a> std::borrow::Borrow<dyn MapKey + 'a> for Value {
+    fn borrow(&self) -> &(dyn MapKey + 'a) {
+        self
+    }
+}
+
+impl PartialEq for dyn MapKey + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.as_value(), other.as_value()) {
+            (Some(a), Some(b)) => a == b,
+            _ => self.as_str().is_some() && self.as_str() == other.as_str(),
+        }
+    }
+}
+
+impl Eq for dyn MapKey + '_ {}
+
+impl std::hash::Hash for dyn MapKey + '_ {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        match (self.as_value(), self.as_str()) {
+            (Some(value), _) => value.hash(state),
+            (None, Some(s)) => s.hash(state),
+            (None, None) => {}
+        }
+    }
+}
@@ -0,0 +1,17 @@
+# Bytes hold binary data: `b"..."` literals take ASCII text and `\xHH` escapes, indexing gives
+# integers, and slicing and `+` give new bytes.
+
+let data = b"GIF\x00\xff\\";
+print(data, repr(b"say \x22hi\x22"), type(data), len(data), data.len());
+print(data[0], data[-2], data[1:3], data[::-1], data + b"!", bool(b""));
+print(71 in data, b"IF" in data, data == b"GIF\x00\xff\\", data == "GIF");
+
+let total = 0;
+for byte in b"\x01\x02\x03" {
+    total = total + byte;
+}
+print(total);
+# expect_stdout: b"GIF\x00\xff\\" b"say \x22hi\x22" bytes 6 6
+# expect_stdout: 71 255 b"IF" b"\\\xff\x00FIG" b"GIF\x00\xff\\!" false
+# expect_stdout: true true true false
+# expect_stdout: 6
@@ -0,0 +1,3 @@
+let data = b"abc";
+data[0] = 65;
+# expect_runtime_error: Cannot assign to an element of bytes. Bytes are immutable.
@@ -0,0 +1,12 @@
+# hex() and from_hex() convert bytes to and from hex digits; encode() and decode() convert
+# strings to and from their UTF-8 bytes.
+
+let encoded = "héllo".encode();
+print(encoded, encoded.hex(), len(encoded), len("héllo"));
+print(from_hex("00FFa0"), from_hex(""), from_hex(encoded.hex()) == encoded);
+print(encoded.decode(), encoded.decode("utf-8"), "x".encode("UTF-8"));
+b"\xff\xfe".decode()
+# expect_stdout: b"h\xc3\xa9llo" 68c3a96c6c6f 6 5
+# expect_stdout: b"\x00\xff\xa0" b"" true
+# expect_stdout: héllo héllo b"x"
+# expect_runtime_error: decode() found invalid UTF-8 at byte 0.
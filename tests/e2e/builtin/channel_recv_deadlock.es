@@ -0,0 +1,7 @@
+# Receiving in the main program when no task can ever send is an error, not a hang.
+let ch = channel();
+spawn(fun() { ch.send(1); });
+print(ch.recv());
+# expect_stdout: 1
+ch.recv();
+# expect_runtime_error: recv() would block forever
@@ -0,0 +1,51 @@
+# Channels pass values between tasks; recv() blocks until a value arrives.
+
+let jobs = channel();
+let results = channel();
+print(type(jobs));
+# expect_stdout: channel
+
+let worker = fun(id) {
+    for true {
+        let job = jobs.recv();
+        results.send("worker " + str(id) + " did " + str(job));
+        yield_now();
+    };
+};
+spawn(worker, 1);
+spawn(worker, 2);
+
+for job in [10, 20, 30] {
+    jobs.send(job);
+};
+print(jobs.len());
+# expect_stdout: 3
+
+print(results.recv());
+# expect_stdout: worker 1 did 10
+print(results.recv());
+# expect_stdout: worker 2 did 20
+print(results.recv());
+# expect_stdout: worker 1 did 30
+
+# A generator running inside a task can block the task.
+let received = fun(ch) {
+    for true {
+        yield ch.recv();
+    };
+};
+let numbers = channel();
+let sums = channel();
+spawn(fun() {
+    let total = 0;
+    for n in received(numbers).take(3) {
+        total = total + n;
+    };
+    sums.send(total);
+});
+numbers.send(1);
+sleep(1);
+numbers.send(2);
+numbers.send(3);
+print(sums.recv());
+# expect_stdout: 6
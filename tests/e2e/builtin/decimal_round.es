@@ -0,0 +1,18 @@
+# round() rounds or pads a decimal to a number of places; set_decimal_context() configures division.
+
+print(decimal("2.675").round(2), decimal("2.665").round(2), decimal("2.5").round(0));
+# expect_stdout: 2.68 2.66 2
+print(decimal("2.665").round(2, "half_up"), decimal("2.669").round(2, "down"), decimal("-2.661").round(2, "floor"));
+# expect_stdout: 2.67 2.66 -2.67
+print(decimal("2.661").round(2, "ceiling"), decimal("2.661").round(2, "up"), decimal("2.665").round(2, "half_down"));
+# expect_stdout: 2.67 2.67 2.66
+print(decimal("1.5").round(3));
+# expect_stdout: 1.500
+
+set_decimal_context(6, "down");
+print(decimal(2) / 3);
+# expect_stdout: 0.666666
+
+# The context's rounding is also round()'s default
+decimal("2.669").round(2)
+# expect: 2.66
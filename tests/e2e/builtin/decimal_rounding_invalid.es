@@ -0,0 +1,4 @@
+# Rounding modes are checked by name.
+
+decimal("1.25").round(1, "nearest")
+# expect_runtime_error: round() rounding must be one of half_even, half_up, half_down, up, down, ceiling, floor, but got "nearest".
@@ -0,0 +1,27 @@
+# Test freeze() and is_frozen()
+
+let config = freeze({"name": "plugin", "limits": {"max": 3}, "tags": ["a"]});
+print(is_frozen(config));
+# expect_stdout: true
+
+# Nested lists and maps are frozen too
+print(is_frozen(config.limits));
+# expect_stdout: true
+print(is_frozen(config.tags));
+# expect_stdout: true
+
+# Reading and deriving new values still works; the results are not frozen
+print(config.limits.max);
+# expect_stdout: 3
+let more = config.tags + ["b"];
+more.push("c");
+print(more);
+# expect_stdout: ["a", "b", "c"]
+print(is_frozen(config.tags.map(fun(t) { t })));
+# expect_stdout: false
+
+# Other values are returned unchanged
+print(freeze(5));
+# expect_stdout: 5
+print(is_frozen([]));
+# expect_stdout: false
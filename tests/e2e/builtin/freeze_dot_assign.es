@@ -0,0 +1,5 @@
+# Dot assignment cannot add keys to a frozen map
+
+let config = freeze({});
+config.debug = true
+# expect_runtime_error: Cannot modify a frozen map.
@@ -0,0 +1,5 @@
+# Index assignment into a nested map of a frozen map fails
+
+let config = freeze({"limits": {"max": 3}});
+config.limits["max"] = 4
+# expect_runtime_error: Cannot modify a frozen map.
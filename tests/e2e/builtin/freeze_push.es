@@ -0,0 +1,5 @@
+# List methods cannot modify a frozen list
+
+let tags = freeze(["a"]);
+tags.push("b")
+# expect_runtime_error: Cannot modify a frozen list.
@@ -0,0 +1,7 @@
+# A frozen set still answers queries and builds new sets, but cannot change.
+
+let s = freeze(#{1, 2});
+print(is_frozen(s), s.contains(2), s.union(#{3}));
+s.add(3)
+# expect_stdout: true true #{1, 2, 3}
+# expect_runtime_error: Cannot modify a frozen set.
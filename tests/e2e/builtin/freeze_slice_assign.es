@@ -0,0 +1,3 @@
+let xs = freeze([1, 2, 3]);
+xs[0:1] = [9];
+# expect_runtime_error: Cannot modify a frozen list.
@@ -0,0 +1,3 @@
+let m = freeze({"b": 1, "a": 2});
+m.sort_keys()
+# expect_runtime_error: Cannot modify a frozen map.
@@ -0,0 +1,2 @@
+from_hex("abc")
+# expect_runtime_error: from_hex() expected an even number of hex digits, but got 3.
@@ -0,0 +1,29 @@
+## Greets someone by name.
+##
+## The greeting defaults to "Hello".
+let greet = fun(name, greeting = "Hello", ...rest) {
+    greeting + ", " + name
+};
+
+let undocumented = fun(x) { x };
+
+let shapes = {
+    "area": fun(self, r) { 3 * r * r }
+};
+
+help(greet);
+help(undocumented);
+help(shapes.area);
+help(range);
+help([].push);
+help("".substring);
+# expect: nil
+# expect_stdout: greet(name[, greeting], ...rest)
+# expect_stdout:     Greets someone by name.
+# expect_stdout:
+# expect_stdout:     The greeting defaults to "Hello".
+# expect_stdout: undocumented(x)
+# expect_stdout: <anonymous>(self, r)
+# expect_stdout: range(start[, end][, step])
+# expect_stdout: list.push(element)
+# expect_stdout: string.substring(start[, end])
@@ -0,0 +1,2 @@
+help(42);
+# expect_runtime_error: help() expected a function, but got type 'number'.
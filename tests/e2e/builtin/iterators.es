@@ -0,0 +1,41 @@
+# Test native iterators and lazy adaptors
+
+print(range(4).collect());
+# expect_stdout: [0, 1, 2, 3]
+print(range(2, 5).collect());
+# expect_stdout: [2, 3, 4]
+print(range(10, 0, -3).collect());
+# expect_stdout: [10, 7, 4, 1]
+print(type(range(3)));
+# expect_stdout: iterator
+
+let it = iter([1, 2]);
+print(it.next());
+# expect_stdout: 1
+print(it.next());
+# expect_stdout: 2
+print(it.next());
+# expect_stdout: nil
+
+# Adaptors compose lazily: only the items that are needed get computed.
+let calls = 0;
+let squares = range(1000000).map(fun(x) { calls = calls + 1; x * x });
+print(squares.filter(fun(x) { x % 2 == 1 }).take(3).collect());
+# expect_stdout: [1, 9, 25]
+print(calls);
+# expect_stdout: 6
+
+print(iter(["a", "b", "c"]).enumerate().collect());
+# expect_stdout: [[0, "a"], [1, "b"], [2, "c"]]
+print(range(3).zip(["x", "y"]).collect());
+# expect_stdout: [[0, "x"], [1, "y"]]
+print(range(2).chain([7, 8]).collect());
+# expect_stdout: [0, 1, 7, 8]
+
+# for-in accepts iterators directly
+let total = 0;
+for x in range(5).map(fun(x) { x * 10 }) {
+    total = total + x;
+}
+print(total);
+# expect_stdout: 100
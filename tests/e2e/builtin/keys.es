@@ -2,6 +2,7 @@
 let my_map1 = {1: "a", 2: "b", 3: "c"}; # 键改为数字
 let keys1 = my_map1.keys();
 print(len(keys1)); # Should be 3
+print(keys1);
 
 let sum = 0;
 for k in keys1 {
@@ -13,6 +14,7 @@ print(sum); # Should be 6 (1+2+3)
 let my_map2 = {"a": 1, "b": 2};
 let keys2 = my_map2.keys();
 print(len(keys2)); # Should be 2
+print(keys2);
 
 # Test with an empty map
 let my_map3 = {};
@@ -22,7 +24,9 @@ print(keys3);     # Should be []
 
 # expect: nil
 # expect_stdout: 3
+# expect_stdout: [1, 2, 3]
 # expect_stdout: 6
 # expect_stdout: 2
+# expect_stdout: ["a", "b"]
 # expect_stdout: 0
 # expect_stdout: []
@@ -0,0 +1,4 @@
+# An error raised inside a callback surfaces from the method call
+
+[1, 2, 0].map(fun(x) { 10 / x })
+# expect_runtime_error: Division by zero.
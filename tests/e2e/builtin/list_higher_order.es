@@ -0,0 +1,55 @@
+# Test higher-order list methods: each calls back into a script function and returns a new value
+
+let nums = [3, 1, 4, 1, 5];
+
+print(nums.map(fun(x) { x * 2 }));
+# expect_stdout: [6, 2, 8, 2, 10]
+print(nums.filter(fun(x) { x > 2 }));
+# expect_stdout: [3, 4, 5]
+print(nums);
+# expect_stdout: [3, 1, 4, 1, 5]
+
+# reduce uses the first item when no initial value is given
+print(nums.reduce(fun(acc, x) { acc + x }));
+# expect_stdout: 14
+print(nums.reduce(fun(acc, x) { acc + str(x) }, ""));
+# expect_stdout: 31415
+print([].reduce(fun(acc, x) { acc + x }, 0));
+# expect_stdout: 0
+
+# sort_by is stable and leaves the receiver unchanged
+let words = ["pear", "fig", "apple", "kiwi"];
+print(words.sort_by(fun(w) { w.len() }));
+# expect_stdout: ["fig", "pear", "kiwi", "apple"]
+print(words.sort_by(fun(w) { w }));
+# expect_stdout: ["apple", "fig", "kiwi", "pear"]
+print(words);
+# expect_stdout: ["pear", "fig", "apple", "kiwi"]
+
+# any, all and find stop at the first decisive item
+let seen = [];
+print(nums.any(fun(x) { seen.push(x); x == 4 }));
+# expect_stdout: true
+print(seen);
+# expect_stdout: [3, 1, 4]
+print(nums.all(fun(x) { x > 0 }));
+# expect_stdout: true
+print(nums.all(fun(x) { x > 1 }));
+# expect_stdout: false
+print([].all(fun(x) { false }));
+# expect_stdout: true
+print(nums.find(fun(x) { x > 3 }));
+# expect_stdout: 4
+print(nums.find(fun(x) { x > 10 }));
+# expect_stdout: nil
+
+# Any callable works: natives, closures and bound methods
+print([1, 2].map(str));
+# expect_stdout: ["1", "2"]
+let offset = 100;
+print([1, 2].map(fun(x) { x + offset }));
+# expect_stdout: [101, 102]
+let out = [];
+[1, 2].map(out.push);
+print(out);
+# expect_stdout: [1, 2]
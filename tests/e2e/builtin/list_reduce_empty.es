@@ -0,0 +1,4 @@
+# reduce() needs an initial value to fold an empty list
+
+[].reduce(fun(acc, x) { acc + x })
+# expect_runtime_error: reduce() of an empty list with no initial value.
@@ -0,0 +1,4 @@
+# sort_by() keys must all be numbers or all be strings
+
+[1, "two", 3].sort_by(fun(x) { x })
+# expect_runtime_error: sort_by() keys must all have the same type, but got 'number' and 'string'.
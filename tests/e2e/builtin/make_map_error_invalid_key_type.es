@@ -5,4 +5,4 @@ let error_test_4_func = fun() {
     make_map([["a", 1], [["nested"], 2]]);
 };
 error_test_4_func(); # Call the function to trigger the runtime error
-# expect_runtime_error: make_map() map keys must be primitive types (String, Number, Boolean), but got 'list'.
+# expect_runtime_error: Map keys must be strings, numbers, booleans or tuples of these, but got type 'list'.
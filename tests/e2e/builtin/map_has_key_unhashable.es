@@ -0,0 +1,3 @@
+let m = {"a": 1};
+m.has_key(fun() { 1 })
+# expect_runtime_error: Map keys must be strings, numbers, booleans or tuples of these, but got type 'function'.
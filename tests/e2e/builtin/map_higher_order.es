@@ -0,0 +1,19 @@
+# Test higher-order map methods
+
+let prices = {"apple": 3, "kiwi": 5, "fig": 8};
+
+let doubled = prices.map_values(fun(v) { v * 2 });
+print(doubled);
+# expect_stdout: {"apple": 6, "kiwi": 10, "fig": 16}
+print(prices["fig"]);
+# expect_stdout: 8
+
+# filter passes each key and value
+let cheap = prices.filter(fun(k, v) { v < 6 });
+print(cheap);
+# expect_stdout: {"apple": 3, "kiwi": 5}
+print(cheap.has_key("fig"));
+# expect_stdout: false
+let short = prices.filter(fun(k, v) { k.len() == 3 });
+print(short);
+# expect_stdout: {"fig": 8}
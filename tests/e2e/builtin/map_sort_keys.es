@@ -0,0 +1,13 @@
+# sort_keys() reorders a map's entries by key, in place, and returns the map.
+
+let scores = {"carol": 7, "alice": 9, "bob": 8};
+print(scores.keys(), repr(scores));
+let same = scores.sort_keys();
+print(same is scores, scores);
+print({3: "c", 1.5: "b", 1: "a"}.sort_keys(), {}.sort_keys());
+scores["aaron"] = 1;
+scores.keys()
+# expect: ["alice", "bob", "carol", "aaron"]
+# expect_stdout: ["carol", "alice", "bob"] {"carol": 7, "alice": 9, "bob": 8}
+# expect_stdout: true {"alice": 9, "bob": 8, "carol": 7}
+# expect_stdout: {1: "a", 1.5: "b", 3: "c"} {}
@@ -0,0 +1,3 @@
+let m = {"a": 1, 2: "b"};
+m.sort_keys()
+# expect_runtime_error: sort_keys() keys must all have the same type, but got 'string' and 'number'.
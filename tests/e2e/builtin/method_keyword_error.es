@@ -0,0 +1,5 @@
+# Builtin methods bind keyword arguments against their declared parameters.
+
+let items = [1, 2];
+items.join(sep: ", ")
+# expect_runtime_error: join() got an unexpected keyword argument 'sep'.
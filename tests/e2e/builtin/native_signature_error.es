@@ -0,0 +1,4 @@
+# Global natives declare their signature, so their arity errors read like user functions'.
+
+len()
+# expect_runtime_error: len() missing required argument 'value'.
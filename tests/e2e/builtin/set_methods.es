@@ -0,0 +1,19 @@
+# set() builds a set from any iterable; the set methods never modify their argument.
+
+let a = set([1, 2, 3, 2]);
+let b = set(range(2, 5));
+print(a, b, set(), set({"x": 1, "y": 2}));
+print(a.union(b), a.intersection(b), a.difference(b));
+print(#{2, 3}.is_subset(a), a.is_subset(b), #{}.is_subset(a));
+print(a.contains(1), a.contains("1"), a.len());
+
+print(a.add(4), a.add(1), a);
+print(a.remove(1), a.remove(1), a);
+print(b);
+# expect_stdout: #{1, 2, 3} #{2, 3, 4} #{} #{"x", "y"}
+# expect_stdout: #{1, 2, 3, 4} #{2, 3} #{1}
+# expect_stdout: true false true
+# expect_stdout: true false 3
+# expect_stdout: nil nil #{1, 2, 3, 4}
+# expect_stdout: true false #{2, 3, 4}
+# expect_stdout: #{2, 3, 4}
@@ -0,0 +1,2 @@
+#{1}.union([2])
+# expect_runtime_error: union() expected a set, but got type 'list'.
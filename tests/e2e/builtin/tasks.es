@@ -0,0 +1,40 @@
+# Spawned tasks run cooperatively, switching only when a task blocks.
+
+let worker = fun(name, steps) {
+    let i = 0;
+    for i < steps {
+        print(name + " " + str(i));
+        yield_now();
+        i = i + 1;
+    };
+};
+
+spawn(worker, "a", 2);
+spawn(worker, "b", 3);
+print("spawned");
+# expect_stdout: spawned
+
+# The main program takes part in scheduling when it yields.
+yield_now();
+# expect_stdout: a 0
+# expect_stdout: b 0
+print("main");
+# expect_stdout: main
+
+# Sleepers wake in deadline order on a virtual clock, ties in the order they slept.
+spawn(fun() { sleep(20); print("slept 20"); });
+spawn(fun() { sleep(5); print("slept 5"); });
+spawn(fun() { sleep(5); print("slept 5 again"); });
+sleep(10);
+# expect_stdout: a 1
+# expect_stdout: b 1
+# expect_stdout: b 2
+# expect_stdout: slept 5
+# expect_stdout: slept 5 again
+print("main woke");
+# expect_stdout: main woke
+
+# Tasks still running when the program ends are run to completion.
+print("end of main");
+# expect_stdout: end of main
+# expect_stdout: slept 20
@@ -2,6 +2,7 @@
 let my_map1 = {"a": 10, "b": 20, "c": 30};
 let values1 = my_map1.values();
 print(len(values1)); # Should be 3
+print(values1);
 
 let sum = 0;
 for v in values1 {
@@ -13,6 +14,7 @@ print(sum); # Should be 60 (10+20+30)
 let my_map2 = {1: "hello", 2: "world"};
 let values2 = my_map2.values();
 print(len(values2)); # Should be 2
+print(values2);
 
 # Test with an empty map
 let my_map3 = {};
@@ -22,7 +24,9 @@ print(values3);     # Should be []
 
 # expect: nil
 # expect_stdout: 3
+# expect_stdout: [10, 20, 30]
 # expect_stdout: 60
 # expect_stdout: 2
+# expect_stdout: ["hello", "world"]
 # expect_stdout: 0
 # expect_stdout: []
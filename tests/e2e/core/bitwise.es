@@ -28,11 +28,11 @@ let g = 40 >> 2;
 print(g);
 # expect_stdout: 10
 
-let h = 5.9 & 3.1; # (5 & 3)
+let h = int(5.9) & int(3.1); # (5 & 3)
 print(h);
 # expect_stdout: 1
 
-let i = 10.9 << 1.1; # (10 << 1)
+let i = int(10.9) << int(1.1); # (10 << 1)
 print(i);
 # expect_stdout: 20
 
@@ -58,5 +58,5 @@ print(n);
 
 # The actual final expression that will be returned by the script.
 # This value will be compared against the final #expect.
-n # This will be the last expression, and its value is -3.0
+n # This will be the last expression, and its value is -3
 # expect: -3
\ No newline at end of file
@@ -0,0 +1,4 @@
+# Bitwise operators only work on integers.
+
+5.5 & 3
+# expect_runtime_error: Operator '&' requires integers, but got 5.5 and 3.
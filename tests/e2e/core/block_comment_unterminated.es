@@ -0,0 +1,7 @@
+# A block comment must be closed, counting the ones nested inside it.
+
+let x = 1;
+#[ outer
+#[ inner ]#
+x
+# expect_runtime_error: Unterminated block comment.
@@ -0,0 +1,5 @@
+# `#{` starts a set literal rather than a comment, so a comment that starts with `{` needs a
+# space after the `#`.
+# {todo: this line is a comment}
+#{todo}
+# expect_runtime_error: Undefined variable 'todo'
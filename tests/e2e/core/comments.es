@@ -0,0 +1,15 @@
+# Line comments, block comments and doc comments are all ignored when running.
+
+let total = 0;
+#[
+total = total + 100;
+#[ Block comments nest, so a region that already contains one
+   can be commented out as a whole. ]#
+total = total + 1000;
+]#
+total = total + #[ inline ]# 5;
+
+## Doc comments document the binding below them.
+let double = fun(x) { x * 2 };
+[total, double(total)]
+# expect: [5, 10]
@@ -0,0 +1,78 @@
+# Compound assignment operators combine the current value with the right-hand side.
+
+let a = 10;
+a += 5;
+print(a);
+# expect_stdout: 15
+a -= 3;
+print(a);
+# expect_stdout: 12
+a *= 2;
+print(a);
+# expect_stdout: 24
+a //= 8;
+print(a);
+# expect_stdout: 3
+a %= 2;
+print(a);
+# expect_stdout: 1
+a <<= 4;
+print(a);
+# expect_stdout: 16
+a >>= 2;
+print(a);
+# expect_stdout: 4
+a |= 3;
+print(a);
+# expect_stdout: 7
+a &= 5;
+print(a);
+# expect_stdout: 5
+a ^= 1;
+print(a);
+# expect_stdout: 4
+
+let s = "ab";
+s += "cd";
+print(s);
+# expect_stdout: abcd
+
+# Index and dot targets work the same way.
+let list = [1, 2, 3];
+list[1] += 40;
+print(list);
+# expect_stdout: [1, 42, 3]
+
+let m = {"count": 1};
+m["count"] *= 10;
+m.count -= 3;
+print(m.count);
+# expect_stdout: 7
+
+# The target and index are evaluated exactly once.
+let calls = 0;
+let counters = [0, 0];
+let pick = fun() {
+    calls += 1;
+    counters
+};
+let first = fun() {
+    calls += 1;
+    0
+};
+pick()[first()] += 5;
+print(counters);
+# expect_stdout: [5, 0]
+print(calls);
+# expect_stdout: 2
+
+let holder = {"inner": {"n": 1}};
+let get_inner = fun() {
+    calls += 1;
+    holder.inner
+};
+get_inner().n += 1;
+print(holder.inner.n);
+# expect_stdout: 2
+print(calls);
+# expect_stdout: 3
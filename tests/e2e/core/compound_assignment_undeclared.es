@@ -0,0 +1,4 @@
+# Compound assignment needs an existing variable to read from.
+
+missing += 1;
+# expect_runtime_error: Cannot assign to undeclared variable 'missing'. Use 'let' to declare it.
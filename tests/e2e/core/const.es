@@ -0,0 +1,26 @@
+# Test const declarations
+
+const LIMIT = 10;
+print(LIMIT * 2);
+# expect_stdout: 20
+
+# const returns its value, like let
+print(const NAME = "cfg");
+# expect_stdout: cfg
+
+# An inner scope can shadow a constant with its own binding
+let f = fun() {
+    let LIMIT = 1;
+    LIMIT = LIMIT + 1;
+    LIMIT
+};
+print(f());
+# expect_stdout: 2
+print(LIMIT);
+# expect_stdout: 10
+
+# const only protects the binding; use freeze() to protect the value
+const items = [1];
+items.push(2);
+print(items);
+# expect_stdout: [1, 2]
@@ -0,0 +1,6 @@
+# A constant cannot be reassigned, even from a nested function
+
+const LIMIT = 10;
+let bump = fun() { LIMIT += 1; };
+bump()
+# expect_runtime_error: Cannot assign to constant 'LIMIT'.
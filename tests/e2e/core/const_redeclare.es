@@ -0,0 +1,5 @@
+# A constant cannot be redeclared in its own scope
+
+const LIMIT = 10;
+let LIMIT = 11
+# expect_runtime_error: Cannot redeclare constant 'LIMIT'.
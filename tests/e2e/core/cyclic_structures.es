@@ -0,0 +1,39 @@
+# Lists and maps that contain themselves print, repr and compare without recursing forever.
+# A structure met again while it is still being rendered is shown as [...] or {...}.
+
+let xs = [1];
+xs.push(xs);
+print(xs, repr(xs), str(xs));
+
+# Mutually referencing maps (each has a single key, so the output order is fixed).
+let a = {};
+let b = {};
+a.peer = b;
+b.peer = a;
+print(a, repr(b));
+
+# Revisiting a structure that is not an ancestor is not a cycle.
+let shared = [0];
+print([shared, shared]);
+
+# Two cycles are equal when unrolling them never finds a difference.
+let ys = [1];
+ys.push(ys);
+let zs = [1, [1]];
+zs[1].push(zs);
+let other = [2];
+other.push(other);
+print(xs == xs, xs == ys, xs == zs, xs == other);
+
+let c = {};
+let d = {};
+c.peer = d;
+d.peer = c;
+let e = {"peer": {}};
+e.peer.peer = 1;
+[a == c, a == b, a == e]
+# expect: [true, true, false]
+# expect_stdout: [1, [...]] [1, [...]] [1, [...]]
+# expect_stdout: {"peer": {"peer": {...}}} {"peer": {"peer": {...}}}
+# expect_stdout: [[0], [0]]
+# expect_stdout: true true true false
@@ -0,0 +1,4 @@
+# Decimal arithmetic refuses floats, which would bring binary rounding back in.
+
+decimal("1.10") + 0.1
+# expect_runtime_error: Operator '+' cannot mix a decimal with a float, but got decimal("1.10") and 0.1. Convert one with decimal() or float().
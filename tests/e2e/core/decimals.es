@@ -0,0 +1,39 @@
+# Decimals are exact: no binary rounding error, and trailing zeros are kept.
+
+let price = decimal("12.30");
+print(price, repr(price), type(price));
+# expect_stdout: 12.30 decimal("12.30") decimal
+
+print(decimal("0.1") + decimal("0.2"), decimal("0.1") + decimal("0.2") == decimal("0.3"));
+# expect_stdout: 0.3 true
+
+# Integers mix in exactly; multiplication keeps every digit
+print(price * 3, price * decimal("0.15"), price - 20);
+# expect_stdout: 36.90 1.8450 -7.70
+
+# Division rounds to 28 significant digits unless the result is exact
+print(price / 2, decimal(1) / 3, decimal(2) / 3);
+# expect_stdout: 6.15 0.3333333333333333333333333333 0.6666666666666666666666666667
+print(decimal("10.5") // 4, decimal("10.5") % 4);
+# expect_stdout: 2 2.5
+
+# Comparisons are exact, against integers and floats too
+print(price == decimal("12.3"), price > 12, decimal("0.5") == 0.5, price == 12.3);
+# expect_stdout: true true true false
+
+# Equal numbers are the same map key, whatever their kind
+let totals = {};
+totals[decimal("2.50")] = "two and a half";
+print(totals[2.5], totals[decimal("2.5")]);
+# expect_stdout: two and a half two and a half
+
+print(int(decimal("-3.99")), float(decimal("0.25")), decimal(0.1), decimal(7), decimal("abc"));
+# expect_stdout: -3 0.25 0.1 7 nil
+
+# Big integers work as usual: 30! is exact
+let factorial = 1;
+for i in range(1, 31) {
+    factorial *= i;
+}
+factorial
+# expect: 265252859812191058636308480000000
@@ -0,0 +1,41 @@
+# `defer expr` runs expr when the enclosing block exits, last registered first, in the
+# block's scope: it sees the variables' values at exit time.
+
+let log = [];
+let work = fun(name) {
+    log.push("open " + name);
+    defer log.push("close " + name);
+    let step = "start";
+    defer log.push("last step: " + step);
+    step = "done";
+    name + "!"
+};
+print(work("a"));
+print(log);
+
+# A loop body is a block, so a defer in it runs at the end of each iteration.
+for i in range(2) {
+    defer print("end of iteration", i);
+    print("iteration", i);
+};
+
+# A defer also runs when its block fails; the original error is kept.
+let risky = fun() {
+    defer print("cleanup 1");
+    defer undefined_function();
+    defer print("cleanup 2");
+    print("before error");
+    1 // 0;
+    print("not reached");
+};
+risky();
+# expect_stdout: a!
+# expect_stdout: ["open a", "last step: done", "close a"]
+# expect_stdout: iteration 0
+# expect_stdout: end of iteration 0
+# expect_stdout: iteration 1
+# expect_stdout: end of iteration 1
+# expect_stdout: before error
+# expect_stdout: cleanup 2
+# expect_stdout: cleanup 1
+# expect_runtime_error: Division by zero
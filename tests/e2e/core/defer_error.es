@@ -0,0 +1,13 @@
+# When the block itself succeeds, the first failing deferred expression is the error, and
+# the remaining ones still run.
+
+let f = fun() {
+    defer print("still runs");
+    defer missing_name;
+    print("body");
+    1
+};
+f();
+# expect_stdout: body
+# expect_stdout: still runs
+# expect_runtime_error: Undefined variable 'missing_name'
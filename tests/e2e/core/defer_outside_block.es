@@ -0,0 +1,4 @@
+# `defer` is a statement of a block, not an expression.
+
+let x = defer print("no");
+# expect_runtime_error: 'defer' can only be used directly inside a block.
@@ -0,0 +1,7 @@
+# A variant constructor takes exactly one argument per field.
+
+enum Shape { Circle(r), Rect(w, h) };
+print(Shape.Rect(1, 2));
+Shape.Rect(1);
+# expect_stdout: Shape.Rect(1, 2)
+# expect_runtime_error: Shape.Rect() expected 2 arguments (w, h), but got 1
@@ -0,0 +1,4 @@
+# Variant names within one enum must be distinct.
+
+enum Color { Red, Green, Red };
+# expect_runtime_error: Duplicate variant 'Red' in enum 'Color'.
@@ -0,0 +1,37 @@
+# `enum` declares tagged values; `match` dispatches on them by variant, binding fields.
+
+enum Shape {
+    Circle(r),
+    Rect(w, h),
+    Empty,
+};
+let area = fun(shape) {
+    match shape {
+        Shape.Circle(r) => 3 * r * r,
+        Shape.Rect(w, h) if w == h => { w * w },
+        Shape.Rect(w, h) => w * h,
+        Shape.Empty => 0,
+    }
+};
+let c = Shape.Circle(2);
+print(c, Shape.Empty, c.r, type(c));
+print(area(c), area(Shape.Rect(2, 3)), area(Shape.Rect(4, 4)), area(Shape.Empty));
+print(c == Shape.Circle(2), c == Shape.Circle(3), Shape.Empty == Shape.Empty);
+print([Shape.Rect(1, Shape.Empty)]);
+let describe = fun(x) {
+    match x {
+        0 => "zero",
+        "hi" => "greeting",
+        Shape.Circle(_) => "a circle",
+        n if n > 10 => "big",
+        _ => "other",
+    }
+};
+print(describe(0), describe("hi"), describe(Shape.Circle(1)), describe(11), describe(5));
+match 5 { 1 => "one", 2 => "two" };
+# expect_stdout: Shape.Circle(2) Shape.Empty 2 enum
+# expect_stdout: 12 6 16 0
+# expect_stdout: true false true
+# expect_stdout: [Shape.Rect(1, Shape.Empty)]
+# expect_stdout: zero greeting a circle big other
+# expect_runtime_error: No match arm matches 5.
@@ -0,0 +1,8 @@
+# Reading a field the variant does not declare is an error.
+
+enum Shape { Circle(r), Rect(w, h) };
+let c = Shape.Circle(1);
+print(c.r);
+c.w;
+# expect_stdout: 1
+# expect_runtime_error: Shape.Circle has no field 'w'.
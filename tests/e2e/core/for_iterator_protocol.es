@@ -0,0 +1,62 @@
+# Test the iterator protocol for user-defined objects
+
+# An object with a `next` method is an iterator: `next()` returns nil when done. Like any
+# method, it gets the object as `self`.
+let countdown = fun(n) {
+    let current = n;
+    {"next": fun(self) {
+        if current > 0 {
+            current = current - 1;
+            current + 1
+        }
+    }}
+};
+print(for x in countdown(3) { x });
+# expect_stdout: [3, 2, 1]
+
+# An object with an `__iter` method is iterable: `__iter()` returns a fresh iterator.
+let pair = {
+    "first": "a",
+    "second": "b",
+    "__iter": fun(self) { iter([self.first, self.second]) }
+};
+print(for x in pair { x + "!" });
+# expect_stdout: ["a!", "b!"]
+print(for x in pair { x });
+# expect_stdout: ["a", "b"]
+
+# Protocol objects work with the native adaptors too.
+print(iter(countdown(5)).filter(fun(x) { x > 2 }).collect());
+# expect_stdout: [5, 4, 3]
+
+# `next` and `__iter` may come from the prototype chain, like any method.
+let Counter = {
+    "next": fun(self) {
+        if self.current < self.limit {
+            self.current = self.current + 1;
+            self.current
+        }
+    }
+};
+let counter = fun(limit) { set_proto({"current": 0, "limit": limit}, Counter) };
+print(for x in counter(3) { x });
+# expect_stdout: [1, 2, 3]
+
+let Range = {"__iter": fun(self) { counter(self.size) }};
+print(for x in set_proto({"size": 2}, Range) { x * 10 });
+# expect_stdout: [10, 20]
+
+# A `next` assigned after the object is made works the same.
+let it = {"items": ["x", "y"], "index": 0};
+it.next = fun(self) {
+    if self.index < len(self.items) {
+        self.index = self.index + 1;
+        self.items[self.index - 1]
+    }
+};
+print(for x in it { x });
+# expect_stdout: ["x", "y"]
+
+# Plain maps still iterate over their keys.
+print(for k in {"only": 1} { k });
+# expect_stdout: ["only"]
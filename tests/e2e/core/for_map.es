@@ -1,12 +1,13 @@
+# Iterating a map visits its keys in insertion order.
+
+let visited = [];
 let sum_values = 0;
-let my_map = {"a": 10, "b": 20};
+let my_map = {"b": 20, "a": 10};
+my_map["c"] = 30;
+my_map["b"] = 25; # Reassigning a key keeps its position
 for k in my_map {
-    if k == "a" {
-        sum_values = sum_values + 10;
-    };
-    if k == "b" {
-        sum_values = sum_values + 20;
-    };
+    visited.push(k);
+    sum_values = sum_values + my_map[k];
 }
-sum_values
-# expect: 30
\ No newline at end of file
+[visited, sum_values]
+# expect: [["b", "a", "c"], 65]
@@ -0,0 +1,3 @@
+# Test that iterating over a non-iterable value is a runtime error
+for x in 42 { x }
+# expect_runtime_error: Cannot iterate over type 'number'.
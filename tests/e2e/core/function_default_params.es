@@ -0,0 +1,29 @@
+# Parameters with a default may be omitted; defaults are evaluated at call time.
+
+let greet = fun(name, greeting = "Hello", punct = "!") {
+    greeting + ", " + name + punct
+};
+print(greet("Ann"));
+# expect_stdout: Hello, Ann!
+print(greet("Ann", "Hi"));
+# expect_stdout: Hi, Ann!
+print(greet("Ann", "Hi", "?"));
+# expect_stdout: Hi, Ann?
+
+# A default can refer to earlier parameters and to the enclosing scope.
+let base = 100;
+let offset = fun(x, by = x + base) { x + by };
+print(offset(1));
+# expect_stdout: 102
+print(offset(1, 2));
+# expect_stdout: 3
+
+# Each call evaluates the default again, so calls do not share a list.
+let append = fun(item, into = []) {
+    into.push(item);
+    into
+};
+print(append(1));
+# expect_stdout: [1]
+print(append(2));
+# expect_stdout: [2]
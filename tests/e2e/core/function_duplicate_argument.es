@@ -0,0 +1,5 @@
+# A parameter cannot be given both positionally and by keyword.
+
+let greet = fun(name) { "Hello, " + name };
+greet("Ann", name: "Bob")
+# expect_runtime_error: greet() got multiple values for argument 'name'.
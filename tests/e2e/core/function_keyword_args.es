@@ -0,0 +1,39 @@
+# Arguments can be passed by parameter name after the positional ones.
+
+let connect = fun(host, port = 80, secure = false) {
+    host + ":" + str(port) + (if secure { " (tls)" } else { "" })
+};
+print(connect("example.org", secure: true));
+# expect_stdout: example.org:80 (tls)
+print(connect(port: 8080, host: "localhost"));
+# expect_stdout: localhost:8080
+
+# Parameters after a rest parameter can only be passed by keyword.
+let join_all = fun(...parts, sep = ", ") { parts.join(sep) };
+print(join_all("a", "b", "c"));
+# expect_stdout: a, b, c
+print(join_all("a", "b", sep: "-"));
+# expect_stdout: a-b
+
+# Natives with a declared signature accept keywords as well.
+print(1, 2, 3, sep: " | ");
+# expect_stdout: 1 | 2 | 3
+print(range(0, 10, step: 4).collect());
+# expect_stdout: [0, 4, 8]
+
+# So do builtin methods, whose receiver is `self`.
+print([3, 1, 2].sort_by(key: fun(x) { -x }), [1, 2].join(separator: "+"));
+# expect_stdout: [3, 2, 1] 1+2
+print("hello".substring(start: 1, end: 3), [1, 2, 3].reduce(fun(a, b) { a + b }, initial: 10));
+# expect_stdout: el 16
+
+# Generators bind their arguments the same way.
+let countdown = fun(from, step = 1) {
+    let i = from;
+    for i > 0 {
+        yield i;
+        i -= step;
+    };
+};
+print(for n in countdown(6, step: 2) { n });
+# expect_stdout: [6, 4, 2]
@@ -0,0 +1,5 @@
+# Arity errors name the function and the missing parameter.
+
+let add = fun(x, y) { x + y };
+add(1)
+# expect_runtime_error: add() missing required argument 'y'.
@@ -0,0 +1,4 @@
+# Parameters without a default cannot follow ones that have a default.
+
+let f = fun(x = 1, y) { x + y };
+# expect_runtime_error: Parameter 'y' without a default cannot follow a parameter with a default.
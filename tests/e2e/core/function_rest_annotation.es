@@ -0,0 +1,4 @@
+# A rest parameter is always a list, so it takes no annotation.
+
+let total = fun(...values: list) { len(values) };
+# expect_runtime_error: Rest parameter '...values' cannot have a type annotation; it is always a list.
@@ -0,0 +1,29 @@
+# `...rest` collects the remaining positional arguments; `...list` spreads a list at a call site.
+
+let sum = fun(first, ...rest) {
+    let total = first;
+    for x in rest {
+        total += x;
+    };
+    total
+};
+print(sum(1));
+# expect_stdout: 1
+print(sum(1, 2, 3, 4));
+# expect_stdout: 10
+
+let numbers = [5, 6, 7];
+print(sum(...numbers));
+# expect_stdout: 18
+print(sum(0, ...numbers, 100));
+# expect_stdout: 118
+
+let describe = fun(...items) { items };
+print(describe());
+# expect_stdout: []
+print(describe(...[], 1, ...[2, 3]));
+# expect_stdout: [1, 2, 3]
+
+# Spreading works for natives too.
+print(...["a", "b", "c"]);
+# expect_stdout: a b c
@@ -0,0 +1,5 @@
+# Too many positional arguments are reported with the accepted range.
+
+let scale = fun(x, factor = 2) { x * factor };
+scale(1, 2, 3)
+# expect_runtime_error: scale() takes 1 to 2 arguments but got 3.
@@ -0,0 +1,5 @@
+# Keyword arguments must name a parameter.
+
+let greet = fun(name) { "Hello, " + name };
+greet("Ann", greting: "Hi")
+# expect_runtime_error: greet() got an unexpected keyword argument 'greting'.
@@ -0,0 +1,10 @@
+# Errors raised inside a generator body surface at the call to next().
+let bad = fun() {
+    yield 1;
+    missing_variable
+};
+let g = bad();
+print(g.next());
+# expect_stdout: 1
+g.next();
+# expect_runtime_error: Undefined variable 'missing_variable'
@@ -0,0 +1,46 @@
+# Every started generator or task has a stack of its own. Stacks start small and grow as the
+# body recurses, so many can be alive at once and each can still recurse deeply.
+let counter = fun(start) {
+    yield start;
+    yield start + 1;
+};
+let live = [];
+let i = 0;
+for i < 3000 {
+    let g = counter(i);
+    g.next();
+    live.push(g);
+    i = i + 1;
+};
+print(len(live));
+# expect_stdout: 3000
+print(live[2999].next());
+# expect_stdout: 3000
+
+let depth = fun(n) {
+    if n == 0 {
+        yield_now();
+        0
+    } else {
+        1 + depth(n - 1)
+    }
+};
+
+# A task that blocks at the bottom of a deep recursion resumes there.
+let results = [];
+spawn(fun() { results.push(depth(120)); });
+spawn(fun() { results.push(depth(60)); });
+yield_now();
+yield_now();
+print(results);
+# expect_stdout: [120, 60]
+
+let steps = fun(n) {
+    let total = 0;
+    for total < 3 * n {
+        yield total;
+        total = total + depth(n);
+    };
+};
+print(for x in steps(100) { x });
+# expect_stdout: [0, 100, 200]
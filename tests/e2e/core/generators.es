@@ -0,0 +1,80 @@
+# Functions containing `yield` return a generator that runs lazily.
+
+let count_up = fun(limit) {
+    let i = 0;
+    for i < limit {
+        yield i;
+        i = i + 1;
+    };
+};
+print(for x in count_up(3) { x * 10 });
+# expect_stdout: [0, 10, 20]
+
+let g = count_up(2);
+print(g);
+# expect_stdout: <generator>
+print(type(g));
+# expect_stdout: iterator
+print(g.next());
+# expect_stdout: 0
+print(g.next());
+# expect_stdout: 1
+print(g.next());
+# expect_stdout: nil
+print(g.next());
+# expect_stdout: nil
+
+# The body only runs as values are requested, and keeps its variables between resumptions.
+let noisy = fun() {
+    print("start");
+    yield 1;
+    print("resumed");
+    yield;
+    print("done");
+};
+let n = noisy();
+print("created");
+# expect_stdout: created
+print(n.next());
+# expect_stdout: start
+# expect_stdout: 1
+print(n.next());
+# expect_stdout: resumed
+# expect_stdout: nil
+print(n.next());
+# expect_stdout: done
+# expect_stdout: nil
+
+# Infinite generators work with the lazy adaptors.
+let naturals = fun() {
+    let k = 0;
+    for true {
+        yield k;
+        k = k + 1;
+    };
+};
+print(naturals().filter(fun(x) { x % 2 == 0 }).take(4).collect());
+# expect_stdout: [0, 2, 4, 6]
+
+# Recursive generators: flatten a tree.
+let walk = fun(tree) {
+    if type(tree) == "list" {
+        for child in tree {
+            for leaf in walk(child) {
+                yield leaf;
+            };
+        };
+    } else {
+        yield tree;
+    };
+};
+print(walk([1, [2, [3, 4]], 5]).collect());
+# expect_stdout: [1, 2, 3, 4, 5]
+
+# The value of a `yield` expression is nil when resumed by next().
+let echo = fun() {
+    let got = yield "first";
+    yield got;
+};
+print(echo().collect());
+# expect_stdout: ["first", nil]
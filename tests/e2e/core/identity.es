@@ -0,0 +1,27 @@
+# `is` tells whether two values are the same object, while `==` compares contents.
+
+let config = {"debug": false};
+let alias = config;
+let copy = {"debug": false};
+print(alias is config, copy is config, copy == config);
+
+# Mutating through one name is visible through the other only when they alias.
+alias.debug = true;
+print(config.debug, copy.debug);
+
+let xs = [1, 2];
+print(xs is xs, xs is [1, 2], xs[:] is xs);
+
+# Immutable values have no identity of their own: they are the same when equal in kind and value.
+print(nil is nil, 1 is 1, 1 is 1.0, "a" is "a", true is false);
+
+# `is` ignores `__eq`, which only customizes `==`.
+let always_equal = {"__eq": fun(a, b) { true }};
+let a = set_proto({}, always_equal);
+let b = set_proto({}, always_equal);
+[a == b, a is b, 2 in [1, 1 + 1] && !(3 in [1, 2])]
+# expect: [true, false, true]
+# expect_stdout: true false true
+# expect_stdout: true false
+# expect_stdout: true false false
+# expect_stdout: true true false true false
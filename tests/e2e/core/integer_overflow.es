@@ -0,0 +1,17 @@
+# Integer arithmetic that leaves the 64-bit range promotes to a big integer instead of wrapping.
+
+let big = 9223372036854775807;
+print(big + 1, type(big + 1));
+# expect_stdout: 9223372036854775808 number
+print(-big - 2);
+# expect_stdout: -9223372036854775809
+print(1 << 63);
+# expect_stdout: 9223372036854775808
+print(1 << 64, 1 << 200);
+# expect_stdout: 18446744073709551616 1606938044258990275541962092341162602522202993782792835301376
+print((1 << 200) >> 199, 5 >> 64, -5 >> 100);
+# expect_stdout: 2 0 -1
+
+# Results that fit again are ordinary integers
+(big + 1) - 1 == big
+# expect: true
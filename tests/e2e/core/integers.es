@@ -0,0 +1,44 @@
+# Integer literals stay integers; a decimal point makes a float.
+
+print(repr(7), repr(7.0), type(7), type(7.0));
+# expect_stdout: 7 7.0 number number
+
+# `/` always gives a float, `//` divides exactly, truncating toward zero
+print(7 / 2, 7 // 2, -7 // 2, 7 % 3, -7 % 3);
+# expect_stdout: 3.5 3 -3 1 -1
+print(repr(6 / 2), repr(7.5 // 2));
+# expect_stdout: 3.0 3.0
+
+# Mixing an integer with a float gives a float
+print(repr(1 + 0.5), repr(2 * 1.0));
+# expect_stdout: 1.5 2.0
+
+# No precision is lost above 2^53
+let id = 9007199254740993;
+print(id + 2, id * 10);
+# expect_stdout: 9007199254740995 90071992547409930
+
+# Comparisons are exact across integers and floats
+print(1 == 1.0, id == 9007199254740992.0, id > 9007199254740992.0, 2 < 2.5);
+# expect_stdout: true false true true
+
+# 1 and 1.0 are the same map key
+let m = {1: "one"};
+m[1.0] = "uno";
+print(len(m), m[1]);
+# expect_stdout: 1 uno
+
+let x = 17;
+x //= 5;
+print(x);
+# expect_stdout: 3
+
+print(int(3.9), int(-3.9), int("42"), repr(float(2)), num("8"), repr(num("8.5")));
+# expect_stdout: 3 -3 42 2.0 8 8.5
+
+let squares = [];
+for i in range(4) {
+    squares.push(i * i);
+}
+squares
+# expect: [0, 1, 4, 9]
@@ -0,0 +1,13 @@
+# `let (a, b) = value` binds the elements of a tuple or list by position.
+
+let (x, y) = (3, 4);
+const (first, second) = ["a", "b"];
+let (only,) = (42,);
+let swap = fun(pair) {
+    let (a, b) = pair;
+    (b, a)
+};
+print(x, y, first, second, only, swap((1, 2)));
+let (q, r) = (7, 8, 9);
+# expect_stdout: 3 4 a b 42 (2, 1)
+# expect_runtime_error: Cannot destructure a tuple of 3 elements into 2 names.
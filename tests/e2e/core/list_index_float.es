@@ -0,0 +1,5 @@
+# List indices must be integers; a float is not truncated.
+
+let items = [10, 20, 30];
+items[1.7]
+# expect_runtime_error: List index must be an integer. Got: 1.7
@@ -0,0 +1,6 @@
+# Negative indices count from the end, for reading and assigning.
+
+let xs = [10, 20, 30];
+xs[-1] = xs[-1] + xs[-3];
+[xs[-1], xs[-2], xs]
+# expect: [40, 20, [10, 20, 40]]
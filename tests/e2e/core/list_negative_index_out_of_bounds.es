@@ -0,0 +1,3 @@
+let xs = [10, 20, 30];
+xs[-4]
+# expect_runtime_error: List index out of bounds: -4
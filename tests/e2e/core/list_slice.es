@@ -0,0 +1,14 @@
+# Slices copy a range of a list: [start:end:step], with any part omitted.
+
+let xs = [0, 1, 2, 3, 4, 5];
+print(xs[1:3], xs[:2], xs[4:], xs[:-1], xs[-2:]);
+print(xs[::2], xs[1::2], xs[::-1], xs[4:1:-1], xs[-1::-2]);
+print(xs[2:100], xs[-100:1], xs[4:2], xs[nil:2]);
+
+let copy = xs[:];
+copy[0] = 99;
+xs[0]
+# expect: 0
+# expect_stdout: [1, 2] [0, 1] [4, 5] [0, 1, 2, 3, 4] [4, 5]
+# expect_stdout: [0, 2, 4] [1, 3, 5] [5, 4, 3, 2, 1, 0] [4, 3, 2] [5, 3, 1]
+# expect_stdout: [2, 3, 4, 5] [0] [] [0, 1]
@@ -0,0 +1,18 @@
+# Assigning to a slice replaces it; a step-1 slice may grow or shrink the list.
+
+let xs = [0, 1, 2, 3, 4];
+xs[1:3] = ["a", "b", "c"];
+print(xs);
+xs[:2] = [];
+print(xs);
+xs[len(xs):] = [5, 6];
+print(xs);
+xs[::2] = [nil, nil, nil];
+print(xs);
+xs[-2:] += ["end"];
+xs
+# expect: [nil, "c", nil, 4, nil, 6, "end"]
+# expect_stdout: [0, "a", "b", "c", 3, 4]
+# expect_stdout: ["b", "c", 3, 4]
+# expect_stdout: ["b", "c", 3, 4, 5, 6]
+# expect_stdout: [nil, "c", nil, 4, nil, 6]
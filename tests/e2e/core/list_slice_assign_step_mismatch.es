@@ -0,0 +1,5 @@
+# An extended slice keeps its length, so it needs exactly one value per element.
+
+let xs = [0, 1, 2, 3, 4];
+xs[::2] = [7, 8];
+# expect_runtime_error: Cannot assign 2 values to a slice with step 2 that selects 3 elements.
@@ -0,0 +1,2 @@
+let data = b"\q";
+# expect_runtime_error: Invalid escape in bytes literal: \q
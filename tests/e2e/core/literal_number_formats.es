@@ -0,0 +1,15 @@
+# Hexadecimal, binary, octal and scientific literals, with `_` between digits.
+
+print(0xFF, 0b1010, 0o17, 0xdead_beef, 1_000_000);
+# expect_stdout: 255 10 15 3735928559 1000000
+print(1.5e-3, 2E3, 1_000.000_5, 0xFFFF_FFFF_FFFF_FFFF);
+# expect_stdout: 0.0015 2000 1000.0005 18446744073709551615
+
+# Very large and very small floats print in a form that reads back as the same number
+print(1e300, 1.5e-7, 0.0001, 10000000000000000.0);
+# expect_stdout: 1e300 1.5e-7 0.0001 1e16
+print(repr(1e300) == "1e300", repr(123.0), repr(-0.00001));
+# expect_stdout: true 123.0 -1e-5
+
+(0x0F << 4) | 0b1111
+# expect: 255
@@ -0,0 +1,4 @@
+# A digit outside the literal's base is a lexer error.
+
+let mask = 0xFG;
+# expect_runtime_error: Invalid digit 'G' in hexadecimal literal: 0xFG
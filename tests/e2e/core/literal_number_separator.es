@@ -0,0 +1,4 @@
+# `_` must sit between two digits.
+
+let million = 1_000_;
+# expect_runtime_error: Misplaced '_' in number literal: 1_000_
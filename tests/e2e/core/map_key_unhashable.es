@@ -0,0 +1,5 @@
+# Only strings, numbers and booleans can be map keys; anything else is a runtime error.
+
+let grid = {};
+grid[[0, 0]] = "origin";
+# expect_runtime_error: Map keys must be strings, numbers, booleans or tuples of these, but got type 'list'.
@@ -0,0 +1,19 @@
+# `in` finds an element in a list, a key in a map, a substring in a string,
+# or a value in a range (without advancing it).
+
+let xs = [1, "two", [3]];
+print(1 in xs, 1.0 in xs, "two" in xs, [3] in xs, 4 in xs);
+
+let ages = {"alice": 30, 1: "one"};
+print("alice" in ages, "bob" in ages, 1 in ages, 30 in ages);
+
+print("ell" in "hello", "" in "hello", "Hello" in "hello");
+
+let r = range(1, 10, 3);
+print(4 in r, 5 in r, 10 in r, 7.0 in r, 3 in range(5, 0, -1), 0.5 in range(0, 1, 0.25));
+r.collect()
+# expect: [1, 4, 7]
+# expect_stdout: true true true true false
+# expect_stdout: true false true false
+# expect_stdout: true true false
+# expect_stdout: true false false true true true
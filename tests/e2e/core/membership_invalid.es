@@ -0,0 +1,4 @@
+# `in` needs something it can search on its right.
+
+3 in 123
+# expect_runtime_error: Operator 'in' expects a list, tuple, map, set, string, bytes or range on the right, but got type 'number'.
@@ -0,0 +1,5 @@
+# Only range() can be searched without consuming it.
+
+let evens = range(10).filter(fun(n) { n % 2 == 0 });
+4 in evens
+# expect_runtime_error: Operator 'in' can only search the iterator returned by range(). Collect other iterators into a list first.
@@ -0,0 +1,5 @@
+# `in` on a map checks its key the same way indexing does.
+
+let seen = {"a": true};
+{} in seen
+# expect_runtime_error: Map keys must be strings, numbers, booleans or tuples of these, but got type 'map'.
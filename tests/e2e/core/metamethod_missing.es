@@ -0,0 +1,6 @@
+# A map without the metamethod still rejects the operator
+
+let Money = {"__add": fun(a, b) { a }};
+let m = set_proto({"cents": 5}, Money);
+m - m
+# expect_runtime_error: Cannot apply operator 'Sub' to unsupported types: map and map
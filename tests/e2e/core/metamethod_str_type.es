@@ -0,0 +1,5 @@
+# __str must return a string
+
+let Bad = {"__str": fun(self) { 42 }};
+print(set_proto({}, Bad))
+# expect_runtime_error: __str() must return a string, but got type 'number'.
@@ -0,0 +1,85 @@
+# Test operator overloading through metamethods on a map's prototype
+
+let Vec2 = {
+    "__add": fun(a, b) { vec2(a.x + b.x, a.y + b.y) },
+    "__sub": fun(a, b) { vec2(a.x - b.x, a.y - b.y) },
+    "__mul": fun(a, k) { vec2(a.x * k, a.y * k) },
+    "__eq": fun(a, b) { a.x == b.x && a.y == b.y },
+    "__lt": fun(a, b) { a.norm2() < b.norm2() },
+    "__str": fun(self) { "(" + str(self.x) + ", " + str(self.y) + ")" },
+    "norm2": fun(self) { self.x * self.x + self.y * self.y }
+};
+let vec2 = fun(x, y) { set_proto({"x": x, "y": y}, Vec2) };
+
+let a = vec2(1, 2);
+let b = vec2(3, 4);
+
+print(a + b);
+# expect_stdout: (4, 6)
+print(b - a);
+# expect_stdout: (2, 2)
+print(a * 3);
+# expect_stdout: (3, 6)
+print(str(a));
+# expect_stdout: (1, 2)
+print([a.x, b.y], a, sep: " ");
+# expect_stdout: [1, 4] (1, 2)
+
+# Compound assignment goes through the same metamethods
+let c = a;
+c += b;
+print(c);
+# expect_stdout: (4, 6)
+
+# __eq and __lt; the other comparisons are derived from them
+print(a + b == vec2(4, 6));
+# expect_stdout: true
+print(a != vec2(1, 2));
+# expect_stdout: false
+print(a < b);
+# expect_stdout: true
+print(a > b);
+# expect_stdout: false
+print(a <= vec2(2, 1));
+# expect_stdout: true
+print(b >= a);
+# expect_stdout: true
+
+# Indexing, length and calls
+let Grid = {
+    "__index": fun(self, pos) { self.cells[pos[0] * self.width + pos[1]] },
+    "__setindex": fun(self, pos, value) { self.cells[pos[0] * self.width + pos[1]] = value; },
+    "__len": fun(self) { self.cells.len() },
+    "__call": fun(self, row, col = 0) { self[[row, col]] }
+};
+let grid = set_proto({"width": 2, "cells": [0, 0, 0, 0]}, Grid);
+grid[[1, 0]] = 7;
+grid[[1, 1]] += 5;
+print(grid[[1, 0]]);
+# expect_stdout: 7
+print(grid.cells);
+# expect_stdout: [0, 0, 7, 5]
+print(len(grid));
+# expect_stdout: 4
+print(grid.len());
+# expect_stdout: 4
+print(grid(1, col: 1));
+# expect_stdout: 5
+
+# Dot access and dot assignment bypass __index and __setindex
+grid.width += 1;
+print(grid.width);
+# expect_stdout: 3
+print(grid.has_key("width"));
+# expect_stdout: true
+
+# Maps without metamethods keep the builtin behavior
+print({"a": 1} == {"a": 1});
+# expect_stdout: true
+print(len({"a": 1}));
+# expect_stdout: 1
+
+# Metamethods come from the prototype only: a map's own entries are plain data
+let plain = {"__len": fun(self) { 99 }, "__index": fun(self, key) { "meta" }};
+print(len(plain), plain["__len"] == nil);
+# expect_stdout: 2 false
@@ -0,0 +1,33 @@
+# `a ?? b` evaluates to `b` only when `a` is nil.
+
+let settings = {"retries": 0, "name": "", "tags": []};
+print(settings.retries ?? 3);
+# expect_stdout: 0
+print(settings.retries || 3);
+# expect_stdout: 3
+print(settings.name ?? "default");
+# expect_stdout: 
+print(settings.tags ?? ["x"]);
+# expect_stdout: []
+print(settings.timeout ?? 30);
+# expect_stdout: 30
+print(nil ?? nil ?? "last");
+# expect_stdout: last
+
+# The right-hand side is only evaluated when needed.
+let fallback = fun() {
+    print("fallback called");
+    1
+};
+print(settings.retries ?? fallback());
+# expect_stdout: 0
+print(settings.timeout ?? fallback());
+# expect_stdout: fallback called
+# expect_stdout: 1
+
+# Combines with optional chaining for nested config lookups.
+let config = {"db": {"port": 5432}};
+print(config.cache?.size ?? 64);
+# expect_stdout: 64
+print(config.db?.port ?? 3306);
+# expect_stdout: 5432
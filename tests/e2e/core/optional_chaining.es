@@ -0,0 +1,33 @@
+# `?.`, `?[` and `?.(` short-circuit to nil when the value on their left is nil.
+
+let config = {"db": {"host": "localhost"}, "tags": ["fast"]};
+print(config?.db?.host);
+# expect_stdout: localhost
+print(config.cache?.size);
+# expect_stdout: nil
+print(config.tags?[0]);
+# expect_stdout: fast
+print(config.cache?["size"]);
+# expect_stdout: nil
+
+# The rest of the chain is skipped too, so plain accesses after `?.` do not fail.
+print(config.cache?.size.limit[0]);
+# expect_stdout: nil
+print(config.cache?.keys());
+# expect_stdout: nil
+
+let double = fun(x) { x * 2 };
+let missing = nil;
+print(double?.(21));
+# expect_stdout: 42
+print(missing?.(21));
+# expect_stdout: nil
+
+# Arguments of a short-circuited call are not evaluated.
+missing?.(print("evaluated"));
+print(config.on_load?.(print("evaluated")));
+# expect_stdout: nil
+
+# Only nil short-circuits; other values are accessed as usual.
+print(config?.tags.len());
+# expect_stdout: 1
@@ -0,0 +1,5 @@
+# An optional chain cannot be assigned to.
+
+let config = {};
+config?.port = 80;
+# expect_runtime_error: Cannot assign to an optional chain ('?.' or '?[').
@@ -0,0 +1,5 @@
+# Optional links only guard against nil; other errors are still reported.
+
+let count = 3;
+count?.value
+# expect_runtime_error: Cannot use dot access on type 'number'. No method 'value' or map key found.
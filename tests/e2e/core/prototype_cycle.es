@@ -0,0 +1,6 @@
+# A prototype chain cannot loop back to the object itself
+
+let a = {};
+let b = set_proto({}, a);
+set_proto(a, b)
+# expect_runtime_error: set_proto() would create a prototype cycle.
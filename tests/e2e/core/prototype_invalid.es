@@ -0,0 +1,4 @@
+# Only maps have prototypes
+
+set_proto({}, [1, 2])
+# expect_runtime_error: set_proto() expected a map or nil as the prototype, but got type 'list'.
@@ -0,0 +1,72 @@
+# Test prototype-based objects: dot calls bind the receiver as `self`
+
+let Animal = {
+    "describe": fun(self) { self.name + " says " + self.sound() },
+    "sound": fun(self) { "..." }
+};
+
+let Dog = set_proto({
+    "sound": fun(self) { "woof" }
+}, Animal);
+
+let rex = set_proto({"name": "Rex"}, Dog);
+let tom = set_proto({"name": "Tom"}, Animal);
+
+# Methods are found along the prototype chain, and `self` is the original receiver
+print(rex.describe());
+# expect_stdout: Rex says woof
+print(tom.describe());
+# expect_stdout: Tom says ...
+
+# Own keys shadow the prototype's
+tom.sound = fun(self) { "meow" };
+print(tom.describe());
+# expect_stdout: Tom says meow
+print(get_proto(rex).has_key("sound"));
+# expect_stdout: true
+
+# Prototype keys are only visible through dot access
+print(rex.len());
+# expect_stdout: 1
+print(rex.has_key("sound"));
+# expect_stdout: false
+print(rex["sound"]);
+# expect_stdout: nil
+print(rex);
+# expect_stdout: {"name": "Rex"}
+
+# Changing the prototype is seen by every instance
+Animal.legs = 4;
+print(rex.legs);
+# expect_stdout: 4
+
+# Methods take further arguments after `self`, including keyword arguments
+let Counter = {
+    "add": fun(self, by = 1) { self.count += by; self }
+};
+let c = set_proto({"count": 0}, Counter);
+c.add().add(by: 10);
+print(c.count);
+# expect_stdout: 11
+
+# A method taken off an object stays bound to it
+let add = c.add;
+add(5);
+print(c.count);
+# expect_stdout: 16
+
+# Index access returns the raw function, so a prototype's method can be called explicitly
+print(Animal["sound"](rex));
+# expect_stdout: ...
+
+# Natives stored on a map are not bound
+let logger = {"log": print};
+logger.log("plain");
+# expect_stdout: plain
+
+# nil removes the prototype
+set_proto(tom, nil);
+print(get_proto(tom));
+# expect_stdout: nil
+print(tom.describe);
+# expect_stdout: nil
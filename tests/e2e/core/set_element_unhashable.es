@@ -0,0 +1,4 @@
+# Set elements are hashed by value, so mutable values cannot be elements.
+
+#{1, [2]}
+# expect_runtime_error: Set elements must be strings, numbers, booleans or tuples of these, but got type 'list'.
@@ -0,0 +1,18 @@
+# Set literals keep one copy of each element, in insertion order; equal numbers are one element.
+
+let s = #{3, 1, "a", 3, 1.0};
+print(s, repr(s), len(s), type(s));
+print(#{}, bool(#{}), bool(#{false}));
+print(1 in s, 2 in s, "a" in s);
+print(#{1, 2} == #{2, 1}, #{1, 2} == #{1, 2, 3}, #{1} == [1]);
+
+let total = 0;
+for item in #{1, 2, 3, 2} {
+    total = total + item;
+}
+print(total);
+# expect_stdout: #{3, 1, "a"} #{3, 1, "a"} 3 set
+# expect_stdout: #{} false true
+# expect_stdout: true false true
+# expect_stdout: true false false
+# expect_stdout: 6
@@ -0,0 +1,4 @@
+# Shift amounts are capped so a huge shift fails instead of exhausting memory
+
+1 << 1000000
+# expect_runtime_error: Shift amount must be at most 65536.
@@ -0,0 +1,9 @@
+# A step larger than the sequence selects only the first position, without overflowing.
+
+let xs = [1, 2, 3];
+print(xs[1::9223372036854775807], xs[::9223372036854775807]);
+print(xs[1::-9223372036854775807], xs[::-9223372036854775808]);
+"abc"[::9223372036854775807] + "abc"[::-9223372036854775807]
+# expect: ac
+# expect_stdout: [2] [1]
+# expect_stdout: [2] [3]
@@ -0,0 +1,3 @@
+let xs = [1, 2, 3];
+xs[::0]
+# expect_runtime_error: Slice step cannot be zero.
@@ -0,0 +1,9 @@
+# Strings are indexed and sliced by character (Unicode scalar value), not by byte.
+
+let word = "héllo, 世界";
+print(word[0], word[1], word[-1], word[-2]);
+print(word[:5], word[7:], word[::-1], word[1::3]);
+len(word[1:2])
+# expect: 1
+# expect_stdout: h é 界 世
+# expect_stdout: héllo 世界 界世 ,olléh éo世
@@ -0,0 +1,2 @@
+"héllo"[5]
+# expect_runtime_error: String index out of bounds: 5
@@ -0,0 +1,3 @@
+let t = (1, 2);
+t[0] = 5;
+# expect_runtime_error: Cannot assign to an element of a tuple. Tuples are immutable.
@@ -0,0 +1,5 @@
+# A tuple is only hashable when all of its elements are.
+
+let seen = {};
+seen[(1, [2])] = true;
+# expect_runtime_error: Map keys must be strings, numbers, booleans or tuples of these, but got a tuple containing type 'list'.
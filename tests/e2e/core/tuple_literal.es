@@ -0,0 +1,17 @@
+# Tuples are written with parentheses and commas: `(x)` is only grouping, while `(x,)` and `()`
+# are tuples of one and zero elements.
+
+let point = (3, -4);
+print(point, repr(("a", 1.0)), (1,), (), (2), type(point));
+print(point[0], point[-1], len(point), (1, 2, 3, 4)[1:3], bool(()), bool((nil,)));
+print((1, 2) == (1, 2), (1, 2) == (2, 1), (1, 2) == [1, 2], 4 in (3, 4));
+
+let sum = 0;
+for n in point {
+    sum = sum + n;
+}
+print(sum);
+# expect_stdout: (3, -4) ("a", 1.0) (1,) () 2 tuple
+# expect_stdout: 3 -4 2 (2, 3) false true
+# expect_stdout: true false false true
+# expect_stdout: -1
@@ -0,0 +1,22 @@
+# Tuples of hashable values can be map keys and set elements, so a grid can be keyed by (x, y).
+
+let grid = {};
+for y in range(2) {
+    for x in range(3) {
+        grid[(x, y)] = x * y;
+    }
+}
+print(grid[(2, 1)], (1, 1) in grid, (5, 5) in grid, len(grid));
+print(make_map([[("a", 1), true]]), #{(1, 2), (1, 2), (2, 1)});
+grid[(0, 1.0)] = "one";
+print(grid[(0, 1)], grid.has_key((0, 0)));
+# expect_stdout: 2 true false 6
+# expect_stdout: {("a", 1): true} #{(1, 2), (2, 1)}
+# expect_stdout: one true
+
+# Map literals take any key expression, tuples included; a block is still a block.
+let walls = {(0, 1): "wall", (2, 2): "door", ("a", (1, 2)): true};
+print(walls[(0, 1)], walls[("a", (1, 2))], len(walls));
+print({1 + 1: "two"}, { (1, 2) });
+# expect_stdout: wall true 3
+# expect_stdout: {2: "two"} (1, 2)
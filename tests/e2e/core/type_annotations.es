@@ -0,0 +1,13 @@
+# Type annotations are only read by `--check`; running a script ignores them, even when the
+# values do not match.
+
+let count: number = 3;
+const name: string = "box";
+let scale = fun(x: number, factor: number = 2) -> number {
+    x * factor
+};
+let mislabeled: string = scale(count);
+let anything: any = nil;
+let nothing = fun() -> nil { };
+print(name, mislabeled, scale(1, factor: 10), anything, nothing());
+# expect_stdout: box 6 10 nil nil
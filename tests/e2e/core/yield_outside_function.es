@@ -0,0 +1,3 @@
+# `yield` is only allowed inside a function body.
+yield 1;
+# expect_runtime_error: 'yield' can only be used inside a function body.
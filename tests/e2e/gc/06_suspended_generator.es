@@ -0,0 +1,20 @@
+# Variables of a suspended generator stay reachable through the generator.
+let make = fun() {
+    let data = [1, 2, 3];
+    yield 0;
+    yield data;
+};
+
+let g = make();
+g.next();
+gc_collect();
+print(g.next());
+# expect_stdout: [1, 2, 3]
+
+# An abandoned generator is collected once its suspended body has been unwound.
+let abandoned = make();
+abandoned.next();
+abandoned = nil;
+gc_collect();
+print("ok");
+# expect_stdout: ok
@@ -0,0 +1,15 @@
+# Spawned tasks and the channels they wait on are GC roots, even anonymous ones.
+let ch = channel();
+spawn(fun() {
+    let data = [1, 2, 3];
+    let got = ch.recv();
+    print(data);
+    print(got);
+});
+yield_now();
+gc_collect();
+ch.send("go");
+print("sent");
+# expect_stdout: sent
+# expect_stdout: [1, 2, 3]
+# expect_stdout: go
@@ -0,0 +1,22 @@
+# Test that a prototype reachable only through an instance survives collection,
+# and that a bound user method keeps its function alive.
+
+let make_point = fun(x) {
+    let Point = {"double": fun(self) { self.x * 2 }};
+    set_proto({"x": x}, Point)
+};
+
+let p = make_point(21);
+let q = make_point(1);
+let double = q.double;
+# Only the bound method still refers to q's prototype function.
+set_proto(q, nil);
+
+gc_collect();
+
+print(p.double());
+# expect_stdout: 42
+print(double());
+# expect_stdout: 2
+
+# expect: nil
@@ -0,0 +1,18 @@
+# Test that the elements of a reachable set survive collection, including ones
+# built at runtime that nothing else refers to.
+
+let make_words = fun(n) {
+    let words = set();
+    for i in range(n) {
+        words.add("word" + str(i));
+    }
+    words
+};
+
+let words = make_words(3);
+gc_collect();
+
+print(words, words.contains("word2"));
+# expect_stdout: #{"word0", "word1", "word2"} true
+
+# expect: nil
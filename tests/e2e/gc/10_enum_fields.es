@@ -0,0 +1,25 @@
+# Test that the fields of a reachable enum value survive collection, and that a match
+# subject stays alive while its arms run.
+
+enum Node { Leaf(value), Pair(left, right) };
+
+let make_tree = fun() {
+    Node.Pair(Node.Leaf([1, 2]), Node.Leaf("x" + str(3)))
+};
+
+let tree = make_tree();
+gc_collect();
+print(tree);
+
+let total = match make_tree() {
+    Node.Pair(Node.Leaf(items), _) => {
+        gc_collect();
+        items[0] + items[1]
+    },
+    _ => 0,
+};
+print(total);
+# expect_stdout: Node.Pair(Node.Leaf([1, 2]), Node.Leaf("x3"))
+# expect_stdout: 3
+
+# expect: nil
@@ -0,0 +1,36 @@
+# A generator abandoned at a `yield` is cancelled once a collection finds it unreachable:
+# the `yield` fails, so the body unwinds and its pending `defer`s run.
+let make = fun(name) {
+    let data = [1, 2];
+    defer print("cleanup " + name + " " + str(data));
+    yield 1;
+    print("never resumed");
+    yield 2;
+};
+
+let g = make("g");
+print(g.next());
+g = nil;
+gc_collect();
+print("after collection");
+# expect_stdout: 1
+# expect_stdout: cleanup g [1, 2]
+# expect_stdout: after collection
+
+# A generator still reachable is left alone.
+let kept = make("kept");
+kept.next();
+gc_collect();
+print("kept alive");
+# expect_stdout: kept alive
+
+# Generators and tasks still suspended when the program ends are cancelled too.
+let ch = channel();
+spawn(fun() {
+    defer print("task cleanup");
+    ch.recv();
+});
+print("end of main");
+# expect_stdout: end of main
+# expect_stdout: cleanup kept [1, 2]
+# expect_stdout: task cleanup
@@ -0,0 +1,44 @@
+# Builtin methods that call back into script keep their receiver, the items they work on
+# and the results built so far alive while the callback runs a collection.
+
+print([1, 2, 3].map(fun(x) { gc_collect(); [x, x] }));
+# expect_stdout: [[1, 1], [2, 2], [3, 3]]
+
+print([[1], [2], [3]].filter(fun(x) { gc_collect(); x[0] != 2 }));
+# expect_stdout: [[1], [3]]
+
+print([1, 2, 3].reduce(fun(acc, x) { gc_collect(); acc + [x] }, []));
+# expect_stdout: [1, 2, 3]
+
+print([[3], [1], [2]].sort_by(fun(x) { gc_collect(); x[0] }));
+# expect_stdout: [[1], [2], [3]]
+
+print({"a": 1, "b": 2}.map_values(fun(v) { gc_collect(); [v] }));
+# expect_stdout: {"a": [1], "b": [2]}
+
+print({"a": [1], "b": [2]}.filter(fun(k, v) { gc_collect(); k == "b" }));
+# expect_stdout: {"b": [2]}
+
+print([[1], [2]].find(fun(x) { gc_collect(); x[0] == 2 }));
+# expect_stdout: [2]
+
+# The items are a snapshot: a callback that removes them from the list does not free them.
+let items = [[1], [2], [3]];
+print(items.map(fun(x) { items.pop(); gc_collect(); x }));
+# expect_stdout: [[1], [2], [3]]
+
+# Collecting an iterator keeps the items gathered so far.
+print(range(3).map(fun(x) { gc_collect(); [x] }).collect());
+# expect_stdout: [[0], [1], [2]]
+
+# A task that blocks inside a callback keeps its roots while other code collects.
+let ch = channel();
+spawn(fun() { print([1, 2].map(fun(x) { [x, ch.recv()] })); });
+yield_now();
+print([[5]].map(fun(x) { gc_collect(); x }));
+ch.send("a");
+yield_now();
+gc_collect();
+ch.send("b");
+# expect_stdout: [[5]]
+# expect_stdout: [[1, "a"], [2, "b"]]
@@ -38,7 +38,9 @@ fn parse_test_file(source: &str) -> (String, Expectation) {
 
         // Check for stdout expectation comment (can be on the same line or different)
         if let Some((code_part, comment_part)) = current_code_part.split_once("# expect_stdout:") {
-            stdout_expectations.push(comment_part.trim().to_string());
+            // Only the space after the colon is dropped, so indented output can be expected.
+            let expected = comment_part.strip_prefix(' ').unwrap_or(comment_part);
+            stdout_expectations.push(expected.trim_end().to_string());
             current_code_part = code_part;
         }
 
@@ -165,7 +167,7 @@ fn run_test_file(path: PathBuf) {
     // 3. Interpreter
     // Capture stdout during interpretation
     let mut buf = BufferRedirect::stdout().unwrap();
-    let mut interpreter = Interpreter::new();
+    let interpreter = Interpreter::new();
     let result = interpreter.run(&ast);
 
     // Read captured stdout
@@ -211,6 +213,14 @@ fn run_test_file(path: PathBuf) {
                 // EasyScriptError's Display trait outputs the message prefixed with type and location.
                 // We need to check if the contained message matches.
                 if e.to_string().contains(&expected_err_msg) {
+                    // Output printed before the error (or while it unwound) is checked too.
+                    if let Some(expected_stdout) = expectation.stdout {
+                        assert_eq!(
+                            captured_stdout, expected_stdout,
+                            "Stdout expectation mismatch for {:?}!",
+                            path
+                        );
+                    }
                     println!("   PASS (Runtime Error): {:?}", path.display());
                 } else {
                     panic!(